@@ -0,0 +1,150 @@
+use std::hint::black_box;
+
+use chip8_core::{BoolArrayScreen, Emulator, PackedBitScreen, QuirksConfig, ScreenBuffer, roms};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+// nothing ticks the timers here, so DXYN must not wait for vblank
+const BENCH_QUIRKS: QuirksConfig = QuirksConfig {
+    display_wait: false,
+    ..QuirksConfig::cosmac_vip()
+};
+
+fn emulator_with(rom: &[u16]) -> Emulator {
+    emulator_with_screen(BoolArrayScreen::default(), rom)
+}
+
+fn emulator_with_screen<S: ScreenBuffer>(screen: S, rom: &[u16]) -> Emulator<S> {
+    let bytes: Vec<u8> = rom.iter().flat_map(|op| op.to_be_bytes()).collect();
+    let mut chip8 = Emulator::with_screen(screen);
+    chip8.set_quirks(BENCH_QUIRKS);
+    chip8.load_rom(&bytes).unwrap();
+    chip8
+}
+
+fn bench_tight_loop(c: &mut Criterion) {
+    let mut chip8 = Emulator::new_with_quirks(BENCH_QUIRKS);
+    chip8.load_rom(roms::COUNTER_ROM).unwrap();
+    c.bench_function("tick tight loop", |b| b.iter(|| black_box(chip8.tick())));
+}
+
+fn bench_opcode_families(c: &mut Criterion) {
+    // each opcode is followed by a NOP and a jump back to the start, so a
+    // taken skip lands on the jump and an untaken one runs the NOP first.
+    let families: [(&str, u16); 17] = [
+        ("00E0 CLS", 0x00E0),
+        ("3XNN SE", 0x3000),
+        ("4XNN SNE", 0x4000),
+        ("5XY0 SE", 0x5010),
+        ("6XNN LD", 0x6042),
+        ("7XNN ADD", 0x7001),
+        ("8XY4 ADD", 0x8014),
+        ("8XY6 SHR", 0x8016),
+        ("9XY0 SNE", 0x9010),
+        ("ANNN LD I", 0xA000),
+        ("CXNN RND", 0xC0FF),
+        ("DXYN DRW", 0xD005),
+        ("FX1E ADD I", 0xF01E),
+        ("FX29 LD F", 0xF029),
+        ("FX33 BCD", 0xF033),
+        ("FX55 LD [I]", 0xF355),
+        ("FX65 LD Vx", 0xF365),
+    ];
+
+    let mut group = c.benchmark_group("opcode family");
+    for (name, op) in families {
+        let mut chip8 = emulator_with(&[0xA300, op, 0x0000, 0x1202]);
+        group.bench_function(name, |b| b.iter(|| black_box(chip8.tick())));
+    }
+
+    // CALL 0x206; JP 0x200; NOP; RET
+    let mut chip8 = emulator_with(&[0x2206, 0x1200, 0x0000, 0x00EE]);
+    group.bench_function("2NNN/00EE CALL+RET", |b| b.iter(|| black_box(chip8.tick())));
+    group.finish();
+}
+
+fn bench_max_sprite(c: &mut Criterion) {
+    // I = 0; DRW V0, V1, 15; JP 0x202
+    let rom = [0xA000, 0xD01F, 0x1202];
+    let mut group = c.benchmark_group("DXYN 8x15 sprite");
+    let mut bools = emulator_with_screen(BoolArrayScreen::default(), &rom);
+    group.bench_function("BoolArrayScreen", |b| b.iter(|| black_box(bools.tick())));
+    let mut packed = emulator_with_screen(PackedBitScreen::default(), &rom);
+    group.bench_function("PackedBitScreen", |b| b.iter(|| black_box(packed.tick())));
+    group.finish();
+}
+
+fn bench_snapshot(c: &mut Criterion) {
+    // the counter never writes to RAM, so every snapshot has an empty RAM diff
+    let mut chip8 = Emulator::new_with_quirks(BENCH_QUIRKS);
+    chip8.load_rom(roms::COUNTER_ROM).unwrap();
+    chip8.checkpoint();
+    c.bench_function("tick + take_snapshot", |b| {
+        b.iter(|| {
+            chip8.tick();
+            black_box(chip8.take_snapshot())
+        })
+    });
+}
+
+fn bench_mixed_opcodes(c: &mut Criterion) {
+    // a hot loop touching most opcode families, used to compare the match
+    // dispatch against the "dispatch-table" feature.
+    let rom = [
+        0x6005, // V0 = 5
+        0x7101, // V1 += 1
+        0x8014, // V0 += V1
+        0x8126, // V1 = V2 >> 1
+        0xA300, // I = 0x300
+        0xF01E, // I += V0
+        0xC2FF, // V2 = rand()
+        0x3203, // SE V2, 3
+        0x4104, // SNE V1, 4
+        0x9010, // SNE V0, V1
+        0xF233, // BCD V2
+        0xF165, // LD V0..V1, [I]
+        0xF015, // DT = V0
+        0xF307, // V3 = DT
+        0x2220, // CALL 0x220
+        0x1200, // JP 0x200
+        0x00EE, // RET
+    ];
+    c.bench_function("tick_n(10000) mixed opcodes", |b| {
+        b.iter(|| {
+            let mut chip8 = emulator_with(&rom);
+            black_box(chip8.tick_n(10000))
+        })
+    });
+}
+
+fn bench_draw_program(c: &mut Criterion) {
+    // Draws every font digit across the screen, clears it and starts over.
+    let rom = [
+        0x00E0, // CLS
+        0x6000, // V0 = 0 (digit)
+        0x6100, // V1 = 0 (x)
+        0xF029, // I = FONT(V0)
+        0xD125, // DRW V1, V2, 5
+        0x7105, // V1 += 5
+        0x7001, // V0 += 1
+        0x3010, // SE V0, 16
+        0x1206, // JP 0x206
+        0x1200, // JP 0x200
+    ];
+    c.bench_function("tick_n(10000) draw program", |b| {
+        b.iter(|| {
+            let mut chip8 = emulator_with(&rom);
+            black_box(chip8.tick_n(10000))
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_tight_loop,
+    bench_opcode_families,
+    bench_max_sprite,
+    bench_draw_program,
+    bench_mixed_opcodes,
+    bench_snapshot
+);
+criterion_main!(benches);
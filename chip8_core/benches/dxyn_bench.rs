@@ -0,0 +1,116 @@
+//! DXYN and CLS throughput, in pixels per second.
+//!
+//! After a normal `cargo bench` run each result is checked against
+//! `benches/config.json`, and the run fails if any falls more than
+//! `tolerance` below its target.
+
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::process;
+
+use chip8_core::{Emulator, QuirksConfig, SCREEN_HEIGHT, SCREEN_WIDTH};
+use criterion::{Criterion, Throughput};
+use serde_json::Value;
+
+const GROUP: &str = "dxyn";
+
+// nothing ticks the timers here, so DXYN must not wait for vblank
+const BENCH_QUIRKS: QuirksConfig = QuirksConfig {
+    display_wait: false,
+    ..QuirksConfig::cosmac_vip()
+};
+
+/// An emulator about to run `op` at 0x206, followed by a jump back to it.
+fn draw_loop(x: u8, y: u8, op: u16) -> Emulator {
+    // V0 = x; V1 = y; I = 0x000 (font data); op; JP 0x206
+    let rom = [0x6000 | x as u16, 0x6100 | y as u16, 0xA000, op, 0x1206];
+    let bytes: Vec<u8> = rom.iter().flat_map(|op| op.to_be_bytes()).collect();
+    let mut chip8 = Emulator::new_with_quirks(BENCH_QUIRKS);
+    chip8.load_rom(&bytes).unwrap();
+    chip8.tick_n(3);
+    chip8
+}
+
+fn bench_dxyn(c: &mut Criterion) {
+    // (name, x, y, rows, pixels actually drawn after clipping)
+    let sprites: [(&str, u8, u8, u16, u64); 4] = [
+        ("1x8 at 0,0", 0, 0, 1, 8),
+        ("8x15 at 0,0", 0, 0, 15, 120),
+        ("1x8 at 56,24", 56, 24, 1, 8),
+        // rows 32 and up are clipped
+        ("8x15 at 56,24", 56, 24, 15, 64),
+    ];
+
+    let mut group = c.benchmark_group(GROUP);
+    for (name, x, y, rows, pixels) in sprites {
+        let mut chip8 = draw_loop(x, y, 0xD010 | rows);
+        group.throughput(Throughput::Elements(pixels));
+        // the jump back is timed too, it is cheap next to the draw
+        group.bench_function(name, |b| b.iter(|| black_box(chip8.tick_n(2))));
+    }
+
+    let mut chip8 = draw_loop(0, 0, 0x00E0);
+    group.throughput(Throughput::Elements((SCREEN_WIDTH * SCREEN_HEIGHT) as u64));
+    group.bench_function("CLS", |b| b.iter(|| black_box(chip8.tick_n(2))));
+    group.finish();
+}
+
+fn criterion_dir() -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"));
+    target.join("criterion")
+}
+
+fn read_json(path: PathBuf) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+// mean pixels per second from criterion's results for one benchmark
+fn measured_throughput(name: &str) -> Option<f64> {
+    let dir = criterion_dir().join(GROUP).join(name).join("new");
+    let pixels = read_json(dir.join("benchmark.json"))?["throughput"]["Elements"].as_f64()?;
+    let mean_ns = read_json(dir.join("estimates.json"))?["mean"]["point_estimate"].as_f64()?;
+    Some(pixels / (mean_ns / 1e9))
+}
+
+/// Returns the benchmarks that fell short of their target.
+fn check_targets() -> Vec<String> {
+    let config: Value = serde_json::from_str(include_str!("config.json"))
+        .expect("benches/config.json is not valid JSON");
+    let tolerance = config["tolerance"].as_f64().unwrap_or(0.1);
+    let mut failures = Vec::new();
+    for (name, target) in config["targets"].as_object().into_iter().flatten() {
+        let expected = target.as_f64().unwrap_or(0.0);
+        // benchmarks filtered out of this run have nothing to check
+        let Some(measured) = measured_throughput(name) else {
+            continue;
+        };
+        let floor = expected * (1.0 - tolerance);
+        println!(
+            "{:<16} {:>14.0} px/s (target {:.0}, floor {:.0})",
+            name, measured, expected, floor
+        );
+        if measured < floor {
+            failures.push(name.clone());
+        }
+    }
+    failures
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    bench_dxyn(&mut criterion);
+    criterion.final_summary();
+
+    // --test and --list runs do not measure anything
+    if std::env::args().any(|arg| arg == "--test" || arg == "--list") {
+        return;
+    }
+    let failures = check_targets();
+    if !failures.is_empty() {
+        eprintln!("Throughput regressed: {}", failures.join(", "));
+        process::exit(1);
+    }
+}
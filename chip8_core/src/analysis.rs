@@ -0,0 +1,344 @@
+//! Static checks that run on ROM bytes without executing them.
+//! Addresses assume the ROM is loaded at 0x200, except for `disassemble_chip8x`.
+
+use std::collections::BTreeSet;
+use std::ops::Index;
+
+use crate::{CHIP8X_START_ADDR, Instruction, MAX_ROM_SIZE, RAM_SIZE, START_ADDR};
+
+/// One line of `disassemble` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub address: u16,
+    pub opcode: u16,
+    /// The instruction's `Display` text, e.g. `LD V3, 0x42`.
+    pub mnemonic: String,
+    /// `L_XXXX` when a JP or CALL in the ROM targets this address.
+    pub label: Option<String>,
+}
+
+/// Decodes the ROM two bytes at a time from its first byte. Sprite data is
+/// decoded too, so expect some nonsense instructions and `DB` lines in it.
+/// A trailing odd byte is listed as `DB`.
+pub fn disassemble(rom: &[u8]) -> Vec<DisassembledInstruction> {
+    disassemble_from(rom, START_ADDR, Instruction::decode)
+}
+
+/// `disassemble` for CHIP-8X programs, loaded at `CHIP8X_START_ADDR` and
+/// decoded with `Instruction::decode_chip8x`.
+pub fn disassemble_chip8x(rom: &[u8]) -> Vec<DisassembledInstruction> {
+    disassemble_from(rom, CHIP8X_START_ADDR, Instruction::decode_chip8x)
+}
+
+fn disassemble_from(
+    rom: &[u8],
+    start: u16,
+    decode: fn(u16) -> Instruction,
+) -> Vec<DisassembledInstruction> {
+    let rom = &rom[..rom.len().min(RAM_SIZE - start as usize)];
+    let rom_end = start as usize + rom.len();
+    let words: Vec<(u16, u16)> = rom
+        .chunks(2)
+        .enumerate()
+        .map(|(i, word)| {
+            let op = u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+            (start + 2 * i as u16, op)
+        })
+        .collect();
+
+    let targets: BTreeSet<u16> = words
+        .iter()
+        .filter_map(|(_, op)| match decode(*op) {
+            Instruction::Jump(target) | Instruction::Call(target) => Some(target),
+            _ => None,
+        })
+        .filter(|target| (start as usize..rom_end).contains(&(*target as usize)))
+        .collect();
+
+    words
+        .into_iter()
+        .enumerate()
+        .map(|(i, (address, op))| {
+            let odd_tail = 2 * i + 1 == rom.len();
+            DisassembledInstruction {
+                address,
+                opcode: op,
+                mnemonic: if odd_tail {
+                    format!("DB {:#04X}", op >> 8)
+                } else {
+                    decode(op).to_string()
+                },
+                label: targets
+                    .contains(&address)
+                    .then(|| format!("L_{:04X}", address)),
+            }
+        })
+        .collect()
+}
+
+/// One line of a `DisassemblyListing`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassemblyLine {
+    pub address: u16,
+    pub opcode: u16,
+    pub instruction: Instruction,
+    /// The instruction's `Display` text, e.g. `LD V3, 0x42`.
+    pub mnemonic: String,
+}
+
+/// `disassemble` output with the decoded instructions, for searching and
+/// iterating. Lines are in address order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DisassemblyListing(Vec<DisassemblyLine>);
+
+impl DisassemblyListing {
+    /// Disassembles `rom` the same way as `disassemble`, without the labels.
+    pub fn new(rom: &[u8]) -> Self {
+        let odd_tail = rom.len() % 2 == 1 && rom.len() <= MAX_ROM_SIZE;
+        let mut lines: Vec<DisassemblyLine> = disassemble(rom)
+            .into_iter()
+            .map(|line| DisassemblyLine {
+                address: line.address,
+                opcode: line.opcode,
+                instruction: Instruction::decode(line.opcode),
+                mnemonic: line.mnemonic,
+            })
+            .collect();
+        // the `DB` for a trailing odd byte isn't the opcode it was padded to
+        if let Some(last) = lines.last_mut().filter(|_| odd_tail) {
+            last.instruction = Instruction::Unknown(last.opcode);
+        }
+        DisassemblyListing(lines)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, DisassemblyLine> {
+        self.0.iter()
+    }
+
+    /// The line at `address`, None if it isn't the start of a line.
+    pub fn get(&self, address: u16) -> Option<&DisassemblyLine> {
+        self.0
+            .binary_search_by_key(&address, |line| line.address)
+            .ok()
+            .map(|i| &self.0[i])
+    }
+
+    /// Lines whose mnemonic contains `pattern`, e.g. `"LD I,"`.
+    pub fn find_by_mnemonic(&self, pattern: &str) -> Vec<&DisassemblyLine> {
+        self.0
+            .iter()
+            .filter(|line| line.mnemonic.contains(pattern))
+            .collect()
+    }
+}
+
+/// Panics if no line starts at the address, see `get`.
+impl Index<u16> for DisassemblyListing {
+    type Output = DisassemblyLine;
+
+    fn index(&self, address: u16) -> &DisassemblyLine {
+        self.get(address)
+            .unwrap_or_else(|| panic!("no disassembly line at {:#05X}", address))
+    }
+}
+
+impl<'a> IntoIterator for &'a DisassemblyListing {
+    type Item = &'a DisassemblyLine;
+    type IntoIter = std::slice::Iter<'a, DisassemblyLine>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Finds the targets of backward `JP NNN` instructions, which is where loops start.
+/// A self-jump (`JP A` at A) counts, and so does the jump back in `JP B` / `JP A`,
+/// since the jump at B is a back-edge. Most games loop forever, so these are
+/// hints for reading a ROM rather than bugs.
+///
+/// Only even offsets are decoded, so code that starts on an odd address is missed.
+/// Returns each header once, in ascending order.
+pub fn detect_infinite_loops(rom: &[u8]) -> Vec<u16> {
+    // anything past the end of RAM could never run
+    let rom = &rom[..rom.len().min(MAX_ROM_SIZE)];
+    let rom_end = START_ADDR as usize + rom.len();
+    let mut headers: Vec<u16> = rom
+        .chunks_exact(2)
+        .enumerate()
+        .filter_map(|(i, word)| {
+            let addr = START_ADDR + 2 * i as u16;
+            match Instruction::decode(u16::from_be_bytes([word[0], word[1]])) {
+                Instruction::Jump(target)
+                    if target <= addr
+                        && (START_ADDR as usize..rom_end).contains(&(target as usize)) =>
+                {
+                    Some(target)
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    headers.sort_unstable();
+    headers.dedup();
+    headers
+}
+
+/// Addresses of the instructions that can run, found by following jumps,
+/// calls and both sides of every skip from 0x200. Whatever is never reached
+/// is taken to be data. Execution is assumed to stop at RET, 00FD, BNNN
+/// (its target depends on V0) and unknown opcodes, and where it would leave
+/// the ROM.
+pub fn get_reachable_addresses(rom: &[u8]) -> BTreeSet<u16> {
+    let rom = &rom[..rom.len().min(MAX_ROM_SIZE)];
+    // an instruction needs both of its bytes inside the ROM
+    let in_rom = |addr: u16| {
+        rom.len() >= 2
+            && (START_ADDR as usize..=START_ADDR as usize + rom.len() - 2)
+                .contains(&(addr as usize))
+    };
+    let mut reachable = BTreeSet::new();
+    let mut pending = vec![START_ADDR];
+    while let Some(addr) = pending.pop() {
+        if !in_rom(addr) || !reachable.insert(addr) {
+            continue;
+        }
+        let offset = (addr - START_ADDR) as usize;
+        let next = addr + 2;
+        match Instruction::decode(u16::from_be_bytes([rom[offset], rom[offset + 1]])) {
+            Instruction::Jump(target) => pending.push(target),
+            Instruction::Call(target) => pending.extend([target, next]),
+            Instruction::SkipEqByte { .. }
+            | Instruction::SkipNeByte { .. }
+            | Instruction::SkipEqReg { .. }
+            | Instruction::SkipNeReg { .. }
+            | Instruction::SkipKeyPressed { .. }
+            | Instruction::SkipKeyNotPressed { .. } => pending.extend([next, next + 2]),
+            Instruction::Ret
+            | Instruction::Exit
+            | Instruction::JumpV0(_)
+            | Instruction::Unknown(_) => (),
+            _ => pending.push(next),
+        }
+    }
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listing_finds_every_load_i() {
+        let rom = [
+            0xA2, 0x0A, // LD I, 0x20A
+            0x60, 0x05, // LD V0, 0x05
+            0xD0, 0x05, // DRW V0, V0, 5
+            0xA2, 0x0F, // LD I, 0x20F
+            0x12, 0x08, // JP 0x208
+            0xF0, // DB 0xF0
+        ];
+        let listing = DisassemblyListing::new(&rom);
+        assert_eq!(listing.len(), 6);
+
+        let loads = listing.find_by_mnemonic("LD I,");
+        assert_eq!(loads.len(), 2);
+        assert_eq!(loads[1].address, 0x206);
+        assert_eq!(loads[1].instruction, Instruction::LoadI(0x20F));
+
+        assert_eq!(listing[0x208].mnemonic, "JP 0x208");
+        assert_eq!(listing[0x20A].instruction, Instruction::Unknown(0xF000));
+        assert_eq!(listing.get(0x201), None);
+        let draws = listing
+            .into_iter()
+            .filter(|line| matches!(line.instruction, Instruction::Draw { .. }))
+            .count();
+        assert_eq!(draws, 1);
+    }
+
+    #[test]
+    fn reachable_leaves_out_data() {
+        let rom = [
+            0x22, 0x06, // CALL 0x206
+            0x12, 0x00, // JP 0x200
+            0xFF, 0xFF, // sprite data
+            0xA2, 0x04, // LD I, 0x204
+            0x00, 0xEE, // RET
+        ];
+        let reachable: Vec<u16> = get_reachable_addresses(&rom).into_iter().collect();
+        assert_eq!(reachable, [0x200, 0x202, 0x206, 0x208]);
+    }
+
+    #[test]
+    fn reachable_follows_both_sides_of_a_skip() {
+        let rom = [
+            0x30, 0x01, // SE V0, 0x01
+            0x12, 0x08, // JP 0x208
+            0x00, 0xFD, // EXIT
+            0xFF, 0xFF, // data
+            0x12, 0x08, // JP 0x208
+        ];
+        let reachable: Vec<u16> = get_reachable_addresses(&rom).into_iter().collect();
+        assert_eq!(reachable, [0x200, 0x202, 0x204, 0x208]);
+    }
+
+    #[test]
+    fn self_jump_is_a_loop() {
+        let rom = [
+            0x12, 0x00, // JP 0x200
+        ];
+        assert_eq!(detect_infinite_loops(&rom), [0x200]);
+    }
+
+    #[test]
+    fn back_edge_is_a_loop() {
+        let rom = [
+            0x60, 0x00, // LD V0, 0x00
+            0x70, 0x01, // ADD V0, 0x01
+            0x12, 0x02, // JP 0x202
+            0x12, 0x08, // JP 0x208, forward
+            0x00, 0xEE, // RET
+        ];
+        assert_eq!(detect_infinite_loops(&rom), [0x202]);
+    }
+
+    #[test]
+    fn jump_pair_is_a_loop() {
+        let rom = [
+            0x12, 0x04, // JP 0x204
+            0x00, 0xE0, // CLS
+            0x12, 0x00, // JP 0x200
+        ];
+        assert_eq!(detect_infinite_loops(&rom), [0x200]);
+    }
+
+    #[test]
+    fn chip8x_disassembly_uses_its_opcodes_and_load_address() {
+        let rom = [
+            0x02, 0xA0, // BGC
+            0x51, 0x21, // ADDN V1, V2
+            0xB1, 0x24, // COL V1, V2, 4
+            0x13, 0x00, // JP 0x300
+        ];
+        let lines = disassemble_chip8x(&rom);
+        let mnemonics: Vec<&str> = lines.iter().map(|line| line.mnemonic.as_str()).collect();
+        assert_eq!(
+            mnemonics,
+            ["BGC", "ADDN V1, V2", "COL V1, V2, 4", "JP 0x300"]
+        );
+        assert_eq!(lines[0].address, 0x300);
+        assert_eq!(lines[0].label.as_deref(), Some("L_0300"));
+
+        let lines = disassemble(&rom);
+        assert_eq!(lines[0].mnemonic, "DB 0x02A0");
+        assert_eq!(lines[2].mnemonic, "JP V0, 0x124");
+        assert_eq!(lines[0].label, None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+use tokio::time::{Interval, interval};
+
+use crate::{ChipError, Emulator, EmulatorBuilder, KEY_COUNT, ThreadConfig, TickResult};
+
+const TIMER_PERIOD: Duration = Duration::from_micros(16_667);
+
+/// An emulator paced by `tokio::time::interval`, for frontends running on an
+/// async runtime. Takes the same configuration as `Emulator::run_in_thread`.
+pub struct AsyncEmulator {
+    chip8: Emulator,
+    clock: Interval,
+    last_timer_tick: Instant,
+    screen_tx: watch::Sender<Vec<bool>>,
+}
+
+impl AsyncEmulator {
+    /// Must be called from within a Tokio runtime.
+    pub fn new(config: ThreadConfig) -> Result<Self, ChipError> {
+        let mut builder = EmulatorBuilder::new().quirks(config.quirks);
+        if let Some(seed) = config.rng_seed {
+            builder = builder.rng_seed(seed);
+        }
+        let mut chip8 = builder.build();
+        chip8.load_rom_checked(&config.rom)?;
+
+        let cycle = Duration::from_secs_f64(1.0 / config.clock_hz.max(1) as f64);
+        let (screen_tx, _) = watch::channel(chip8.get_display().to_vec());
+
+        Ok(AsyncEmulator {
+            chip8,
+            clock: interval(cycle),
+            last_timer_tick: Instant::now(),
+            screen_tx,
+        })
+    }
+
+    /// Waits for the next CPU cycle and executes one instruction.
+    pub async fn tick(&mut self) -> TickResult {
+        self.clock.tick().await;
+
+        let result = self.chip8.tick();
+        if result == TickResult::Draw {
+            self.screen_tx
+                .send_replace(self.chip8.get_display().to_vec());
+        }
+
+        if self.last_timer_tick.elapsed() >= TIMER_PERIOD {
+            self.chip8.tick_timers();
+            self.last_timer_tick = Instant::now();
+        }
+
+        result
+    }
+
+    /// Ticks until the screen changes. Also returns if the emulator halts or errors.
+    pub async fn wait_for_draw(&mut self) -> &[bool] {
+        loop {
+            match self.tick().await {
+                TickResult::Draw | TickResult::Halted | TickResult::Error(_) => break,
+                _ => (),
+            }
+        }
+        self.chip8.get_display()
+    }
+
+    /// Ticks until the program blocks in FX0A. Also returns if the emulator halts or errors.
+    pub async fn wait_for_key_wait(&mut self) {
+        loop {
+            match self.tick().await {
+                TickResult::KeyWait | TickResult::Halted | TickResult::Error(_) => break,
+                _ => (),
+            }
+        }
+    }
+
+    /// Receives a copy of the screen every time it changes.
+    pub fn subscribe(&self) -> watch::Receiver<Vec<bool>> {
+        self.screen_tx.subscribe()
+    }
+
+    /// Keys outside 0..16 are ignored.
+    pub fn keypress(&mut self, idx: usize, pressed: bool) {
+        if idx < KEY_COUNT {
+            self.chip8.keypress(idx, pressed);
+        }
+    }
+
+    pub fn emulator(&self) -> &Emulator {
+        &self.chip8
+    }
+
+    pub fn emulator_mut(&mut self) -> &mut Emulator {
+        &mut self.chip8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuirksConfig;
+
+    #[tokio::test]
+    async fn runs_and_takes_keys() {
+        // LD V0, K; LD F, V0; DRW V1, V1, 5; JP 0x200
+        let mut emu = AsyncEmulator::new(ThreadConfig {
+            clock_hz: 10_000,
+            rom: vec![0xF0, 0x0A, 0xF0, 0x29, 0xD1, 0x15, 0x12, 0x00],
+            quirks: QuirksConfig::chip48(),
+            rng_seed: None,
+        })
+        .unwrap();
+        let mut screens = emu.subscribe();
+
+        emu.wait_for_key_wait().await;
+        emu.keypress(0x5, true);
+        emu.keypress(KEY_COUNT, true);
+        assert_eq!(emu.tick().await, TickResult::KeyWait);
+        emu.keypress(0x5, false);
+
+        let display = emu.wait_for_draw().await.to_vec();
+        assert_eq!(emu.emulator().get_registers()[0], 0x5);
+        // the top row of the 5 glyph
+        assert_eq!(&display[..5], &[true, true, true, true, false]);
+        assert!(screens.has_changed().unwrap());
+        assert_eq!(*screens.borrow_and_update(), display);
+
+        for _ in 0..100 {
+            assert!(!matches!(emu.tick().await, TickResult::Error(_)));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Prints what is known about a ROM without running it.
+//!
+//! Usage: chip8-info path/to/rom [--loops] [--json] [--check-compatibility VARIANT]
+//!
+//! Opcodes are only counted at addresses `get_reachable_addresses` finds,
+//! so sprite data doesn't show up as instructions. The recommended quirks
+//! come from the catalog when the ROM is in it, otherwise from the variant
+//! its reachable opcodes look like. VARIANT is one of chip8, superchip,
+//! xochip or eti660.
+
+use std::env;
+use std::fs;
+use std::process;
+
+use chip8_core::analysis::{detect_infinite_loops, get_reachable_addresses};
+use chip8_core::{Instruction, QuirksConfig, RomInfo, START_ADDR, Variant, lookup_rom};
+
+// opcode families by their first nibble
+const FAMILIES: [&str; 16] = [
+    "0NNN", "1NNN", "2NNN", "3XNN", "4XNN", "5XY0", "6XNN", "7XNN", "8XYN", "9XY0", "ANNN", "BNNN",
+    "CXNN", "DXYN", "EXNN", "FXNN",
+];
+
+fn main() {
+    let mut path = None;
+    let mut loops = false;
+    let mut json = false;
+    let mut check = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--loops" => loops = true,
+            "--json" => json = true,
+            "--check-compatibility" => match args.next().as_deref().map(parse_variant) {
+                Some(Some(variant)) => check = Some(variant),
+                _ => {
+                    eprintln!("--check-compatibility needs chip8, superchip, xochip or eti660");
+                    process::exit(2);
+                }
+            },
+            _ => path = Some(arg),
+        }
+    }
+    let Some(path) = path else {
+        eprintln!(
+            "Usage: chip8-info path/to/rom [--loops] [--json] [--check-compatibility VARIANT]"
+        );
+        process::exit(2);
+    };
+
+    let rom = match fs::read(&path) {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("Unable to read {}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    let info = RomInfo::from_rom(&rom);
+    let entry = lookup_rom(&info.sha256);
+    let catalog = entry.map(|e| format!("{} by {} ({})", e.title, e.author, e.year));
+    let sha: String = info.sha256.iter().map(|b| format!("{:02x}", b)).collect();
+    let code: Vec<(u16, u16)> = get_reachable_addresses(&rom)
+        .into_iter()
+        .map(|addr| {
+            let offset = (addr - START_ADDR) as usize;
+            (addr, u16::from_be_bytes([rom[offset], rom[offset + 1]]))
+        })
+        .collect();
+    let mut family_counts = [0usize; 16];
+    for (_, op) in &code {
+        family_counts[(op >> 12) as usize] += 1;
+    }
+    let code_bytes: Vec<u8> = code.iter().flat_map(|(_, op)| op.to_be_bytes()).collect();
+    let variant = Variant::detect(&code_bytes);
+    let (quirks_name, quirks) = match entry {
+        Some(entry) => (format!("{} in the catalog", entry.title), entry.quirks),
+        None => recommended_quirks(variant),
+    };
+    let unsupported: Vec<(u16, u16)> = match check {
+        Some(target) => code
+            .iter()
+            .copied()
+            .filter(|(_, op)| !target.supports_opcode(*op))
+            .collect(),
+        None => Vec::new(),
+    };
+    let loop_headers = if loops {
+        detect_infinite_loops(&rom)
+    } else {
+        Vec::new()
+    };
+
+    if json {
+        let families: serde_json::Map<String, serde_json::Value> = FAMILIES
+            .iter()
+            .zip(family_counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| (name.to_string(), count.into()))
+            .collect();
+        let mut report = serde_json::json!({
+            "size": info.size,
+            "sha256": sha,
+            "catalog": catalog,
+            "variant": format!("{:?}", variant),
+            "recommended_quirks": { "for": quirks_name, "quirks": quirks.to_string() },
+            "opcode_count": code.len(),
+            "opcode_families": families,
+        });
+        if let Some(target) = check {
+            report["compatibility"] = serde_json::json!({
+                "variant": format!("{:?}", target),
+                "unsupported": unsupported
+                    .iter()
+                    .map(|(addr, op)| serde_json::json!({ "address": addr, "opcode": op }))
+                    .collect::<Vec<_>>(),
+            });
+        }
+        if loops {
+            report["loops"] = loop_headers.into();
+        }
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("reports always serialize")
+        );
+        return;
+    }
+
+    println!("size:    {} bytes", info.size);
+    println!("sha256:  {}", sha);
+    println!("catalog: {}", catalog.as_deref().unwrap_or("not found"));
+    println!("variant: {:?}", variant);
+    println!("quirks:  {} ({})", quirks_name, quirks);
+    println!("opcodes: {} reachable", code.len());
+    for (name, count) in FAMILIES.iter().zip(family_counts) {
+        if count > 0 {
+            println!("  {}  {}", name, count);
+        }
+    }
+
+    if let Some(target) = check {
+        println!("unsupported by {:?}: {}", target, unsupported.len());
+        for (addr, op) in unsupported {
+            println!("  {:#05X}  {:04X}  {}", addr, op, Instruction::decode(op));
+        }
+    }
+
+    if loops {
+        println!("loops:   {}", loop_headers.len());
+        for addr in loop_headers {
+            println!("  {:#05X}", addr);
+        }
+    }
+}
+
+fn parse_variant(name: &str) -> Option<Variant> {
+    match name.to_ascii_lowercase().as_str() {
+        "chip8" | "chip-8" => Some(Variant::Chip8),
+        "superchip" | "super-chip" | "schip" => Some(Variant::SuperChip),
+        "xochip" | "xo-chip" => Some(Variant::XoChip),
+        "eti660" | "eti-660" => Some(Variant::Eti660),
+        _ => None,
+    }
+}
+
+fn recommended_quirks(variant: Variant) -> (String, QuirksConfig) {
+    let name = match variant {
+        Variant::Chip8 | Variant::Eti660 => "COSMAC VIP",
+        Variant::SuperChip => "SUPER-CHIP",
+        Variant::XoChip => "XO-CHIP",
+    };
+    (name.to_string(), variant.quirks())
+}
@@ -0,0 +1,90 @@
+//! Runs a ROM headless and shows where the time went, per opcode family.
+//!
+//! Usage: chip8-profile path/to/rom [--cycles N] [--quirks LIST]
+//!
+//! Families are sorted by total time, most expensive first. Timers tick once
+//! every `REPLAY_TICKS_PER_FRAME` cycles, as in chip8-run.
+
+use std::env;
+use std::process;
+
+use chip8_core::{Emulator, QuirksConfig, REPLAY_TICKS_PER_FRAME, TickResult};
+
+const DEFAULT_CYCLES: u64 = 100_000;
+const FAMILIES: [&str; 16] = [
+    "0NNN", "1NNN", "2NNN", "3XNN", "4XNN", "5XY0", "6XNN", "7XNN", "8XYN", "9XY0", "ANNN", "BNNN",
+    "CXNN", "DXYN", "EXNN", "FXNN",
+];
+
+fn main() {
+    let mut path = None;
+    let mut cycles = DEFAULT_CYCLES;
+    let mut quirks = QuirksConfig::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cycles" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => cycles = n,
+                None => {
+                    eprintln!("--cycles needs a number");
+                    process::exit(2);
+                }
+            },
+            "--quirks" => match args.next().map(|q| q.parse::<QuirksConfig>()) {
+                Some(Ok(parsed)) => quirks = parsed,
+                Some(Err(err)) => {
+                    eprintln!("--quirks: {}", err);
+                    process::exit(2);
+                }
+                None => {
+                    eprintln!("--quirks needs a list like chip48,clip-sprites");
+                    process::exit(2);
+                }
+            },
+            _ => path = Some(arg),
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("Usage: chip8-profile path/to/rom [--cycles N] [--quirks LIST]");
+        process::exit(2);
+    };
+
+    let mut chip8 = Emulator::new_with_quirks(quirks);
+    if let Err(err) = chip8.load_rom_from_file(&path) {
+        eprintln!("Unable to load {}: {}", path, err);
+        process::exit(1);
+    }
+
+    for tick in 1..=cycles {
+        match chip8.tick() {
+            TickResult::Error(err) => {
+                eprintln!("Stopped after {} cycles: {}", tick - 1, err);
+                break;
+            }
+            TickResult::Halted => break,
+            _ => (),
+        }
+        if tick.is_multiple_of(REPLAY_TICKS_PER_FRAME as u64) {
+            chip8.tick_timers();
+        }
+    }
+
+    let timings = chip8.get_opcode_timings();
+    let counts = chip8.get_opcode_counts();
+    let mut families: Vec<usize> = (0..16).filter(|&f| counts[f] > 0).collect();
+    families.sort_by_key(|&f| std::cmp::Reverse(timings[f]));
+
+    println!(
+        "{:<8} {:>12} {:>14} {:>10}",
+        "family", "calls", "total ns", "avg ns"
+    );
+    for f in families {
+        println!(
+            "{:<8} {:>12} {:>14} {:>10.1}",
+            FAMILIES[f],
+            counts[f],
+            timings[f],
+            timings[f] as f64 / counts[f] as f64
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Runs a ROM without a window and reports where it ended up.
+//!
+//! Usage: chip8-run path/to/rom [--headless] [--cycles N] [--duration SECS] [--cycles-per-second N]
+//!                  [--json-report] [--flip-heatmap OUT.png] [--quirks LIST] [--repl]
+//!
+//! There is no windowed mode here, `--headless` is accepted so scripts can be
+//! explicit about it. Timers tick once every `REPLAY_TICKS_PER_FRAME` cycles.
+//!
+//! The run stops after `--cycles` or `--duration`, whichever comes first, and
+//! defaults to `DEFAULT_CYCLES` when neither is given. The exit status is 3 if
+//! the achieved speed fell more than 10% short of `--cycles-per-second`, so
+//! throughput checks can be scripted.
+//!
+//! `--repl` loads the ROM and hands it to the step debugger in
+//! `chip8_core::repl` instead, reading commands from stdin.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chip8_core::repl::Repl;
+use chip8_core::{
+    Emulator, ExecutionSummary, QuirksConfig, REPLAY_TICKS_PER_FRAME, SCREEN_HEIGHT, SCREEN_WIDTH,
+    TickResult,
+};
+
+const DEFAULT_CYCLES: u64 = 10_000;
+const DEFAULT_CYCLES_PER_SECOND: u64 = 500;
+const MAX_CYCLES_PER_SECOND: u64 = 1_000_000;
+// above this the gap between cycles is shorter than sleep can reliably hit, so spin instead
+const MAX_SLEEPING_CYCLES_PER_SECOND: u64 = 10_000;
+// achieved speeds below this fraction of the target fail the run
+const MIN_SPEED_RATIO: f64 = 0.9;
+// each CHIP-8 pixel becomes a HEATMAP_SCALE x HEATMAP_SCALE block in the image
+const HEATMAP_SCALE: usize = 8;
+
+fn main() {
+    let mut path = None;
+    let mut cycles = None;
+    let mut duration = None;
+    let mut cycles_per_second = DEFAULT_CYCLES_PER_SECOND;
+    let mut json = false;
+    let mut heatmap = None;
+    let mut repl = false;
+    let mut quirks = QuirksConfig::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => (),
+            "--json-report" => json = true,
+            "--repl" => repl = true,
+            "--quirks" => match args.next().map(|q| q.parse::<QuirksConfig>()) {
+                Some(Ok(parsed)) => quirks = parsed,
+                Some(Err(err)) => {
+                    eprintln!("--quirks: {}", err);
+                    process::exit(2);
+                }
+                None => {
+                    eprintln!("--quirks needs a list like chip48,clip-sprites");
+                    process::exit(2);
+                }
+            },
+            "--flip-heatmap" => match args.next() {
+                Some(out) => heatmap = Some(out),
+                None => {
+                    eprintln!("--flip-heatmap needs an output path");
+                    process::exit(2);
+                }
+            },
+            "--cycles" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => cycles = Some(n),
+                None => {
+                    eprintln!("--cycles needs a number");
+                    process::exit(2);
+                }
+            },
+            "--duration" => match args.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(secs) if secs > 0.0 => duration = Some(Duration::from_secs_f64(secs)),
+                _ => {
+                    eprintln!("--duration needs a positive number of seconds");
+                    process::exit(2);
+                }
+            },
+            "--cycles-per-second" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=MAX_CYCLES_PER_SECOND) => cycles_per_second = n,
+                _ => {
+                    eprintln!(
+                        "--cycles-per-second needs a number from 1 to {}",
+                        MAX_CYCLES_PER_SECOND
+                    );
+                    process::exit(2);
+                }
+            },
+            _ => path = Some(arg),
+        }
+    }
+    let Some(path) = path else {
+        eprintln!(
+            "Usage: chip8-run path/to/rom [--headless] [--cycles N] [--duration SECS] [--cycles-per-second N]"
+        );
+        eprintln!(
+            "                 [--json-report] [--flip-heatmap OUT.png] [--quirks LIST] [--repl]"
+        );
+        process::exit(2);
+    };
+
+    let mut chip8 = Emulator::new_with_quirks(quirks);
+    let rom_len = match chip8.load_rom_from_file(&path) {
+        Ok(info) => info.size,
+        Err(err) => {
+            eprintln!("Unable to load {}: {}", path, err);
+            process::exit(1);
+        }
+    };
+
+    if repl {
+        let mut session = Repl::new(chip8);
+        if let Err(err) = session.run(io::stdin().lock(), io::stdout()) {
+            eprintln!("REPL stopped: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if cycles.is_none() && duration.is_none() {
+        cycles = Some(DEFAULT_CYCLES);
+    }
+
+    let mut error = None;
+    let pacing = Pacing::new(Instant::now(), cycles_per_second);
+    for tick in 1.. {
+        if run_is_over(tick, pacing.start.elapsed(), cycles, duration) {
+            break;
+        }
+        match chip8.tick() {
+            TickResult::Error(err) => {
+                error = Some(err);
+                break;
+            }
+            TickResult::Halted => break,
+            _ => (),
+        }
+        if tick.is_multiple_of(REPLAY_TICKS_PER_FRAME as u64) {
+            chip8.tick_timers();
+        }
+        wait_until(pacing.due(tick), pacing.sleeps());
+    }
+
+    let summary = chip8.execution_summary();
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&summary).expect("summaries always serialize")
+        );
+    } else {
+        print_report(&summary);
+        println!("target c/s:   {}", cycles_per_second);
+        println!(
+            "coverage:     {:.1}%",
+            chip8.get_coverage_percentage(rom_len)
+        );
+    }
+
+    if let Some(out) = heatmap
+        && let Err(err) = write_heatmap(&out, chip8.get_pixel_flip_counts())
+    {
+        eprintln!("Unable to write {}: {}", out, err);
+        process::exit(1);
+    }
+
+    if let Some(err) = error {
+        eprintln!("Stopped with an error: {}", err);
+        process::exit(1);
+    }
+    if summary.cycles_per_second < cycles_per_second as f64 * MIN_SPEED_RATIO {
+        eprintln!(
+            "Only reached {:.0} of the requested {} cycles/s",
+            summary.cycles_per_second, cycles_per_second
+        );
+        process::exit(3);
+    }
+}
+
+/// When each cycle should finish to hold a steady rate.
+struct Pacing {
+    start: Instant,
+    cycles_per_second: u64,
+}
+
+impl Pacing {
+    fn new(start: Instant, cycles_per_second: u64) -> Self {
+        Pacing {
+            start,
+            cycles_per_second,
+        }
+    }
+
+    // pacing against the start rather than the previous cycle keeps errors from adding up
+    fn due(&self, tick: u64) -> Instant {
+        self.start + Duration::from_secs_f64(tick as f64 / self.cycles_per_second as f64)
+    }
+
+    fn sleeps(&self) -> bool {
+        self.cycles_per_second <= MAX_SLEEPING_CYCLES_PER_SECOND
+    }
+}
+
+// `tick` is the cycle about to run, counting from 1
+fn run_is_over(
+    tick: u64,
+    elapsed: Duration,
+    cycles: Option<u64>,
+    duration: Option<Duration>,
+) -> bool {
+    cycles.is_some_and(|limit| tick > limit) || duration.is_some_and(|limit| elapsed >= limit)
+}
+
+fn wait_until(deadline: Instant, sleep: bool) {
+    let now = Instant::now();
+    if now >= deadline {
+        return;
+    }
+    if sleep {
+        thread::sleep(deadline - now);
+    } else {
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+fn print_report(summary: &ExecutionSummary) {
+    println!("ticks:        {}", summary.total_ticks);
+    println!("cycles:       {}", summary.total_cycles);
+    println!("wall clock:   {:.3} s", summary.wall_clock_secs);
+    println!("cycles/s:     {:.0}", summary.cycles_per_second);
+    println!("timer ticks:  {}", summary.timer_ticks);
+    println!("key events:   {}", summary.key_events);
+    println!("draw calls:   {}", summary.draw_calls);
+    println!("final PC:     {:#05X}", summary.final_pc);
+    println!("I:            {:#05X}", summary.i_reg);
+    for (half, regs) in summary.registers.chunks(8).enumerate() {
+        let line: Vec<String> = regs
+            .iter()
+            .enumerate()
+            .map(|(i, v)| format!("V{:X}: {:02X}", half * 8 + i, v))
+            .collect();
+        println!("{}", line.join("  "));
+    }
+    println!("{}", summary.screen);
+}
+
+/// Writes a grayscale PNG where brighter pixels flipped more often.
+fn write_heatmap(path: &str, counts: &[u32]) -> Result<(), png::EncodingError> {
+    let max = counts.iter().copied().max().unwrap_or(0).max(1) as u64;
+    let width = SCREEN_WIDTH * HEATMAP_SCALE;
+    let height = SCREEN_HEIGHT * HEATMAP_SCALE;
+    let pixels: Vec<u8> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x / HEATMAP_SCALE, y / HEATMAP_SCALE)))
+        .map(|(x, y)| (counts[x + SCREEN_WIDTH * y] as u64 * 255 / max) as u8)
+        .collect();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_are_due_at_even_steps_from_the_start() {
+        let start = Instant::now();
+        let pacing = Pacing::new(start, 500);
+        assert_eq!(pacing.due(1), start + Duration::from_millis(2));
+        assert_eq!(pacing.due(500), start + Duration::from_secs(1));
+        assert_eq!(pacing.due(750) - pacing.due(250), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn fast_rates_spin_instead_of_sleeping() {
+        let start = Instant::now();
+        assert!(Pacing::new(start, DEFAULT_CYCLES_PER_SECOND).sleeps());
+        assert!(Pacing::new(start, MAX_SLEEPING_CYCLES_PER_SECOND).sleeps());
+        assert!(!Pacing::new(start, MAX_SLEEPING_CYCLES_PER_SECOND + 1).sleeps());
+    }
+
+    #[test]
+    fn stops_at_whichever_limit_comes_first() {
+        let second = Duration::from_secs(1);
+        assert!(!run_is_over(10, second, Some(10), None));
+        assert!(run_is_over(11, second, Some(10), None));
+        assert!(run_is_over(1, second, None, Some(second)));
+        assert!(run_is_over(1, second, Some(10), Some(second)));
+        assert!(!run_is_over(1_000_000, second, None, Some(2 * second)));
+    }
+
+    #[test]
+    fn waiting_paces_a_run() {
+        let pacing = Pacing::new(Instant::now(), 1000);
+        for tick in 1..=20 {
+            wait_until(pacing.due(tick), pacing.sleeps());
+        }
+        assert!(pacing.start.elapsed() >= Duration::from_millis(20));
+    }
+}
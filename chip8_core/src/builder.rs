@@ -0,0 +1,215 @@
+use std::cell::Cell;
+
+use crate::{
+    BoolArrayScreen, COLOR_RAM_SIZE, Chip8Mode, ChipError, CowRam, DEFAULT_KEY_REPEAT_DELAY_MS,
+    DEFAULT_KEY_REPEAT_INTERVAL_MS, Emulator, KEY_COUNT, LARGE_FONT_START, LARGE_FONTSET_SIZE,
+    QuirksConfig, RAM_SIZE, REGISTER_COUNT, RPL_FLAG_COUNT, RngSource, RunCounters, STACK_SIZE,
+    START_ADDR, ScreenBuffer,
+};
+use crate::{chip8x, rng};
+
+/// Configures an `Emulator` before construction.
+/// `Emulator::new()` is equivalent to `EmulatorBuilder::new().build()`.
+pub struct EmulatorBuilder {
+    stack_size: usize,
+    quirks: QuirksConfig,
+    rng_seed: Option<u64>,
+    rng: Option<Box<dyn RngSource>>,
+    start_addr: u16,
+    mode: Chip8Mode,
+}
+
+impl Default for EmulatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EmulatorBuilder {
+    pub fn new() -> Self {
+        EmulatorBuilder {
+            stack_size: STACK_SIZE,
+            quirks: QuirksConfig::default(),
+            rng_seed: None,
+            rng: None,
+            start_addr: START_ADDR,
+            mode: Chip8Mode::Standard,
+        }
+    }
+
+    /// Maximum call depth. Defaults to 16, matching the COSMAC VIP.
+    pub fn stack_size(mut self, n: usize) -> Self {
+        self.stack_size = n;
+        self
+    }
+
+    pub fn quirks(mut self, quirks: QuirksConfig) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Instruction set to run, plain CHIP-8 unless set.
+    pub fn mode(mut self, mode: Chip8Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Seeds the CXNN random number generator. Unseeded emulators use OS entropy.
+    pub fn rng_seed(mut self, seed: u64) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    /// Draws CXNN's random bytes from `rng`. Takes precedence over `rng_seed`.
+    pub fn rng(mut self, rng: impl RngSource + 'static) -> Self {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    /// Address ROMs are loaded at and execution starts from. Defaults to 0x200,
+    /// the ETI-660 uses 0x600 (see `Variant::start_address`). Fails with
+    /// `InvalidStartAddress` if `addr` is odd, overlaps the fonts or is
+    /// outside RAM.
+    pub fn start_address(mut self, addr: u16) -> Result<Self, ChipError> {
+        let fonts_end = LARGE_FONT_START as usize + LARGE_FONTSET_SIZE;
+        if !addr.is_multiple_of(2) || !(fonts_end..RAM_SIZE).contains(&(addr as usize)) {
+            return Err(ChipError::InvalidStartAddress(addr));
+        }
+        self.start_addr = addr;
+        Ok(self)
+    }
+
+    pub fn build(self) -> Emulator {
+        self.build_with_screen(BoolArrayScreen::default())
+    }
+
+    /// Builds an emulator that draws into `screen` instead of the default `BoolArrayScreen`.
+    pub fn build_with_screen<S: ScreenBuffer>(self, screen: S) -> Emulator<S> {
+        let mut new_emulator = Emulator {
+            pc: self.start_addr,
+            ram: CowRam::default(),
+            screen,
+            v_reg: [0; REGISTER_COUNT],
+            i_reg: 0,
+            sp: 0,
+            stack: vec![0; self.stack_size],
+            keys: [false; KEY_COUNT],
+            keys_just_pressed: [false; KEY_COUNT],
+            keys_just_released: [false; KEY_COUNT],
+            key_repeat_delay_ms: DEFAULT_KEY_REPEAT_DELAY_MS,
+            key_repeat_interval_ms: DEFAULT_KEY_REPEAT_INTERVAL_MS,
+            #[cfg(feature = "std")]
+            key_hold_start: [None; KEY_COUNT],
+            #[cfg(feature = "std")]
+            key_repeats_fired: [0; KEY_COUNT],
+            dt: 0,
+            st: 0,
+            waiting_for_vblank: false,
+            collision_count: 0,
+            screen_hash: Cell::new(None),
+            screen_dirty: true,
+            mode: self.mode,
+            rpl_flags: [0; RPL_FLAG_COUNT],
+            color_ram: [chip8x::DEFAULT_ZONE_COLOR; COLOR_RAM_SIZE],
+            background_color: chip8x::DEFAULT_BACKGROUND,
+            foreground_color: None,
+            waiting_for_key_release: None,
+            waiting_key_register: None,
+            mmio: Vec::new(),
+            mmio_output: Vec::new(),
+            quirks: self.quirks,
+            loaded_rom: Vec::new(),
+            rom_crc32: 0,
+            start_addr: self.start_addr,
+            warnings: Vec::new(),
+            events: Vec::new(),
+            elapsed_cycles: 0,
+            cycle_accurate_timing: false,
+            watchdog_limit: None,
+            halted: false,
+            breakpoints: Vec::new(),
+            resuming_from_breakpoint: false,
+            rng: match (self.rng, self.rng_seed) {
+                (Some(rng), _) => rng,
+                (None, Some(seed)) => rng::seeded_rng(seed),
+                (None, None) => rng::entropy_rng(),
+            },
+            timer_accumulator_ms: 0,
+            checkpoint: None,
+            counters: RunCounters::default(),
+            #[cfg(feature = "history")]
+            history: None,
+            #[cfg(feature = "mem-log")]
+            mem_log: None,
+            #[cfg(feature = "trace")]
+            trace: None,
+            #[cfg(feature = "profiling")]
+            pixel_flip_count: Box::new([0; crate::HIRES_WIDTH * crate::HIRES_HEIGHT]),
+            #[cfg(feature = "profiling")]
+            opcode_calls: [0; 16],
+            #[cfg(feature = "profiling")]
+            opcode_time_ns: [0; 16],
+            #[cfg(feature = "profiling")]
+            pc_coverage: bitvec::bitvec![0; RAM_SIZE / 2],
+            #[cfg(feature = "hooks")]
+            pre_hooks: Vec::new(),
+            #[cfg(feature = "hooks")]
+            post_hooks: Vec::new(),
+        };
+
+        new_emulator.load_fonts();
+        new_emulator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TickResult;
+
+    #[test]
+    fn start_address_moves_the_first_fetch() {
+        let mut chip8 = EmulatorBuilder::new().start_address(0x600).unwrap().build();
+        assert_eq!(chip8.start_address(), 0x600);
+        // LD V3, 0x0F
+        chip8.load_rom(&[0x63, 0x0F]).unwrap();
+        assert_eq!(chip8.get_pc(), 0x600);
+
+        let step = chip8.step().unwrap();
+        assert_eq!(step.pc_before, 0x600);
+        assert_eq!(step.opcode, 0x630F);
+        assert_eq!(chip8.get_registers()[3], 0x0F);
+        assert_eq!(chip8.get_pc(), 0x602);
+    }
+
+    #[test]
+    fn start_address_is_validated() {
+        // odd, inside the large font that ends at 240, and outside RAM
+        for addr in [0x601, 0x000, 0x0EE, 0x1000] {
+            assert_eq!(
+                EmulatorBuilder::new().start_address(addr).err(),
+                Some(ChipError::InvalidStartAddress(addr))
+            );
+        }
+        assert!(EmulatorBuilder::new().start_address(0x0F0).is_ok());
+    }
+
+    #[test]
+    fn stack_size_sets_the_call_depth() {
+        // every instruction calls the next one
+        let rom: Vec<u8> = (1..=33u16)
+            .flat_map(|n| (0x2000 | (START_ADDR + 2 * n)).to_be_bytes())
+            .collect();
+        let mut chip8 = EmulatorBuilder::new().stack_size(32).build();
+        chip8.load_rom(&rom).unwrap();
+
+        for _ in 0..32 {
+            assert_eq!(chip8.tick(), TickResult::Ran);
+        }
+        assert_eq!(chip8.get_sp(), 32);
+        match chip8.tick() {
+            TickResult::Error(err) => assert_eq!(err.inner(), &ChipError::StackOverflow),
+            other => panic!("expected a stack overflow, got {:?}", other),
+        }
+    }
+}
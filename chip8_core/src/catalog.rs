@@ -0,0 +1,93 @@
+use sha2::{Digest, Sha256};
+
+use crate::{DEFAULT_QUIRKS, Emulator, QuirksConfig, ScreenBuffer, Variant};
+
+/// What is known about a ROM, matched by the SHA-256 of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomEntry {
+    pub sha256: [u8; 32],
+    pub title: &'static str,
+    pub author: &'static str,
+    pub year: u16,
+    pub variant: Variant,
+    /// Quirks the ROM was written against.
+    pub quirks: QuirksConfig,
+    pub description: &'static str,
+}
+
+/// ROMs recognised by `identify_rom` and `RomInfo::from_rom`.
+///
+/// Only add entries hashed from the actual file, a guessed hash never matches anything.
+pub const ROM_CATALOG: &[RomEntry] = &[
+    RomEntry {
+        sha256: [
+            0x8D, 0xF7, 0xB1, 0x61, 0x2A, 0xC5, 0x92, 0xCA, 0xBB, 0xFA, 0x68, 0xDA, 0x4E, 0xDE,
+            0x64, 0x22, 0xA3, 0x9C, 0x69, 0xB1, 0xCC, 0x81, 0x5B, 0x4D, 0xD1, 0xB2, 0xF2, 0x03,
+            0x3D, 0x80, 0xF8, 0x6B,
+        ],
+        title: "Counter",
+        author: "chip8_core",
+        year: 2026,
+        variant: Variant::Chip8,
+        quirks: DEFAULT_QUIRKS,
+        description: "roms::COUNTER_ROM, counts up in V0 forever",
+    },
+    RomEntry {
+        sha256: [
+            0x57, 0xB2, 0x59, 0x38, 0x32, 0xFB, 0x24, 0xA8, 0x85, 0xCD, 0x0B, 0x3D, 0xE0, 0x4D,
+            0x3F, 0x73, 0x1F, 0xC8, 0xC4, 0x85, 0x03, 0x7A, 0x55, 0x78, 0x48, 0xCF, 0x21, 0x36,
+            0x3B, 0x6C, 0x2D, 0xE0,
+        ],
+        title: "Draw",
+        author: "chip8_core",
+        year: 2026,
+        variant: Variant::Chip8,
+        quirks: DEFAULT_QUIRKS,
+        description: "roms::DRAW_ROM, cycles through the font digits",
+    },
+];
+
+/// The catalog entry with this hash, if any.
+pub fn lookup_rom(sha256: &[u8; 32]) -> Option<&'static RomEntry> {
+    ROM_CATALOG.iter().find(|entry| entry.sha256 == *sha256)
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Looks the loaded ROM up in `ROM_CATALOG`. The hash is taken from the
+    /// ROM as it was loaded, so ROMs that modify themselves are recognised too.
+    pub fn identify_rom(&self) -> Option<&'static RomEntry> {
+        lookup_rom(&Sha256::digest(&self.loaded_rom).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roms::{COUNTER_ROM, DRAW_ROM};
+
+    #[test]
+    fn bundled_roms_match_their_entries() {
+        for (rom, title) in [(COUNTER_ROM, "Counter"), (DRAW_ROM, "Draw")] {
+            let entry =
+                lookup_rom(&Sha256::digest(rom).into()).expect("bundled ROM in the catalog");
+            assert_eq!(entry.title, title);
+
+            let mut chip8 = Emulator::new();
+            chip8.load_rom(rom).unwrap();
+            assert_eq!(chip8.identify_rom(), Some(entry));
+        }
+    }
+
+    #[test]
+    fn hashes_are_unique() {
+        for (i, entry) in ROM_CATALOG.iter().enumerate() {
+            assert!(
+                ROM_CATALOG[i + 1..]
+                    .iter()
+                    .all(|other| other.sha256 != entry.sha256),
+                "{} is listed twice",
+                entry.title
+            );
+        }
+    }
+}
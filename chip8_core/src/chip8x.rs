@@ -0,0 +1,299 @@
+//! CHIP-8X, the colour extension RCA shipped for the COSMAC VIP with the
+//! VP-590 colour board. Enabled by `QuirksConfig::chip8x_enabled`. CHIP-8X
+//! programs start at `CHIP8X_START_ADDR`, see `EmulatorBuilder::start_address`.
+//!
+//! The pixels themselves stay monochrome. Lit pixels take the colour of
+//! their zone from color RAM, or the foreground colour set by 02A2, 02A4 or
+//! 02A6 until the next BXYN. Unlit pixels show the background colour.
+//!
+//! Color RAM layout: 256 bytes mapped at `COLOR_RAM_START` (0x2C0..0x3C0).
+//! The storage is separate from the 4 KB of RAM, as on the VP-590: while
+//! CHIP-8X is enabled, opcodes that read or write memory through I see
+//! color RAM in that range, but instruction fetches still see RAM. Programs
+//! loaded at 0x300 should keep their sprites at 0x3C0 and above.
+//!
+//! The screen is split into 8x1 pixel zones, 8 per row and 32 rows. Byte
+//! `row * 8 + column`, at address `0x2C0 + row * 8 + column`, colours pixels
+//! `column * 8 .. column * 8 + 8` of screen row `row`. Only the low 3 bits
+//! are used, see `CHIP8X_COLORS`.
+
+use crate::{
+    ChipError, Emulator, Instruction, SCREEN_HEIGHT, SCREEN_WIDTH, ScreenBuffer, TickResult,
+};
+
+/// Where CHIP-8X programs are loaded, above the interpreter's extra code.
+pub const CHIP8X_START_ADDR: u16 = 0x300;
+
+pub const COLOR_RAM_SIZE: usize = 256;
+/// Where color RAM appears to opcodes that access memory, see the module docs.
+pub const COLOR_RAM_START: u16 = 0x2C0;
+/// Width of a color RAM zone in pixels, zones are one pixel tall.
+pub const COLOR_ZONE_WIDTH: usize = 8;
+const ZONES_PER_ROW: usize = SCREEN_WIDTH / COLOR_ZONE_WIDTH;
+
+/// RGB for each 3 bit colour code: black, red, blue, violet, green, yellow,
+/// aqua and white.
+pub const CHIP8X_COLORS: [(u8, u8, u8); 8] = [
+    (0x00, 0x00, 0x00),
+    (0xFF, 0x00, 0x00),
+    (0x00, 0x00, 0xFF),
+    (0xFF, 0x00, 0xFF),
+    (0x00, 0xFF, 0x00),
+    (0xFF, 0xFF, 0x00),
+    (0x00, 0xFF, 0xFF),
+    (0xFF, 0xFF, 0xFF),
+];
+
+// 02A0 steps through these colour codes: blue, black, green, red
+const BACKGROUND_CYCLE: [u8; 4] = [2, 0, 4, 1];
+/// Every zone starts out red.
+pub(crate) const DEFAULT_ZONE_COLOR: u8 = 1;
+/// The background starts out blue.
+pub(crate) const DEFAULT_BACKGROUND: u8 = BACKGROUND_CYCLE[0];
+
+impl<S: ScreenBuffer> Emulator<S> {
+    pub fn get_color_ram(&self) -> &[u8; COLOR_RAM_SIZE] {
+        &self.color_ram
+    }
+
+    /// Colour code of the background, an index into `CHIP8X_COLORS`.
+    pub fn get_background_color(&self) -> u8 {
+        self.background_color
+    }
+
+    /// Colour code every lit pixel shows instead of its zone's, set by
+    /// 02A2, 02A4 and 02A6 and cleared by BXYN.
+    pub fn get_foreground_color(&self) -> Option<u8> {
+        self.foreground_color
+    }
+
+    /// Colour code shown at a pixel: the foreground or its zone's colour
+    /// when lit, the background otherwise. None when the pixel is off the screen.
+    pub fn get_pixel_color(&self, x: usize, y: usize) -> Option<u8> {
+        let lit = self.get_pixel(x, y)?;
+        if !lit {
+            return Some(self.background_color);
+        }
+        let zone = (y * ZONES_PER_ROW + x / COLOR_ZONE_WIDTH) % COLOR_RAM_SIZE;
+        Some(self.foreground_color.unwrap_or(self.color_ram[zone] & 0x7))
+    }
+
+    /// Decodes `op` the way this emulator runs it, with the CHIP-8X opcodes
+    /// while they are enabled.
+    pub fn decode_opcode(&self, op: u16) -> Instruction {
+        if self.quirks.chip8x_enabled {
+            Instruction::decode_chip8x(op)
+        } else {
+            Instruction::decode(op)
+        }
+    }
+
+    // index into color RAM when `addr` is mapped to it, see the module docs
+    pub(crate) fn color_ram_index(&self, addr: u16) -> Option<usize> {
+        let index = addr.checked_sub(COLOR_RAM_START)? as usize;
+        (self.quirks.chip8x_enabled && index < COLOR_RAM_SIZE).then_some(index)
+    }
+
+    // 02A0 - next background colour
+    pub(crate) fn op_cycle_background(&mut self) -> Result<TickResult, ChipError> {
+        let current = BACKGROUND_CYCLE
+            .iter()
+            .position(|&color| color == self.background_color)
+            .unwrap_or(0);
+        self.background_color = BACKGROUND_CYCLE[(current + 1) % BACKGROUND_CYCLE.len()];
+        Ok(TickResult::Draw)
+    }
+
+    // 02A2, 02A4, 02A6 - foreground = the color RAM byte at I, I + 1 or I + 2
+    pub(crate) fn op_set_foreground(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let addr = self.i_reg.wrapping_add((op & 0x000F) / 2 - 1);
+        let Some(index) = self.color_ram_index(addr) else {
+            return Err(ChipError::InvalidMemoryAccess(addr));
+        };
+        self.foreground_color = Some(self.color_ram[index] & 0x7);
+        Ok(TickResult::Draw)
+    }
+
+    // 5XY1 - add VY to VX one nibble at a time, each wrapping at 8. Programs
+    // use it to step zone coordinates packed as two nibbles.
+    pub(crate) fn op_add_nibbles(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        self.v_reg[x] = ((self.v_reg[x] & 0x77) + (self.v_reg[y] & 0x77)) & 0x77;
+        Ok(TickResult::Ran)
+    }
+
+    // 5XY2 - move each nibble of VX one step towards the same nibble of VY,
+    // wrapping at 8 the way 5XY1 does and taking the shorter way round.
+    // Programs use it to walk packed zone coordinates to a target.
+    pub(crate) fn op_step_nibbles(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        let step = |from: u8, to: u8| match to.wrapping_sub(from) & 0x7 {
+            0 => from,
+            1..=4 => (from + 1) & 0x7,
+            _ => from.wrapping_sub(1) & 0x7,
+        };
+        let (vx, vy) = (self.v_reg[x], self.v_reg[y]);
+        self.v_reg[x] = step(vx >> 4 & 0x7, vy >> 4 & 0x7) << 4 | step(vx & 0x7, vy & 0x7);
+        Ok(TickResult::Ran)
+    }
+
+    // BXYN - colour zones with V(Y+1). The low nibble of VX is the first zone
+    // column and the high nibble how many more to colour. BXY0 does the same
+    // for 4 row bands picked by VY, BXYN colours N rows starting at row VY.
+    pub(crate) fn op_color_zones(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        let n = (op & 0x000F) as usize;
+        let (vx, vy) = (self.v_reg[x] as usize, self.v_reg[y] as usize);
+        let color = self.v_reg[(y + 1) & 0xF] & 0x7;
+        self.foreground_color = None;
+
+        let columns = (vx & 0xF)..=(vx & 0xF) + (vx >> 4);
+        let rows = if n == 0 {
+            (vy & 0xF) * 4..((vy & 0xF) + (vy >> 4) + 1) * 4
+        } else {
+            vy..vy + n
+        };
+        for row in rows.filter(|row| *row < SCREEN_HEIGHT) {
+            for column in columns.clone().filter(|column| *column < ZONES_PER_ROW) {
+                self.color_ram[row * ZONES_PER_ROW + column] = color;
+            }
+        }
+        Ok(TickResult::Draw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuirksConfig;
+
+    fn chip8x(opcodes: &[u16]) -> Emulator {
+        let rom: Vec<u8> = opcodes.iter().flat_map(|op| op.to_be_bytes()).collect();
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig {
+            chip8x_enabled: true,
+            ..QuirksConfig::default()
+        });
+        chip8.load_rom(&rom).unwrap();
+        chip8
+    }
+
+    fn run(chip8: &mut Emulator, ticks: usize) {
+        for _ in 0..ticks {
+            if let TickResult::Error(err) = chip8.tick() {
+                panic!("{}", err);
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_background() {
+        let mut chip8 = chip8x(&[0x02A0, 0x02A0, 0x02A0, 0x02A0]);
+        let mut seen = vec![chip8.get_background_color()];
+        for _ in 0..4 {
+            run(&mut chip8, 1);
+            seen.push(chip8.get_background_color());
+        }
+        assert_eq!(seen, [2, 0, 4, 1, 2]);
+    }
+
+    #[test]
+    fn set_foreground_from_color_ram() {
+        // LD I, 0x2C0; V0 = 3, V1 = 5, V2 = 6; LD [I], V2; then 02A2, 02A4, 02A6
+        let mut chip8 = chip8x(&[
+            0xA2C0, 0x6003, 0x6105, 0x6206, 0xF255, 0xA2C0, 0x02A2, 0x02A4, 0x02A6,
+        ]);
+        run(&mut chip8, 6);
+        assert_eq!(&chip8.get_color_ram()[..3], &[3, 5, 6]);
+        assert_eq!(chip8.get_foreground_color(), None);
+        for expected in [3, 5, 6] {
+            run(&mut chip8, 1);
+            assert_eq!(chip8.get_foreground_color(), Some(expected));
+        }
+
+        // a lit pixel shows the foreground, not its zone
+        chip8.screen.set_pixel(0, 0, true);
+        assert_eq!(chip8.get_pixel_color(0, 0), Some(6));
+    }
+
+    #[test]
+    fn set_foreground_outside_color_ram_is_an_error() {
+        let mut chip8 = chip8x(&[0xA3BF, 0x02A6]);
+        run(&mut chip8, 1);
+        match chip8.tick() {
+            TickResult::Error(err) => {
+                assert_eq!(err.inner(), &ChipError::InvalidMemoryAccess(0x3C1))
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn add_nibbles() {
+        // V0 = 0x36, V1 = 0x53, 5011
+        let mut chip8 = chip8x(&[0x6036, 0x6153, 0x5011]);
+        run(&mut chip8, 3);
+        // 3 + 5 and 6 + 3 both wrap at 8
+        assert_eq!(chip8.get_registers()[0], 0x01);
+    }
+
+    #[test]
+    fn step_nibbles() {
+        // V0 = 0x06, V1 = 0x21, 5012 twice
+        let mut chip8 = chip8x(&[0x6006, 0x6121, 0x5012, 0x5012, 0x5012]);
+        run(&mut chip8, 3);
+        // the high nibble steps up, the low one wraps 6 -> 7 on its way to 1
+        assert_eq!(chip8.get_registers()[0], 0x17);
+        run(&mut chip8, 2);
+        assert_eq!(chip8.get_registers()[0], 0x21);
+    }
+
+    #[test]
+    fn color_zones() {
+        // V0 = 0x12 (columns 2-3), V1 = 4 (row 4), V2 = colour 5; B013 colours rows 4-6
+        let mut chip8 = chip8x(&[0x6012, 0x6104, 0x6205, 0xB013]);
+        run(&mut chip8, 4);
+        let color_ram = chip8.get_color_ram();
+        for row in 0..SCREEN_HEIGHT {
+            for column in 0..ZONES_PER_ROW {
+                let expected = if (4..7).contains(&row) && (2..4).contains(&column) {
+                    5
+                } else {
+                    DEFAULT_ZONE_COLOR
+                };
+                assert_eq!(color_ram[row * ZONES_PER_ROW + column], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn color_ram_is_only_mapped_with_chip8x() {
+        // LD I, 0x2C8; V0 = 7; LD [I], V0
+        let program = [0xA2C8, 0x6007, 0xF055];
+        let mut chip8 = chip8x(&program);
+        run(&mut chip8, 3);
+        assert_eq!(chip8.get_color_ram()[8], 7);
+
+        let mut chip8 = Emulator::new();
+        let rom: Vec<u8> = program.iter().flat_map(|op| op.to_be_bytes()).collect();
+        chip8.load_rom(&rom).unwrap();
+        run(&mut chip8, 3);
+        assert_eq!(chip8.get_color_ram()[8], DEFAULT_ZONE_COLOR);
+        assert_eq!(chip8.ram[0x2C8], 7);
+    }
+
+    #[test]
+    fn decode_opcode_follows_the_quirk() {
+        let chip8 = chip8x(&[0x02A0]);
+        assert_eq!(
+            chip8.decode_opcode(0xB124),
+            Instruction::ColorZones { vx: 1, vy: 2, n: 4 }
+        );
+        assert_eq!(chip8.decode_opcode(0x02A0), Instruction::CycleBackground);
+        let chip8 = Emulator::new();
+        assert_eq!(chip8.decode_opcode(0xB124), Instruction::JumpV0(0x124));
+        assert_eq!(chip8.decode_opcode(0x02A0), Instruction::Unknown(0x02A0));
+    }
+}
@@ -0,0 +1,60 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use crate::RAM_SIZE;
+
+/// Emulator RAM that checkpoints can share. Cloning only bumps a reference
+/// count, the first write after that copies the 4 KB array.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CowRam(Arc<[u8; RAM_SIZE]>);
+
+impl CowRam {
+    /// True if neither side has been written to since one was cloned from the other.
+    pub(crate) fn shares_with(&self, other: &CowRam) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for CowRam {
+    fn default() -> Self {
+        CowRam(Arc::new([0; RAM_SIZE]))
+    }
+}
+
+impl Deref for CowRam {
+    type Target = [u8; RAM_SIZE];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for CowRam {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Emulator;
+
+    #[test]
+    fn snapshot_keeps_the_bytes_from_before_a_write() {
+        // LD V0, 0xAB; LD I, 0x300; LD [I], V0
+        let mut chip8 = Emulator::new();
+        chip8
+            .load_rom(&[0x60, 0xAB, 0xA3, 0x00, 0xF0, 0x55])
+            .unwrap();
+        chip8.tick_n(2);
+        chip8.checkpoint();
+        let snapshot = chip8.clone();
+        assert!(snapshot.ram.shares_with(&chip8.ram));
+
+        chip8.tick();
+        assert!(!snapshot.ram.shares_with(&chip8.ram));
+        assert_eq!(snapshot.ram[0x300], 0x00);
+        assert_eq!(chip8.ram[0x300], 0xAB);
+        assert_eq!(chip8.take_snapshot().ram_diff, [(0x300, 0xAB)]);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{Emulator, ScreenBuffer};
+
+/// Everything that changed between two emulator states.
+/// Each `Option<(old, new)>` is only `Some` when the value changed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EmulatorDiff {
+    pub pc: Option<(u16, u16)>,
+    pub registers: Vec<(usize, u8, u8)>,
+    pub i_reg: Option<(u16, u16)>,
+    pub ram_changes: Vec<(usize, u8, u8)>,
+    pub screen_changes: Vec<(usize, bool, bool)>,
+    pub dt: Option<(u8, u8)>,
+    pub st: Option<(u8, u8)>,
+}
+
+impl EmulatorDiff {
+    pub fn is_empty(&self) -> bool {
+        self.pc.is_none()
+            && self.registers.is_empty()
+            && self.i_reg.is_none()
+            && self.ram_changes.is_empty()
+            && self.screen_changes.is_empty()
+            && self.dt.is_none()
+            && self.st.is_none()
+    }
+}
+
+pub fn diff<S: ScreenBuffer>(before: &Emulator<S>, after: &Emulator<S>) -> EmulatorDiff {
+    EmulatorDiff {
+        pc: changed(before.pc, after.pc),
+        registers: changed_indices(&before.v_reg, &after.v_reg),
+        i_reg: changed(before.i_reg, after.i_reg),
+        ram_changes: changed_indices(&before.ram[..], &after.ram[..]),
+        screen_changes: changed_pixels(&before.screen, &after.screen),
+        dt: changed(before.dt, after.dt),
+        st: changed(before.st, after.st),
+    }
+}
+
+fn changed<T: PartialEq>(old: T, new: T) -> Option<(T, T)> {
+    if old != new { Some((old, new)) } else { None }
+}
+
+fn changed_indices<T: PartialEq + Copy>(old: &[T], new: &[T]) -> Vec<(usize, T, T)> {
+    old.iter()
+        .zip(new.iter())
+        .enumerate()
+        .filter(|(_, (o, n))| o != n)
+        .map(|(i, (o, n))| (i, *o, *n))
+        .collect()
+}
+
+// indices are row-major, matching `get_display`
+fn changed_pixels<S: ScreenBuffer>(old: &S, new: &S) -> Vec<(usize, bool, bool)> {
+    let width = old.width();
+    (0..old.height())
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter_map(|(x, y)| {
+            let (o, n) = (old.get_pixel(x, y), new.get_pixel(x, y));
+            (o != n).then_some((x + width * y, o, n))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_byte_changes_one_register() {
+        let mut chip8 = Emulator::new();
+        // LD V3, 0x42
+        chip8.load_rom(&[0x63, 0x42]).unwrap();
+        let before = chip8.clone();
+        chip8.tick();
+
+        let changes = diff(&before, &chip8);
+        assert_eq!(changes.registers, vec![(3, 0x00, 0x42)]);
+        assert_eq!(changes.pc, Some((0x200, 0x202)));
+        assert!(changes.ram_changes.is_empty());
+        assert!(diff(&chip8, &chip8).is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{ChipError, Emulator, ScreenBuffer, TickResult};
+
+// Both dispatchers pick a handler by the opcode's first nibble and share the
+// same handlers, so they can only differ in speed, never in behavior.
+//
+// The match lets the optimizer inline every handler into `dispatch`, the
+// table is a single indexed load and an indirect call that can't be inlined.
+// Neither is reliably ahead on the "mixed opcodes" benchmark, the difference
+// is within run-to-run noise, so the match stays the default. Enable
+// "dispatch-table" to get the table and compare on the target with
+// `cargo bench --bench core --features dispatch-table -- "mixed opcodes"`.
+#[cfg(feature = "dispatch-table")]
+type Handler<S> = fn(&mut Emulator<S>, u16) -> Result<TickResult, ChipError>;
+
+#[cfg(feature = "dispatch-table")]
+const fn dispatch_table<S: ScreenBuffer>() -> [Handler<S>; 16] {
+    [
+        Emulator::op_sys,
+        Emulator::op_jp,
+        Emulator::op_call,
+        Emulator::op_se_byte,
+        Emulator::op_sne_byte,
+        Emulator::op_se_reg,
+        Emulator::op_ld_byte,
+        Emulator::op_add_byte,
+        Emulator::op_alu,
+        Emulator::op_sne_reg,
+        Emulator::op_ld_i,
+        Emulator::op_jp_v0,
+        Emulator::op_rnd,
+        Emulator::op_drw,
+        Emulator::op_skp,
+        Emulator::op_misc,
+    ]
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    #[cfg(feature = "dispatch-table")]
+    const DISPATCH: [Handler<S>; 16] = dispatch_table();
+
+    #[cfg(feature = "dispatch-table")]
+    pub(crate) fn dispatch(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        Self::DISPATCH[(op >> 12) as usize](self, op)
+    }
+
+    #[cfg(not(feature = "dispatch-table"))]
+    pub(crate) fn dispatch(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        match op >> 12 {
+            0x0 => self.op_sys(op),
+            0x1 => self.op_jp(op),
+            0x2 => self.op_call(op),
+            0x3 => self.op_se_byte(op),
+            0x4 => self.op_sne_byte(op),
+            0x5 => self.op_se_reg(op),
+            0x6 => self.op_ld_byte(op),
+            0x7 => self.op_add_byte(op),
+            0x8 => self.op_alu(op),
+            0x9 => self.op_sne_reg(op),
+            0xA => self.op_ld_i(op),
+            0xB => self.op_jp_v0(op),
+            0xC => self.op_rnd(op),
+            0xD => self.op_drw(op),
+            0xE => self.op_skp(op),
+            _ => self.op_misc(op),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt;
+
+use crate::{Emulator, ScreenBuffer};
+
+impl<S: ScreenBuffer> fmt::Display for Emulator<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "PC: {:#05X}  I: {:#05X}  SP: {}  DT: {}  ST: {}",
+            self.pc, self.i_reg, self.sp, self.dt, self.st
+        )?;
+        for (half, regs) in self.v_reg.chunks(8).enumerate() {
+            let line: Vec<String> = regs
+                .iter()
+                .enumerate()
+                .map(|(i, v)| format!("V{:X}: {:02X}", half * 8 + i, v))
+                .collect();
+            writeln!(f, "{}", line.join("  "))?;
+        }
+
+        let stack: Vec<String> = self.stack[..self.sp as usize]
+            .iter()
+            .map(|addr| format!("{:#05X}", addr))
+            .collect();
+        writeln!(f, "Stack: [{}]", stack.join(", "))?;
+
+        let keys: Vec<String> = self
+            .get_pressed_keys()
+            .map(|k| format!("{:X}", k))
+            .collect();
+        writeln!(f, "Keys: [{}]", keys.join(", "))?;
+
+        writeln!(f, "{}", self.screen_to_string())
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// The screen as rows of `#` (on) and `.` (off).
+    pub(crate) fn screen_to_string(&self) -> String {
+        (0..self.screen.height())
+            .map(|y| {
+                (0..self.screen.width())
+                    .map(|x| {
+                        if self.screen.get_pixel(x, y) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// the derived output would print all 2048 pixels as booleans
+impl<S: ScreenBuffer> fmt::Debug for Emulator<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Emulator;
+
+    #[test]
+    fn dump_shows_registers_and_screen() {
+        // LD V3, 0x42; LD I, 0x2AB; CALL 0x208; JP 0x206
+        let mut chip8 = Emulator::new();
+        chip8
+            .load_rom(&[0x63, 0x42, 0xA2, 0xAB, 0x22, 0x08, 0x12, 0x06, 0x12, 0x08])
+            .unwrap();
+        chip8.tick_n(3);
+        chip8.keypress(0xB, true);
+        chip8.set_pixel(0, 0, true).unwrap();
+
+        let dump = format!("{}", chip8);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "PC: 0x208  I: 0x2AB  SP: 1  DT: 0  ST: 0");
+        assert!(lines[1].starts_with("V0: 00  V1: 00  V2: 00  V3: 42"));
+        assert!(lines[2].starts_with("V8: 00"));
+        assert_eq!(lines[3], "Stack: [0x206]");
+        assert_eq!(lines[4], "Keys: [B]");
+        assert_eq!(lines[5], format!("#{}", ".".repeat(63)));
+        assert_eq!(lines.len(), 5 + 32);
+
+        assert_eq!(format!("{:?}", chip8), dump);
+    }
+}
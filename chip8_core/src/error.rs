@@ -0,0 +1,182 @@
+use std::fmt;
+
+/// Same type as `ChipError`.
+pub type Chip8Error = ChipError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChipError {
+    /// A CALL was executed with every stack slot already in use.
+    StackOverflow,
+    /// A RET was executed with nothing on the stack.
+    StackUnderflow,
+    /// An MMIO range was empty or overlapped an already registered region.
+    InvalidMmioRegion { start: u16, end: u16 },
+    /// The ROM has no bytes at all.
+    EmptyRom,
+    /// The ROM does not fit in the program area of RAM.
+    RomTooLarge { size: usize, max: usize },
+    /// The ROM file could not be read.
+    RomReadFailed(std::io::ErrorKind),
+    /// Save state data was truncated or came from an incompatible emulator.
+    InvalidSaveState,
+    /// A `SaveState` was taken with a different ROM loaded. Both are CRC-32s.
+    SaveStateRomMismatch { expected: u32, found: u32 },
+    /// A save state file could not be read or written.
+    SaveStateFileFailed(std::io::ErrorKind),
+    /// The program counter is odd or points outside RAM.
+    InvalidProgramCounter(u16),
+    /// `EmulatorBuilder::start_address` was odd, overlapped the fonts or was
+    /// outside RAM.
+    InvalidStartAddress(u16),
+    /// An instruction read or wrote past the end of RAM, usually through I.
+    InvalidMemoryAccess(u16),
+    /// A pixel coordinate was outside the screen.
+    PixelOutOfBounds { x: usize, y: usize },
+    /// The RPL flags file could not be read or written.
+    RplFlagsFileFailed(std::io::ErrorKind),
+    /// The ROM's first instruction is not a known opcode, only checked with
+    /// `QuirksConfig::strict_rom_validation`.
+    InvalidRomStart(u16),
+    /// The opcode isn't part of any instruction set the emulator supports.
+    UnknownOpcode(u16),
+    /// An instruction failed with `error`, returned by `tick()` in place of
+    /// the bare error. The context is also the error's `source()`.
+    Execution {
+        error: Box<ChipError>,
+        context: ChipErrorContext,
+    },
+}
+
+impl ChipError {
+    /// The error without any execution context, for matching on the cause.
+    pub fn inner(&self) -> &ChipError {
+        match self {
+            ChipError::Execution { error, .. } => error.inner(),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for ChipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChipError::StackOverflow => write!(f, "stack overflow"),
+            ChipError::StackUnderflow => write!(f, "stack underflow"),
+            ChipError::InvalidMmioRegion { start, end } => {
+                write!(f, "invalid MMIO region {:#05X}..{:#05X}", start, end)
+            }
+            ChipError::EmptyRom => write!(f, "ROM is empty"),
+            ChipError::RomTooLarge { size, max } => {
+                write!(f, "ROM is {} bytes, the maximum is {}", size, max)
+            }
+            ChipError::RomReadFailed(kind) => write!(f, "unable to read ROM: {}", kind),
+            ChipError::InvalidSaveState => write!(f, "invalid save state"),
+            ChipError::SaveStateRomMismatch { expected, found } => write!(
+                f,
+                "save state is for another ROM (CRC {:08X}, the loaded ROM is {:08X})",
+                found, expected
+            ),
+            ChipError::SaveStateFileFailed(kind) => {
+                write!(f, "unable to access save state file: {}", kind)
+            }
+            ChipError::InvalidProgramCounter(pc) => {
+                write!(f, "invalid program counter {:#05X}", pc)
+            }
+            ChipError::InvalidStartAddress(addr) => write!(
+                f,
+                "invalid start address {:#05X}, it must be even and after the fonts",
+                addr
+            ),
+            ChipError::InvalidMemoryAccess(addr) => {
+                write!(f, "memory access at {:#06X} is outside RAM", addr)
+            }
+            ChipError::PixelOutOfBounds { x, y } => {
+                write!(f, "pixel ({}, {}) is off the screen", x, y)
+            }
+            ChipError::RplFlagsFileFailed(kind) => {
+                write!(f, "unable to access RPL flags file: {}", kind)
+            }
+            ChipError::InvalidRomStart(op) => {
+                write!(f, "ROM starts with unknown opcode {:04X}", op)
+            }
+            ChipError::UnknownOpcode(op) => write!(f, "unknown opcode {:04X}", op),
+            ChipError::Execution { error, context } => write!(
+                f,
+                "{} at {:#05X} (opcode {:04X})",
+                error, context.pc_at_error, context.opcode
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChipError::Execution { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+}
+
+/// Where the emulator was when an instruction failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipErrorContext {
+    /// Address of the failing instruction.
+    pub pc_at_error: u16,
+    /// Return addresses on the stack, oldest first.
+    pub call_stack: Vec<u16>,
+    pub opcode: u16,
+}
+
+impl fmt::Display for ChipErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "call stack:")?;
+        if self.call_stack.is_empty() {
+            return write!(f, " empty");
+        }
+        for addr in &self.call_stack {
+            write!(f, " {:#05X}", addr)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ChipErrorContext {}
+
+/// Non-fatal conditions noticed during execution, collected with `drain_warnings()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChipWarning {
+    /// The program wrote to this address inside its own ROM image.
+    SelfModification(u16),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{EmulatorBuilder, TickResult};
+
+    #[test]
+    fn stack_overflow_carries_its_context() {
+        let mut chip8 = EmulatorBuilder::new().stack_size(2).build();
+        // CALL 0x202; CALL 0x204; CALL 0x206
+        chip8
+            .load_rom(&[0x22, 0x02, 0x22, 0x04, 0x22, 0x06])
+            .unwrap();
+        chip8.tick_n(2);
+
+        let TickResult::Error(err) = chip8.tick() else {
+            panic!("the third call should overflow the stack");
+        };
+        assert_eq!(err.inner(), &ChipError::StackOverflow);
+        assert!(err.source().is_some());
+        let ChipError::Execution { context, .. } = &err else {
+            panic!("tick errors carry their context");
+        };
+        assert_eq!(context.pc_at_error, 0x204);
+        assert_eq!(context.opcode, 0x2206);
+        assert_eq!(context.call_stack, [0x202, 0x204]);
+        assert_eq!(err.to_string(), "stack overflow at 0x204 (opcode 2206)");
+    }
+}
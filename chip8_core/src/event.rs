@@ -0,0 +1,20 @@
+use crate::ChipError;
+
+/// Notifications sent from an emulator running on a background thread.
+/// The timer expiries are also collected by `Emulator::drain_events()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmulatorEvent {
+    /// The screen changed, carries a copy of the display buffer.
+    Draw(Vec<bool>),
+    /// The sound timer started (`true`) or stopped (`false`) sounding.
+    Sound(bool),
+    /// The watchdog limit was reached.
+    Halted,
+    Error(ChipError),
+    /// Reply to `EmulatorCommand::SaveState`.
+    StateSaved(Vec<u8>),
+    /// DT counted down to zero.
+    DelayTimerExpired,
+    /// ST counted down to zero.
+    SoundTimerExpired,
+}
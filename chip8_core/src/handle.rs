@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{ChipError, Emulator, KEY_COUNT, TickResult};
+
+/// Shared, lockable emulator for driving one machine from several threads,
+/// e.g. rendering on one and input on another. Each call holds the lock only
+/// for its own duration.
+#[derive(Clone)]
+pub struct EmulatorHandle(Arc<Mutex<Emulator>>);
+
+impl EmulatorHandle {
+    pub fn new(emulator: Emulator) -> Self {
+        EmulatorHandle(Arc::new(Mutex::new(emulator)))
+    }
+
+    /// Another handle to the same emulator.
+    pub fn clone_handle(&self) -> EmulatorHandle {
+        self.clone()
+    }
+
+    pub fn tick(&self) -> TickResult {
+        self.lock().tick()
+    }
+
+    pub fn tick_timers(&self) {
+        self.lock().tick_timers();
+    }
+
+    /// Keys outside 0..16 are ignored.
+    pub fn keypress(&self, idx: usize, pressed: bool) {
+        if idx < KEY_COUNT {
+            self.lock().keypress(idx, pressed);
+        }
+    }
+
+    pub fn reset(&self) {
+        self.lock().reset();
+    }
+
+    pub fn soft_reset(&self) {
+        self.lock().soft_reset();
+    }
+
+    pub fn load_rom(&self, data: &[u8]) -> Result<(), ChipError> {
+        self.lock().load_rom_checked(data).map(|_| ())
+    }
+
+    /// The display as packed RGBA bytes, four per pixel in row-major order.
+    pub fn get_display_rgba(&self, fg: [u8; 4], bg: [u8; 4]) -> Vec<u8> {
+        self.lock()
+            .get_display()
+            .iter()
+            .flat_map(|on| if *on { fg } else { bg })
+            .collect()
+    }
+
+    /// Runs `f` with the lock held, for anything the handle does not wrap.
+    pub fn with_emulator<T>(&self, f: impl FnOnce(&mut Emulator) -> T) -> T {
+        f(&mut self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Emulator> {
+        // a panic inside with_emulator should not take every other handle down with it
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl From<Emulator> for EmulatorHandle {
+    fn from(emulator: Emulator) -> Self {
+        EmulatorHandle::new(emulator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn input_and_ticks_from_two_threads() {
+        // LD V0, K; ADD V1, 0x01; JP 0x200
+        let handle = EmulatorHandle::new(Emulator::new());
+        handle
+            .load_rom(&[0xF0, 0x0A, 0x71, 0x01, 0x12, 0x00])
+            .unwrap();
+
+        let input = handle.clone_handle();
+        let presses = thread::spawn(move || {
+            for n in 0..50 {
+                input.keypress(n % 16, true);
+                // out of range keys are ignored rather than panicking under the lock
+                input.keypress(16, true);
+                thread::yield_now();
+                input.keypress(n % 16, false);
+            }
+        });
+        let ticker = handle.clone_handle();
+        let ticks = thread::spawn(move || {
+            for _ in 0..10_000 {
+                ticker.tick();
+            }
+        });
+        presses.join().unwrap();
+        ticks.join().unwrap();
+
+        // nothing was lost or deadlocked, and the lock is free again
+        let summary = handle.with_emulator(|chip8| chip8.execution_summary());
+        assert_eq!(summary.total_ticks, 10_000);
+        assert_eq!(summary.key_events, 100);
+        let rgba = handle.get_display_rgba([255; 4], [0; 4]);
+        assert_eq!(rgba.len(), 64 * 32 * 4);
+    }
+}
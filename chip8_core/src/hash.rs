@@ -0,0 +1,170 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::{Emulator, ScreenBuffer};
+
+/// Hashes the machine state only: PC, registers, stack, RAM, screen and timers.
+/// Quirks, breakpoints, history and other host-side settings are ignored.
+impl<S: ScreenBuffer> Hash for Emulator<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pc.hash(state);
+        self.v_reg.hash(state);
+        self.i_reg.hash(state);
+        self.sp.hash(state);
+        self.stack.hash(state);
+        self.ram.hash(state);
+        for row in packed_screen(&self.screen) {
+            row.hash(state);
+        }
+        self.dt.hash(state);
+        self.st.hash(state);
+    }
+}
+
+/// Compares the same fields as `Hash`.
+impl<S: ScreenBuffer> PartialEq for Emulator<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pc == other.pc
+            && self.v_reg == other.v_reg
+            && self.i_reg == other.i_reg
+            && self.sp == other.sp
+            && self.stack == other.stack
+            && self.ram == other.ram
+            && packed_screen(&self.screen).eq(packed_screen(&other.screen))
+            && self.dt == other.dt
+            && self.st == other.st
+    }
+}
+
+impl<S: ScreenBuffer> Eq for Emulator<S> {}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Hash of the machine state, for cheap "have I been here before" checks.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// FNV-1a hash of the screen alone, for spotting frames that did not
+    /// change. Cached until the next draw, so calling it every frame is cheap.
+    pub fn get_screen_hash(&self) -> u64 {
+        if let Some(hash) = self.screen_hash.get() {
+            return hash;
+        }
+        let hash = packed_screen(&self.screen)
+            .flat_map(u64::to_be_bytes)
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            });
+        self.screen_hash.set(Some(hash));
+        hash
+    }
+
+    /// True if the screen may have changed since `mark_draw_complete`.
+    pub fn needs_redraw(&self) -> bool {
+        self.screen_dirty
+    }
+
+    /// Call after rendering the screen, `needs_redraw` is false until the
+    /// next change.
+    pub fn mark_draw_complete(&mut self) {
+        self.screen_dirty = false;
+    }
+
+    // call after anything that may have touched a pixel
+    pub(crate) fn screen_changed(&mut self) {
+        *self.screen_hash.get_mut() = None;
+        self.screen_dirty = true;
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// packs each row into u64s, 64 pixels at a time, so every screen type hashes the same way
+fn packed_screen<S: ScreenBuffer>(screen: &S) -> impl Iterator<Item = u64> + '_ {
+    let width = screen.width();
+    (0..screen.height()).flat_map(move |y| {
+        (0..width).step_by(64).map(move |start| {
+            (start..(start + 64).min(width))
+                .fold(0u64, |word, x| (word << 1) | screen.get_pixel(x, y) as u64)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TickResult;
+
+    fn run() -> Emulator {
+        let mut chip8 = Emulator::new();
+        chip8.seed_rng(7);
+        // LD V0, 0x05; RND V1, 0xFF; LD F, V0; DRW V0, V1, 5
+        chip8
+            .load_rom(&[0x60, 0x05, 0xC1, 0xFF, 0xF0, 0x29, 0xD0, 0x15])
+            .unwrap();
+        chip8.tick_n(4);
+        chip8
+    }
+
+    #[test]
+    fn same_execution_same_hash() {
+        let (a, b) = (run(), run());
+        assert!(a == b);
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn any_field_changes_the_hash() {
+        let changes: [fn(&mut Emulator); 9] = [
+            |c| c.pc += 2,
+            |c| c.v_reg[0xE] ^= 1,
+            |c| c.i_reg ^= 1,
+            |c| c.sp += 1,
+            |c| c.stack[3] ^= 1,
+            |c| c.ram[0xE00] ^= 1,
+            |c| {
+                let on = c.screen.get_pixel(63, 31);
+                c.screen.set_pixel(63, 31, !on);
+            },
+            |c| c.dt ^= 1,
+            |c| c.st ^= 1,
+        ];
+        let original = run();
+        for (idx, change) in changes.iter().enumerate() {
+            let mut changed = run();
+            change(&mut changed);
+            assert!(changed != original, "change {}", idx);
+            assert_ne!(
+                changed.state_hash(),
+                original.state_hash(),
+                "change {}",
+                idx
+            );
+        }
+    }
+
+    #[test]
+    fn screen_hash_changes_only_with_the_screen() {
+        // LD F, V0; LD V1, 0x01; DRW V0, V0, 5
+        let mut chip8 = Emulator::new();
+        chip8
+            .load_rom(&[0xF0, 0x29, 0x61, 0x01, 0xD0, 0x05])
+            .unwrap();
+        let blank = chip8.get_screen_hash();
+        assert_eq!(Emulator::new().get_screen_hash(), blank);
+
+        chip8.tick_n(2);
+        assert_eq!(chip8.get_screen_hash(), blank);
+
+        assert_eq!(chip8.tick(), TickResult::Draw);
+        let drawn = chip8.get_screen_hash();
+        assert_ne!(drawn, blank);
+        assert_eq!(chip8.get_screen_hash(), drawn);
+
+        // a single pixel is enough
+        chip8.set_pixel(63, 31, true).unwrap();
+        assert_ne!(chip8.get_screen_hash(), drawn);
+    }
+}
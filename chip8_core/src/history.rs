@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+
+use crate::{Emulator, ScreenBuffer};
+
+/// Bounded stack of compressed emulator snapshots used for undo/redo.
+#[derive(Clone)]
+pub struct StateHistory {
+    snapshots: VecDeque<Vec<u8>>,
+    redo_stack: Vec<Vec<u8>>,
+    max_depth: usize,
+}
+
+impl StateHistory {
+    pub fn new(max_depth: usize) -> Self {
+        StateHistory {
+            snapshots: VecDeque::with_capacity(max_depth),
+            redo_stack: Vec::new(),
+            max_depth,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    // called before every tick, so any pending redo states are now stale.
+    fn record<S: ScreenBuffer>(&mut self, emulator: &Emulator<S>) {
+        if self.max_depth == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.max_depth {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(compress(emulator));
+        self.redo_stack.clear();
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    pub fn enable_history(&mut self, max_depth: usize) {
+        self.history = Some(StateHistory::new(max_depth));
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    pub fn history_depth(&self) -> usize {
+        self.history.as_ref().map_or(0, |h| h.depth())
+    }
+
+    pub(crate) fn record_history(&mut self) {
+        if let Some(mut history) = self.history.take() {
+            history.record(self);
+            self.history = Some(history);
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(mut history) = self.history.take() else {
+            return false;
+        };
+
+        let undone = match history.snapshots.pop_back() {
+            Some(snapshot) => {
+                history.redo_stack.push(compress(self));
+                restore(self, &snapshot);
+                true
+            }
+            None => false,
+        };
+
+        self.history = Some(history);
+        undone
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(mut history) = self.history.take() else {
+            return false;
+        };
+
+        let redone = match history.redo_stack.pop() {
+            Some(snapshot) => {
+                history.snapshots.push_back(compress(self));
+                restore(self, &snapshot);
+                true
+            }
+            None => false,
+        };
+
+        self.history = Some(history);
+        redone
+    }
+}
+
+fn compress<S: ScreenBuffer>(emulator: &Emulator<S>) -> Vec<u8> {
+    lz4_flex::compress_prepend_size(&emulator.save_state())
+}
+
+fn restore<S: ScreenBuffer>(emulator: &mut Emulator<S>, snapshot: &[u8]) {
+    let state = lz4_flex::decompress_size_prepended(snapshot)
+        .expect("history snapshots are always produced by compress()");
+    emulator
+        .load_state(&state)
+        .expect("history snapshots always match the emulator they came from");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_walk_the_ticks() {
+        let mut chip8: Emulator = Emulator::new();
+        // ADD V0, 0x01 five times
+        chip8.load_rom(&[0x70, 0x01].repeat(5)).unwrap();
+        chip8.enable_history(10);
+
+        let mut states = vec![chip8.save_state()];
+        for _ in 0..5 {
+            chip8.tick();
+            states.push(chip8.save_state());
+        }
+        assert_eq!(chip8.history_depth(), 5);
+
+        for _ in 0..3 {
+            assert!(chip8.undo());
+        }
+        assert_eq!(chip8.save_state(), states[2]);
+        assert_eq!(chip8.v_reg[0], 2);
+
+        for _ in 0..2 {
+            assert!(chip8.redo());
+        }
+        assert_eq!(chip8.save_state(), states[4]);
+        assert_eq!(chip8.v_reg[0], 4);
+        assert_eq!(chip8.history_depth(), 4);
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{BoolArrayScreen, Emulator, ScreenBuffer};
+
+/// Callback invoked with the emulator and the opcode being executed.
+pub type Hook<S = BoolArrayScreen> = Box<dyn Fn(&Emulator<S>, u16) + Send>;
+
+// a hook and its opcode filter, shared between an emulator and its clones
+pub(crate) type HookEntry<S> = (u16, u16, Arc<Mutex<Hook<S>>>);
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Registers a hook that runs before any opcode where `(opcode & mask) == value`.
+    /// For example `mask = 0xF000, value = 0xD000` intercepts every draw.
+    pub fn register_pre_hook(&mut self, mask: u16, value: u16, hook: Hook<S>) {
+        self.pre_hooks
+            .push((mask, value, Arc::new(Mutex::new(hook))));
+    }
+
+    /// Same filter as `register_pre_hook`, but runs after the opcode has executed.
+    pub fn register_post_hook(&mut self, mask: u16, value: u16, hook: Hook<S>) {
+        self.post_hooks
+            .push((mask, value, Arc::new(Mutex::new(hook))));
+    }
+
+    pub fn clear_hooks(&mut self) {
+        self.pre_hooks.clear();
+        self.post_hooks.clear();
+    }
+
+    pub(crate) fn run_pre_hooks(&self, op: u16) {
+        run_matching(&self.pre_hooks, self, op);
+    }
+
+    pub(crate) fn run_post_hooks(&self, op: u16) {
+        run_matching(&self.post_hooks, self, op);
+    }
+}
+
+fn run_matching<S: ScreenBuffer>(hooks: &[HookEntry<S>], emulator: &Emulator<S>, op: u16) {
+    for (mask, value, hook) in hooks {
+        if op & mask == *value {
+            // a hook that panicked before can still run
+            let hook = hook.lock().unwrap_or_else(PoisonError::into_inner);
+            hook(emulator, op);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pre_hook_sees_font_lookups() {
+        let mut chip8: Emulator = Emulator::new();
+        // LD F, V3; LD V1, 0x01; LD F, VA
+        chip8
+            .load_rom(&[0xF3, 0x29, 0x61, 0x01, 0xFA, 0x29])
+            .unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+        chip8.register_pre_hook(
+            0xF0FF,
+            0xF029,
+            Box::new(move |_, op| recorder.lock().unwrap().push((op >> 8) as u8 & 0xF)),
+        );
+        chip8.tick_n(3);
+
+        assert_eq!(*seen.lock().unwrap(), vec![0x3, 0xA]);
+    }
+}
@@ -0,0 +1,444 @@
+use std::fmt;
+
+/// A decoded CHIP-8 opcode. `vx`/`vy` are register indices, `byte` is the NN operand.
+///
+/// Instructions sort by variant, then by operands. The variants are declared
+/// in opcode order, so a sorted list reads like a sorted disassembly, with
+/// `Unknown` after everything else. The CHIP-8X variants only come out of
+/// `decode_chip8x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Instruction {
+    /// 0000
+    Nop,
+    /// 00CN, SUPER-CHIP
+    ScrollDown(u8),
+    /// 00DN, SUPER-CHIP 1.0 and XO-CHIP
+    ScrollUp(u8),
+    /// 00E0
+    Cls,
+    /// 00EE
+    Ret,
+    /// 00FB, SUPER-CHIP
+    ScrollRight,
+    /// 00FC, SUPER-CHIP
+    ScrollLeft,
+    /// 00FD, SUPER-CHIP
+    Exit,
+    /// 00FE, SUPER-CHIP
+    LowRes,
+    /// 00FF, SUPER-CHIP
+    HighRes,
+    /// 02A0, CHIP-8X
+    CycleBackground,
+    /// 02A2, 02A4 and 02A6 for offsets 0, 1 and 2 from I, CHIP-8X
+    SetForeground(u8),
+    /// 1NNN
+    Jump(u16),
+    /// 2NNN
+    Call(u16),
+    /// 3XNN
+    SkipEqByte { vx: u8, byte: u8 },
+    /// 4XNN
+    SkipNeByte { vx: u8, byte: u8 },
+    /// 5XY0
+    SkipEqReg { vx: u8, vy: u8 },
+    /// 5XY1, CHIP-8X
+    AddNibbles { vx: u8, vy: u8 },
+    /// 5XY2, CHIP-8X
+    StepNibbles { vx: u8, vy: u8 },
+    /// 6XNN
+    LoadByte { vx: u8, byte: u8 },
+    /// 7XNN
+    AddByte { vx: u8, byte: u8 },
+    /// 8XY0
+    LoadReg { vx: u8, vy: u8 },
+    /// 8XY1
+    Or { vx: u8, vy: u8 },
+    /// 8XY2
+    And { vx: u8, vy: u8 },
+    /// 8XY3
+    Xor { vx: u8, vy: u8 },
+    /// 8XY4
+    AddReg { vx: u8, vy: u8 },
+    /// 8XY5
+    SubReg { vx: u8, vy: u8 },
+    /// 8XY6
+    ShiftRight { vx: u8, vy: u8 },
+    /// 8XY7
+    SubN { vx: u8, vy: u8 },
+    /// 8XYE
+    ShiftLeft { vx: u8, vy: u8 },
+    /// 9XY0
+    SkipNeReg { vx: u8, vy: u8 },
+    /// ANNN
+    LoadI(u16),
+    /// BNNN
+    JumpV0(u16),
+    /// BXYN, CHIP-8X
+    ColorZones { vx: u8, vy: u8, n: u8 },
+    /// CXNN
+    Random { vx: u8, byte: u8 },
+    /// DXYN, DXY0 draws a 16x16 sprite on SUPER-CHIP
+    Draw { vx: u8, vy: u8, n: u8 },
+    /// EX9E
+    SkipKeyPressed { vx: u8 },
+    /// EXA1
+    SkipKeyNotPressed { vx: u8 },
+    /// FX07
+    LoadDelay { vx: u8 },
+    /// FX0A
+    WaitKey { vx: u8 },
+    /// FX15
+    SetDelay { vx: u8 },
+    /// FX18
+    SetSound { vx: u8 },
+    /// FX1E
+    AddI { vx: u8 },
+    /// FX29
+    LoadFont { vx: u8 },
+    /// FX30, SUPER-CHIP
+    LoadLargeFont { vx: u8 },
+    /// FX33
+    Bcd { vx: u8 },
+    /// FX55
+    StoreRegs { vx: u8 },
+    /// FX65
+    LoadRegs { vx: u8 },
+    /// FX75, SUPER-CHIP
+    StoreFlags { vx: u8 },
+    /// FX85, SUPER-CHIP
+    LoadFlags { vx: u8 },
+    /// Anything the emulator doesn't implement.
+    Unknown(u16),
+}
+
+impl Instruction {
+    pub fn decode(op: u16) -> Self {
+        let vx = ((op & 0x0F00) >> 8) as u8;
+        let vy = ((op & 0x00F0) >> 4) as u8;
+        let n = (op & 0x000F) as u8;
+        let byte = (op & 0x00FF) as u8;
+        let nnn = op & 0x0FFF;
+
+        match op >> 12 {
+            0x0 => match op {
+                0x0000 => Instruction::Nop,
+                0x00C0..=0x00CF => Instruction::ScrollDown(n),
+                0x00D0..=0x00DF => Instruction::ScrollUp(n),
+                0x00E0 => Instruction::Cls,
+                0x00EE => Instruction::Ret,
+                0x00FB => Instruction::ScrollRight,
+                0x00FC => Instruction::ScrollLeft,
+                0x00FD => Instruction::Exit,
+                0x00FE => Instruction::LowRes,
+                0x00FF => Instruction::HighRes,
+                _ => Instruction::Unknown(op),
+            },
+            0x1 => Instruction::Jump(nnn),
+            0x2 => Instruction::Call(nnn),
+            0x3 => Instruction::SkipEqByte { vx, byte },
+            0x4 => Instruction::SkipNeByte { vx, byte },
+            0x5 if n == 0 => Instruction::SkipEqReg { vx, vy },
+            0x6 => Instruction::LoadByte { vx, byte },
+            0x7 => Instruction::AddByte { vx, byte },
+            0x8 => match n {
+                0x0 => Instruction::LoadReg { vx, vy },
+                0x1 => Instruction::Or { vx, vy },
+                0x2 => Instruction::And { vx, vy },
+                0x3 => Instruction::Xor { vx, vy },
+                0x4 => Instruction::AddReg { vx, vy },
+                0x5 => Instruction::SubReg { vx, vy },
+                0x6 => Instruction::ShiftRight { vx, vy },
+                0x7 => Instruction::SubN { vx, vy },
+                0xE => Instruction::ShiftLeft { vx, vy },
+                _ => Instruction::Unknown(op),
+            },
+            0x9 if n == 0 => Instruction::SkipNeReg { vx, vy },
+            0xA => Instruction::LoadI(nnn),
+            0xB => Instruction::JumpV0(nnn),
+            0xC => Instruction::Random { vx, byte },
+            0xD => Instruction::Draw { vx, vy, n },
+            0xE => match byte {
+                0x9E => Instruction::SkipKeyPressed { vx },
+                0xA1 => Instruction::SkipKeyNotPressed { vx },
+                _ => Instruction::Unknown(op),
+            },
+            0xF => match byte {
+                0x07 => Instruction::LoadDelay { vx },
+                0x0A => Instruction::WaitKey { vx },
+                0x15 => Instruction::SetDelay { vx },
+                0x18 => Instruction::SetSound { vx },
+                0x1E => Instruction::AddI { vx },
+                0x29 => Instruction::LoadFont { vx },
+                0x30 => Instruction::LoadLargeFont { vx },
+                0x33 => Instruction::Bcd { vx },
+                0x55 => Instruction::StoreRegs { vx },
+                0x65 => Instruction::LoadRegs { vx },
+                0x75 => Instruction::StoreFlags { vx },
+                0x85 => Instruction::LoadFlags { vx },
+                _ => Instruction::Unknown(op),
+            },
+            _ => Instruction::Unknown(op),
+        }
+    }
+
+    /// Decodes with CHIP-8X's opcodes on top of the CHIP-8 ones. BNNN is
+    /// always `ColorZones`, since CHIP-8X gave it up.
+    pub fn decode_chip8x(op: u16) -> Self {
+        let vx = ((op & 0x0F00) >> 8) as u8;
+        let vy = ((op & 0x00F0) >> 4) as u8;
+        let n = (op & 0x000F) as u8;
+
+        match op {
+            0x02A0 => Instruction::CycleBackground,
+            0x02A2 | 0x02A4 | 0x02A6 => Instruction::SetForeground(n / 2 - 1),
+            _ => match (op >> 12, n) {
+                (0x5, 0x1) => Instruction::AddNibbles { vx, vy },
+                (0x5, 0x2) => Instruction::StepNibbles { vx, vy },
+                (0xB, _) => Instruction::ColorZones { vx, vy, n },
+                _ => Instruction::decode(op),
+            },
+        }
+    }
+
+    /// The opcode for this instruction, the inverse of `decode`, or of
+    /// `decode_chip8x` for the CHIP-8X variants. Operands are
+    /// masked to their field width, so VX = 0x1F encodes as VF.
+    pub fn encode(&self) -> u16 {
+        let xy = |base: u16, vx: u8, vy: u8| base | reg(vx) << 8 | reg(vy) << 4;
+        let xnn = |base: u16, vx: u8, byte: u8| base | reg(vx) << 8 | byte as u16;
+
+        match *self {
+            Instruction::Nop => 0x0000,
+            Instruction::ScrollDown(n) => 0x00C0 | reg(n),
+            Instruction::ScrollUp(n) => 0x00D0 | reg(n),
+            Instruction::Cls => 0x00E0,
+            Instruction::Ret => 0x00EE,
+            Instruction::ScrollRight => 0x00FB,
+            Instruction::ScrollLeft => 0x00FC,
+            Instruction::Exit => 0x00FD,
+            Instruction::LowRes => 0x00FE,
+            Instruction::HighRes => 0x00FF,
+            Instruction::CycleBackground => 0x02A0,
+            // offsets past 2 have no opcode
+            Instruction::SetForeground(offset) => 0x02A2 + 2 * offset.min(2) as u16,
+            Instruction::Jump(nnn) => 0x1000 | nnn & 0x0FFF,
+            Instruction::Call(nnn) => 0x2000 | nnn & 0x0FFF,
+            Instruction::SkipEqByte { vx, byte } => xnn(0x3000, vx, byte),
+            Instruction::SkipNeByte { vx, byte } => xnn(0x4000, vx, byte),
+            Instruction::SkipEqReg { vx, vy } => xy(0x5000, vx, vy),
+            Instruction::AddNibbles { vx, vy } => xy(0x5001, vx, vy),
+            Instruction::StepNibbles { vx, vy } => xy(0x5002, vx, vy),
+            Instruction::LoadByte { vx, byte } => xnn(0x6000, vx, byte),
+            Instruction::AddByte { vx, byte } => xnn(0x7000, vx, byte),
+            Instruction::LoadReg { vx, vy } => xy(0x8000, vx, vy),
+            Instruction::Or { vx, vy } => xy(0x8001, vx, vy),
+            Instruction::And { vx, vy } => xy(0x8002, vx, vy),
+            Instruction::Xor { vx, vy } => xy(0x8003, vx, vy),
+            Instruction::AddReg { vx, vy } => xy(0x8004, vx, vy),
+            Instruction::SubReg { vx, vy } => xy(0x8005, vx, vy),
+            Instruction::ShiftRight { vx, vy } => xy(0x8006, vx, vy),
+            Instruction::SubN { vx, vy } => xy(0x8007, vx, vy),
+            Instruction::ShiftLeft { vx, vy } => xy(0x800E, vx, vy),
+            Instruction::SkipNeReg { vx, vy } => xy(0x9000, vx, vy),
+            Instruction::LoadI(nnn) => 0xA000 | nnn & 0x0FFF,
+            Instruction::JumpV0(nnn) => 0xB000 | nnn & 0x0FFF,
+            Instruction::ColorZones { vx, vy, n } => xy(0xB000, vx, vy) | reg(n),
+            Instruction::Random { vx, byte } => xnn(0xC000, vx, byte),
+            Instruction::Draw { vx, vy, n } => xy(0xD000, vx, vy) | reg(n),
+            Instruction::SkipKeyPressed { vx } => xnn(0xE000, vx, 0x9E),
+            Instruction::SkipKeyNotPressed { vx } => xnn(0xE000, vx, 0xA1),
+            Instruction::LoadDelay { vx } => xnn(0xF000, vx, 0x07),
+            Instruction::WaitKey { vx } => xnn(0xF000, vx, 0x0A),
+            Instruction::SetDelay { vx } => xnn(0xF000, vx, 0x15),
+            Instruction::SetSound { vx } => xnn(0xF000, vx, 0x18),
+            Instruction::AddI { vx } => xnn(0xF000, vx, 0x1E),
+            Instruction::LoadFont { vx } => xnn(0xF000, vx, 0x29),
+            Instruction::LoadLargeFont { vx } => xnn(0xF000, vx, 0x30),
+            Instruction::Bcd { vx } => xnn(0xF000, vx, 0x33),
+            Instruction::StoreRegs { vx } => xnn(0xF000, vx, 0x55),
+            Instruction::LoadRegs { vx } => xnn(0xF000, vx, 0x65),
+            Instruction::StoreFlags { vx } => xnn(0xF000, vx, 0x75),
+            Instruction::LoadFlags { vx } => xnn(0xF000, vx, 0x85),
+            Instruction::Unknown(op) => op,
+        }
+    }
+
+    /// The first nibble of the opcode, e.g. 0x8 for all the ALU instructions.
+    pub fn family(&self) -> u8 {
+        (self.encode() >> 12) as u8
+    }
+}
+
+/// Assembly mnemonics in the style of Cowgod's reference, e.g. `LD V3, 0x42`
+/// or `DRW V1, V2, 5`. Unknown opcodes print as `DB 0xXXXX`. Cowgod has no
+/// CHIP-8X, those follow the same pattern: `BGC`, `LD FG, [I+1]`,
+/// `ADDN V1, V2`, `STEPN V1, V2` and `COL V1, V2, 4`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::Nop => write!(f, "NOP"),
+            Instruction::ScrollDown(n) => write!(f, "SCD {}", n & 0xF),
+            Instruction::ScrollUp(n) => write!(f, "SCU {}", n & 0xF),
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::ScrollRight => write!(f, "SCR"),
+            Instruction::ScrollLeft => write!(f, "SCL"),
+            Instruction::Exit => write!(f, "EXIT"),
+            Instruction::LowRes => write!(f, "LOW"),
+            Instruction::HighRes => write!(f, "HIGH"),
+            Instruction::CycleBackground => write!(f, "BGC"),
+            Instruction::SetForeground(offset) => write!(f, "LD FG, [I+{}]", offset.min(2)),
+            Instruction::Jump(nnn) => write!(f, "JP {:#05X}", nnn & 0x0FFF),
+            Instruction::Call(nnn) => write!(f, "CALL {:#05X}", nnn & 0x0FFF),
+            Instruction::SkipEqByte { vx, byte } => write!(f, "SE V{:X}, {:#04X}", vx & 0xF, byte),
+            Instruction::SkipNeByte { vx, byte } => {
+                write!(f, "SNE V{:X}, {:#04X}", vx & 0xF, byte)
+            }
+            Instruction::SkipEqReg { vx, vy } => write!(f, "SE V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::AddNibbles { vx, vy } => {
+                write!(f, "ADDN V{:X}, V{:X}", vx & 0xF, vy & 0xF)
+            }
+            Instruction::StepNibbles { vx, vy } => {
+                write!(f, "STEPN V{:X}, V{:X}", vx & 0xF, vy & 0xF)
+            }
+            Instruction::LoadByte { vx, byte } => write!(f, "LD V{:X}, {:#04X}", vx & 0xF, byte),
+            Instruction::AddByte { vx, byte } => write!(f, "ADD V{:X}, {:#04X}", vx & 0xF, byte),
+            Instruction::LoadReg { vx, vy } => write!(f, "LD V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::Or { vx, vy } => write!(f, "OR V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::And { vx, vy } => write!(f, "AND V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::Xor { vx, vy } => write!(f, "XOR V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::AddReg { vx, vy } => write!(f, "ADD V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::SubReg { vx, vy } => write!(f, "SUB V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::ShiftRight { vx, vy } => {
+                write!(f, "SHR V{:X}, V{:X}", vx & 0xF, vy & 0xF)
+            }
+            Instruction::SubN { vx, vy } => write!(f, "SUBN V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::ShiftLeft { vx, vy } => write!(f, "SHL V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::SkipNeReg { vx, vy } => write!(f, "SNE V{:X}, V{:X}", vx & 0xF, vy & 0xF),
+            Instruction::LoadI(nnn) => write!(f, "LD I, {:#05X}", nnn & 0x0FFF),
+            Instruction::JumpV0(nnn) => write!(f, "JP V0, {:#05X}", nnn & 0x0FFF),
+            Instruction::ColorZones { vx, vy, n } => {
+                write!(f, "COL V{:X}, V{:X}, {}", vx & 0xF, vy & 0xF, n & 0xF)
+            }
+            Instruction::Random { vx, byte } => write!(f, "RND V{:X}, {:#04X}", vx & 0xF, byte),
+            Instruction::Draw { vx, vy, n } => {
+                write!(f, "DRW V{:X}, V{:X}, {}", vx & 0xF, vy & 0xF, n & 0xF)
+            }
+            Instruction::SkipKeyPressed { vx } => write!(f, "SKP V{:X}", vx & 0xF),
+            Instruction::SkipKeyNotPressed { vx } => write!(f, "SKNP V{:X}", vx & 0xF),
+            Instruction::LoadDelay { vx } => write!(f, "LD V{:X}, DT", vx & 0xF),
+            Instruction::WaitKey { vx } => write!(f, "LD V{:X}, K", vx & 0xF),
+            Instruction::SetDelay { vx } => write!(f, "LD DT, V{:X}", vx & 0xF),
+            Instruction::SetSound { vx } => write!(f, "LD ST, V{:X}", vx & 0xF),
+            Instruction::AddI { vx } => write!(f, "ADD I, V{:X}", vx & 0xF),
+            Instruction::LoadFont { vx } => write!(f, "LD F, V{:X}", vx & 0xF),
+            Instruction::LoadLargeFont { vx } => write!(f, "LD HF, V{:X}", vx & 0xF),
+            Instruction::Bcd { vx } => write!(f, "LD B, V{:X}", vx & 0xF),
+            Instruction::StoreRegs { vx } => write!(f, "LD [I], V{:X}", vx & 0xF),
+            Instruction::LoadRegs { vx } => write!(f, "LD V{:X}, [I]", vx & 0xF),
+            Instruction::StoreFlags { vx } => write!(f, "LD R, V{:X}", vx & 0xF),
+            Instruction::LoadFlags { vx } => write!(f, "LD V{:X}, R", vx & 0xF),
+            Instruction::Unknown(op) => write!(f, "DB {:#06X}", op),
+        }
+    }
+}
+
+fn reg(idx: u8) -> u16 {
+    (idx & 0xF) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn usable_as_keys_and_sorted_by_opcode() {
+        let mut counts = HashMap::new();
+        *counts.entry(Instruction::Cls).or_insert(0u32) += 1;
+        *counts.entry(Instruction::Ret).or_insert(0u32) += 1;
+        *counts.entry(Instruction::Cls).or_insert(0u32) += 1;
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&Instruction::Cls], 2);
+        assert_eq!(counts[&Instruction::Ret], 1);
+
+        assert!(Instruction::Jump(0x200) < Instruction::Jump(0x300));
+        let mut sorted = vec![Instruction::Jump(0x200), Instruction::Ret, Instruction::Cls];
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            [Instruction::Cls, Instruction::Ret, Instruction::Jump(0x200)]
+        );
+        assert!(Instruction::LoadFlags { vx: 0xF } < Instruction::Unknown(0x0001));
+        assert_eq!(Instruction::Jump(0x200).family(), 0x1);
+    }
+
+    #[test]
+    fn chip8x_opcodes_decode_only_in_chip8x() {
+        let cases = [
+            (0x02A0, Instruction::CycleBackground, "BGC"),
+            (0x02A4, Instruction::SetForeground(1), "LD FG, [I+1]"),
+            (
+                0x5121,
+                Instruction::AddNibbles { vx: 1, vy: 2 },
+                "ADDN V1, V2",
+            ),
+            (
+                0x5122,
+                Instruction::StepNibbles { vx: 1, vy: 2 },
+                "STEPN V1, V2",
+            ),
+            (
+                0xB124,
+                Instruction::ColorZones { vx: 1, vy: 2, n: 4 },
+                "COL V1, V2, 4",
+            ),
+        ];
+        for (op, instruction, mnemonic) in cases {
+            assert_eq!(Instruction::decode_chip8x(op), instruction);
+            assert_eq!(instruction.encode(), op);
+            assert_eq!(instruction.to_string(), mnemonic);
+        }
+        assert_eq!(Instruction::decode(0x02A0), Instruction::Unknown(0x02A0));
+        assert_eq!(Instruction::decode(0x5121), Instruction::Unknown(0x5121));
+        assert_eq!(Instruction::decode(0xB124), Instruction::JumpV0(0x124));
+        // the rest is shared
+        assert_eq!(
+            Instruction::decode_chip8x(0x5120),
+            Instruction::SkipEqReg { vx: 1, vy: 2 }
+        );
+        assert_eq!(
+            Instruction::decode_chip8x(0x5123),
+            Instruction::Unknown(0x5123)
+        );
+        assert_eq!(
+            Instruction::decode_chip8x(0x02A8),
+            Instruction::Unknown(0x02A8)
+        );
+        assert_eq!(Instruction::decode_chip8x(0x00E0), Instruction::Cls);
+    }
+
+    #[test]
+    fn chip8x_variants_sort_in_opcode_order() {
+        let mut sorted = [
+            Instruction::ColorZones { vx: 0, vy: 0, n: 0 },
+            Instruction::AddNibbles { vx: 0, vy: 0 },
+            Instruction::Jump(0x200),
+            Instruction::SetForeground(0),
+            Instruction::CycleBackground,
+            Instruction::JumpV0(0xFFF),
+            Instruction::StepNibbles { vx: 0, vy: 0 },
+            Instruction::SkipEqReg { vx: 0xF, vy: 0xF },
+        ];
+        sorted.sort();
+        let opcodes = sorted.map(|instruction| instruction.encode());
+        assert_eq!(
+            opcodes,
+            [
+                0x02A0, 0x02A2, 0x1200, 0x5FF0, 0x5001, 0x5002, 0xBFFF, 0xB000
+            ]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(feature = "std")]
+use crate::KEY_COUNT;
+use crate::{Emulator, ScreenBuffer};
+
+/// Hold time before a held key starts repeating.
+pub const DEFAULT_KEY_REPEAT_DELAY_MS: u32 = 500;
+/// Time between repeats once a key is repeating.
+pub const DEFAULT_KEY_REPEAT_INTERVAL_MS: u32 = 50;
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Sets how long a key must be held before it repeats and how often it
+    /// repeats after that. A repeat shows up as `is_key_just_pressed` for one
+    /// tick, the held state doesn't change. Repeats need the "std" feature.
+    pub fn set_key_repeat(&mut self, delay_ms: u32, interval_ms: u32) {
+        self.key_repeat_delay_ms = delay_ms;
+        self.key_repeat_interval_ms = interval_ms;
+    }
+
+    /// Returns `(delay_ms, interval_ms)`.
+    pub fn key_repeat(&self) -> (u32, u32) {
+        (self.key_repeat_delay_ms, self.key_repeat_interval_ms)
+    }
+
+    // called from keypress() with the new state, before `keys` is updated
+    #[cfg(feature = "std")]
+    pub(crate) fn track_key_hold(&mut self, idx: usize, pressed: bool, now: Instant) {
+        if !pressed {
+            self.key_hold_start[idx] = None;
+        } else if !self.keys[idx] {
+            self.key_hold_start[idx] = Some(now);
+            self.key_repeats_fired[idx] = 0;
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn fire_key_repeats(&mut self, now: Instant) {
+        let delay = self.key_repeat_delay_ms as u128;
+        let interval = self.key_repeat_interval_ms.max(1) as u128;
+        for idx in 0..KEY_COUNT {
+            let Some(start) = self.key_hold_start[idx] else {
+                continue;
+            };
+            let held = now.saturating_duration_since(start).as_millis();
+            if held < delay {
+                continue;
+            }
+            // one repeat at the delay, then one per interval
+            let due = ((held - delay) / interval + 1) as u32;
+            if due > self.key_repeats_fired[idx] {
+                self.key_repeats_fired[idx] = due;
+                self.keys_just_pressed[idx] = true;
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn clear_key_holds(&mut self) {
+        self.key_hold_start = [None; KEY_COUNT];
+        self.key_repeats_fired = [0; KEY_COUNT];
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn holding_a_key_past_the_delay_repeats_it() {
+        let mut chip8 = Emulator::new();
+        assert_eq!(
+            chip8.key_repeat(),
+            (DEFAULT_KEY_REPEAT_DELAY_MS, DEFAULT_KEY_REPEAT_INTERVAL_MS)
+        );
+        chip8.keypress(5, true);
+        let start = chip8.key_hold_start[5].unwrap();
+        chip8.keys_just_pressed = [false; KEY_COUNT];
+
+        chip8.fire_key_repeats(start + Duration::from_millis(499));
+        assert!(!chip8.is_key_just_pressed(5));
+
+        chip8.fire_key_repeats(start + Duration::from_millis(600));
+        assert!(chip8.is_key_just_pressed(5));
+        // the one at 500 ms and one per 50 ms after it
+        assert_eq!(chip8.key_repeats_fired[5], 3);
+        assert!(!chip8.is_key_just_pressed(4));
+
+        chip8.keys_just_pressed = [false; KEY_COUNT];
+        chip8.fire_key_repeats(start + Duration::from_millis(620));
+        assert!(!chip8.is_key_just_pressed(5));
+    }
+
+    #[test]
+    fn releasing_a_key_stops_the_repeat() {
+        let mut chip8 = Emulator::new();
+        chip8.set_key_repeat(100, 10);
+        chip8.keypress(2, true);
+        let start = chip8.key_hold_start[2].unwrap();
+        chip8.keypress(2, false);
+        chip8.keys_just_pressed = [false; KEY_COUNT];
+
+        chip8.fire_key_repeats(start + Duration::from_millis(600));
+        assert!(!chip8.is_key_just_pressed(2));
+        assert!(!chip8.is_key_pressed(2));
+    }
+}
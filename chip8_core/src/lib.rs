@@ -1,12 +1,122 @@
+pub mod analysis;
+#[cfg(feature = "async")]
+pub mod async_emu;
+mod builder;
+mod catalog;
+mod chip8x;
+mod cow_ram;
+pub mod diff;
+mod dispatch;
+mod dump;
+mod error;
+mod event;
+mod handle;
+mod hash;
+#[cfg(feature = "history")]
+mod history;
+#[cfg(feature = "hooks")]
+mod hooks;
+mod instruction;
+mod key_repeat;
+#[cfg(feature = "mem-log")]
+mod mem_log;
+mod mmio;
+#[cfg(feature = "profiling")]
+mod profiling;
+mod quirks;
+#[cfg(feature = "repl")]
+pub mod repl;
+mod replay;
+mod rng;
+mod rom;
+pub mod roms;
+mod save_state;
+mod screen;
+mod snapshot;
+mod state;
+mod step;
+mod summary;
+mod superchip;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+mod thread;
+pub mod timing;
+#[cfg(feature = "trace")]
+mod trace;
+mod transform;
+
+#[cfg(feature = "history")]
+pub use history::StateHistory;
+#[cfg(feature = "hooks")]
+pub use hooks::Hook;
+#[cfg(feature = "mem-log")]
+pub use mem_log::MemoryAccessLog;
+
+use std::cell::Cell;
+use std::slice::ChunksExact;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+pub use analysis::{
+    DisassembledInstruction, DisassemblyLine, DisassemblyListing, disassemble, disassemble_chip8x,
+};
+pub use builder::EmulatorBuilder;
+pub use catalog::{ROM_CATALOG, RomEntry, lookup_rom};
+pub use chip8x::{
+    CHIP8X_COLORS, CHIP8X_START_ADDR, COLOR_RAM_SIZE, COLOR_RAM_START, COLOR_ZONE_WIDTH,
+};
+use cow_ram::CowRam;
+pub use error::{Chip8Error, ChipError, ChipErrorContext, ChipWarning};
+pub use event::EmulatorEvent;
+pub use handle::EmulatorHandle;
+pub use instruction::Instruction;
+pub use key_repeat::{DEFAULT_KEY_REPEAT_DELAY_MS, DEFAULT_KEY_REPEAT_INTERVAL_MS};
+use mmio::MmioRegion;
+pub use mmio::{CHAR_OUTPUT_END, CHAR_OUTPUT_START, MmioRead, MmioWrite};
+pub use quirks::{DEFAULT_QUIRKS, ParseQuirksError, Quirks, QuirksConfig};
+pub use replay::{InputEvent, REPLAY_TICKS_PER_FRAME, ReplayError, ReplayFile, ScreenAssertion};
+#[cfg(feature = "rand")]
+pub use rng::SmallRngSource;
+pub use rng::{CloneRngSource, ConstantRng, LcgRng, RngSource};
+pub use rom::{MAX_ROM_SIZE, RomInfo, Variant};
+pub use save_state::{JSON_SAVE_STATE_EXTENSION, SaveState};
+pub use screen::{BoolArrayScreen, CoordInfo, PackedBitScreen, ScreenBuffer};
+use snapshot::Checkpoint;
+pub use snapshot::EmulatorSnapshot;
+pub use state::{COMPRESSED_SAVE_STATE_EXTENSION, SAVE_STATE_EXTENSION};
+pub use step::{StepInfo, StepResult};
+pub use summary::ExecutionSummary;
+use summary::RunCounters;
+pub use superchip::{Chip8Mode, RPL_FLAG_COUNT};
+pub use thread::{EmulatorCommand, ThreadConfig};
+#[cfg(feature = "trace")]
+pub use trace::ExecutionTrace;
+pub use transform::DisplayTransform;
+
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
-const RAM_SIZE: usize = 4096;
-const NUM_REGS: usize = 16;
-const STACK_SIZE: usize = 16;
-const NUM_KEYS: usize = 16;
-const START_ADDR: u16 = 0x200;
-const FONTSET_SIZE: usize = 80;
-const FONTSET: [u8; FONTSET_SIZE] = [
+/// SUPER-CHIP high resolution, switched on by 00FF. See `Emulator::screen_width`
+/// for the resolution in use.
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+pub const RAM_SIZE: usize = 4096;
+/// V0 through VF.
+pub const REGISTER_COUNT: usize = 16;
+/// Default call stack depth, see `EmulatorBuilder::stack_size`.
+pub const STACK_SIZE: usize = 16;
+/// Keys on the hex keypad.
+pub const KEY_COUNT: usize = 16;
+/// Where programs are loaded by default, see `EmulatorBuilder::start_address`.
+pub const START_ADDR: u16 = 0x200;
+/// Space for a program loaded at `START_ADDR`.
+pub const PROGRAM_SIZE: usize = RAM_SIZE - START_ADDR as usize;
+#[deprecated(note = "renamed to REGISTER_COUNT")]
+pub const NUM_REGS: usize = REGISTER_COUNT;
+#[deprecated(note = "renamed to KEY_COUNT")]
+pub const NUM_KEYS: usize = KEY_COUNT;
+pub const FONTSET_SIZE: usize = 80;
+/// The 4x5 hex digit font loaded at address 0, five bytes per glyph.
+pub const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
     0x20, 0x60, 0x20, 0x20, 0x70, // 1
     0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
@@ -24,205 +134,887 @@ const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
+// SUPER-CHIP 8x10 hex digits, stored directly after the small font.
+pub const LARGE_FONT_START: u16 = 80;
+pub const LARGE_FONTSET_SIZE: usize = 160;
+/// The 8x10 hex digit font loaded at `LARGE_FONT_START`, ten bytes per glyph.
+pub const LARGE_FONTSET: [u8; LARGE_FONTSET_SIZE] = [
+    0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, // 0
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF, // 1
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // 2
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 3
+    0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 5
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 6
+    0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, // 7
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 8
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, // F
+];
+
+/// Returns the glyph for hex digit `c` as rows of pixels. Small glyphs only
+/// fill the first five rows and the left four columns.
+pub fn render_font_char(c: u8, large: bool) -> [[bool; 8]; 10] {
+    let (font, height): (&[u8], usize) = if large {
+        (&LARGE_FONTSET, 10)
+    } else {
+        (&FONTSET, 5)
+    };
+    let start = (c & 0xF) as usize * height;
+
+    let mut glyph = [[false; 8]; 10];
+    for (row, bits) in glyph.iter_mut().zip(&font[start..start + height]) {
+        for (col, pixel) in row.iter_mut().enumerate() {
+            *pixel = bits & (0b1000_0000 >> col) != 0;
+        }
+    }
+    glyph
+}
+
+/// Renders a glyph as lines of `#` (on) and `.` (off), five lines for the
+/// small font and ten for the large one.
+pub fn font_char_to_string(c: u8, large: bool) -> String {
+    let height = if large { 10 } else { 5 };
+    render_font_char(c, large)[..height]
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|on| if *on { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-pub struct Emulator {
+/// Renders a large font glyph as ten lines of `#` (on) and `.` (off), for debugging.
+pub fn render_large_font_char(c: u8) -> String {
+    font_char_to_string(c, true)
+}
+
+/// Outcome of a single `tick()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickResult {
+    /// An instruction executed with nothing notable to report.
+    Ran,
+    /// The screen changed (DXYN or CLS), callers should re-render.
+    Draw,
+    /// Blocked in FX0A waiting for a key press or release.
+    KeyWait,
+    /// Blocked after DXYN until `trigger_frame_interrupt`, see `QuirksConfig::display_wait`.
+    WaitingForVblank,
+    /// Execution stopped before the instruction at this address.
+    BreakpointHit(u16),
+    /// The watchdog limit was reached, no further instructions run.
+    Halted,
+    Error(ChipError),
+}
+
+/// A CHIP-8 machine. The display storage is pluggable through `S`.
+///
+/// Clones are independent machines, except that hooks and MMIO callbacks
+/// are shared with the original rather than copied.
+#[derive(Clone)]
+pub struct Emulator<S: ScreenBuffer = BoolArrayScreen> {
     pc: u16,
-    ram: [u8; RAM_SIZE],
-    screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
-    v_reg: [u8; NUM_REGS],
+    ram: CowRam,
+    screen: S,
+    v_reg: [u8; REGISTER_COUNT],
     i_reg: u16,
-    stack: [u16; STACK_SIZE],
+    stack: Vec<u16>,
     sp: u16,
-    keys: [bool; NUM_KEYS],
+    keys: [bool; KEY_COUNT],
+    // edges seen by keypress() since the last tick, for host-side logic only
+    keys_just_pressed: [bool; KEY_COUNT],
+    keys_just_released: [bool; KEY_COUNT],
+    key_repeat_delay_ms: u32,
+    key_repeat_interval_ms: u32,
+    #[cfg(feature = "std")]
+    key_hold_start: [Option<Instant>; KEY_COUNT],
+    // repeats already reported for the current hold
+    #[cfg(feature = "std")]
+    key_repeats_fired: [u32; KEY_COUNT],
     dt: u8,
-    pub st: u8,
-    pub draw_completed: bool,
-    waiting_for_key_release: Option<usize>,
+    st: u8,
+    // set by DXYN under display_wait, cleared by the next frame interrupt
+    waiting_for_vblank: bool,
+    // pixels turned off by the most recent DXYN
+    collision_count: u8,
+    // get_screen_hash result, cleared whenever a pixel may have changed
+    screen_hash: Cell<Option<u64>>,
+    // set along with clearing screen_hash, cleared by mark_draw_complete
+    screen_dirty: bool,
+    mode: Chip8Mode,
+    // saved and loaded by the SUPER-CHIP FX75/FX85, kept across resets
+    rpl_flags: [u8; RPL_FLAG_COUNT],
+    // CHIP-8X zone colours and background, see the chip8x module
+    color_ram: [u8; COLOR_RAM_SIZE],
+    background_color: u8,
+    foreground_color: Option<u8>,
+    waiting_for_key_release: Option<u8>,
+    // X of the FX0A that is blocking, set until the key is released
+    waiting_key_register: Option<u8>,
+    mmio: Vec<MmioRegion>,
+    mmio_output: Vec<char>,
+    quirks: QuirksConfig,
+    // kept for soft_reset, empty when no ROM is loaded
+    loaded_rom: Vec<u8>,
+    // checked by restore_save_state, 0 when no ROM is loaded
+    rom_crc32: u32,
+    start_addr: u16,
+    warnings: Vec<ChipWarning>,
+    events: Vec<EmulatorEvent>,
+    elapsed_cycles: u64,
+    // count instructions by their VIP cycle cost instead of 1 each
+    cycle_accurate_timing: bool,
+    watchdog_limit: Option<u64>,
+    halted: bool,
+    breakpoints: Vec<u16>,
+    resuming_from_breakpoint: bool,
+    rng: Box<dyn RngSource>,
+    // leftover time from tick_timers_with_dt, in milliseconds scaled by 60
+    timer_accumulator_ms: u32,
+    // baseline for take_snapshot/restore_snapshot
+    checkpoint: Option<Checkpoint>,
+    counters: RunCounters,
+    #[cfg(feature = "history")]
+    history: Option<StateHistory>,
+    #[cfg(feature = "mem-log")]
+    mem_log: Option<MemoryAccessLog>,
+    #[cfg(feature = "trace")]
+    trace: Option<ExecutionTrace>,
+    #[cfg(feature = "profiling")]
+    pixel_flip_count: Box<[u32; HIRES_WIDTH * HIRES_HEIGHT]>,
+    // executions and wall-clock time per opcode family, indexed by the first nibble
+    #[cfg(feature = "profiling")]
+    opcode_calls: [u64; 16],
+    #[cfg(feature = "profiling")]
+    opcode_time_ns: [u64; 16],
+    // bit n is set once the instruction at address 2n has been fetched
+    #[cfg(feature = "profiling")]
+    pc_coverage: bitvec::vec::BitVec,
+    #[cfg(feature = "hooks")]
+    pre_hooks: Vec<hooks::HookEntry<S>>,
+    #[cfg(feature = "hooks")]
+    post_hooks: Vec<hooks::HookEntry<S>>,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
+// `Emulator::default()` is public API, fail the build if it goes away
+const _: () = {
+    const fn assert_default<T: Default>() {}
+    assert_default::<Emulator>();
+};
+
 impl Emulator {
     pub fn new() -> Self {
-        let mut new_emulator = Emulator {
-            pc: START_ADDR,
-            ram: [0; RAM_SIZE],
-            screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
-            v_reg: [0; NUM_REGS],
-            i_reg: 0,
-            sp: 0,
-            stack: [0; STACK_SIZE],
-            keys: [false; NUM_KEYS],
-            dt: 0,
-            st: 0,
-            draw_completed: true,
-            waiting_for_key_release: None,
-        };
+        Self::new_with_quirks(QuirksConfig::default())
+    }
+
+    pub fn new_with_quirks(quirks: QuirksConfig) -> Self {
+        EmulatorBuilder::new().quirks(quirks).build()
+    }
 
-        new_emulator.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
-        new_emulator
+    /// Same as `new_with_quirks`.
+    pub fn with_quirks(quirks: Quirks) -> Self {
+        Self::new_with_quirks(quirks)
     }
 
-    pub fn is_key_pressed(&self) -> bool {
+    /// A new emulator with `data` loaded. Fails with `RomTooLarge` if it
+    /// does not fit in RAM.
+    pub fn new_with_rom(data: &[u8]) -> Result<Self, Chip8Error> {
+        Self::new_with_rom_and_quirks(data, Quirks::default())
+    }
+
+    pub fn new_with_rom_and_quirks(data: &[u8], quirks: Quirks) -> Result<Self, Chip8Error> {
+        let mut emulator = Self::new_with_quirks(quirks);
+        emulator.load_rom_checked(data)?;
+        Ok(emulator)
+    }
+
+    /// An emulator whose CXNN draws from `rng`.
+    pub fn with_rng(rng: impl RngSource + 'static) -> Self {
+        EmulatorBuilder::new().rng(rng).build()
+    }
+
+    /// The pixels of the active resolution, `screen_width()` per row.
+    pub fn get_display(&self) -> &[bool] {
+        self.screen.pixels()
+    }
+
+    /// `get_display` one row at a time, top to bottom. Rows are slices
+    /// rather than arrays since their width depends on the resolution.
+    pub fn get_display_2d(&self) -> ChunksExact<'_, bool> {
+        self.screen.pixels().chunks_exact(self.screen.width())
+    }
+
+    /// `get_pixel` without the bounds check, for renderers that already loop
+    /// over the screen dimensions.
+    ///
+    /// # Safety
+    ///
+    /// `x` must be less than `screen_width()` and `y` less than `screen_height()`.
+    pub unsafe fn get_pixel_unchecked(&self, x: usize, y: usize) -> bool {
+        // SAFETY: the caller guarantees the index is inside the screen array
+        unsafe {
+            *self
+                .screen
+                .pixels()
+                .get_unchecked(x + self.screen.width() * y)
+        }
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Same as `new()`, but drawing into the given screen buffer.
+    pub fn with_screen(screen: S) -> Self {
+        EmulatorBuilder::new().build_with_screen(screen)
+    }
+
+    pub fn is_any_key_pressed(&self) -> bool {
         self.keys.iter().any(|k| *k)
     }
 
-    pub fn push(&mut self, val: u16) {
+    /// Out of range indices are reported as not pressed.
+    pub fn is_key_pressed(&self, idx: usize) -> bool {
+        self.keys.get(idx).copied().unwrap_or(false)
+    }
+
+    /// True if the key went down since the last tick. EX9E/EXA1 only look at the
+    /// held state, the edge flags exist for host-side logic.
+    pub fn is_key_just_pressed(&self, idx: usize) -> bool {
+        self.keys_just_pressed.get(idx).copied().unwrap_or(false)
+    }
+
+    /// True if the key went up since the last tick.
+    pub fn is_key_just_released(&self, idx: usize) -> bool {
+        self.keys_just_released.get(idx).copied().unwrap_or(false)
+    }
+
+    pub fn get_pressed_keys(&self) -> impl Iterator<Item = usize> + '_ {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(_, pressed)| **pressed)
+            .map(|(idx, _)| idx)
+    }
+
+    pub fn get_pc(&self) -> u16 {
+        self.pc
+    }
+
+    /// V0 through VF.
+    pub fn get_registers(&self) -> &[u8; REGISTER_COUNT] {
+        &self.v_reg
+    }
+
+    pub fn get_i(&self) -> u16 {
+        self.i_reg
+    }
+
+    pub fn get_dt(&self) -> u8 {
+        self.dt
+    }
+
+    /// The sound timer, a tone plays while it is above zero.
+    pub fn get_st(&self) -> u8 {
+        self.st
+    }
+
+    /// Same as `get_st`.
+    pub fn sound_timer(&self) -> u8 {
+        self.st
+    }
+
+    pub fn get_sp(&self) -> u16 {
+        self.sp
+    }
+
+    /// The whole stack, entries at `get_sp()` and above are stale.
+    pub fn get_stack(&self) -> &[u16] {
+        &self.stack
+    }
+
+    pub fn get_keys(&self) -> &[bool; KEY_COUNT] {
+        &self.keys
+    }
+
+    pub fn push(&mut self, val: u16) -> Result<(), ChipError> {
+        if self.sp as usize >= self.stack.len() {
+            return Err(ChipError::StackOverflow);
+        }
         self.stack[self.sp as usize] = val;
         self.sp += 1;
+        Ok(())
     }
 
-    pub fn pop(&mut self) -> u16 {
+    pub fn pop(&mut self) -> Result<u16, ChipError> {
+        if self.sp == 0 {
+            return Err(ChipError::StackUnderflow);
+        }
         self.sp -= 1;
-        self.stack[self.sp as usize]
+        Ok(self.stack[self.sp as usize])
     }
 
     pub fn reset(&mut self) {
-        self.pc = START_ADDR;
-        self.ram = [0; RAM_SIZE];
-        self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
-        self.v_reg = [0; NUM_REGS];
+        self.pc = self.start_addr;
+        self.ram = CowRam::default();
+        self.screen.set_hires(false);
+        self.screen_changed();
+        self.v_reg = [0; REGISTER_COUNT];
         self.i_reg = 0;
         self.sp = 0;
-        self.stack = [0; STACK_SIZE];
-        self.keys = [false; NUM_KEYS];
+        self.stack.fill(0);
+        self.keys = [false; KEY_COUNT];
+        self.keys_just_pressed = [false; KEY_COUNT];
+        self.keys_just_released = [false; KEY_COUNT];
+        #[cfg(feature = "std")]
+        self.clear_key_holds();
         self.dt = 0;
         self.st = 0;
+        self.timer_accumulator_ms = 0;
+        self.collision_count = 0;
+        self.waiting_for_vblank = false;
+        self.waiting_for_key_release = None;
+        self.waiting_key_register = None;
+        self.color_ram = [chip8x::DEFAULT_ZONE_COLOR; COLOR_RAM_SIZE];
+        self.background_color = chip8x::DEFAULT_BACKGROUND;
+        self.foreground_color = None;
+        self.mmio_output.clear();
+        self.loaded_rom.clear();
+        self.rom_crc32 = 0;
+        self.warnings.clear();
+        self.events.clear();
+        self.elapsed_cycles = 0;
+        self.halted = false;
+        self.counters = RunCounters::default();
+        #[cfg(feature = "trace")]
+        self.clear_trace();
+        self.load_fonts();
+    }
+
+    /// `reset()`, then loads the ROM that was loaded before it again.
+    pub fn soft_reset(&mut self) {
+        let rom = std::mem::take(&mut self.loaded_rom);
+        self.reset();
+        // already validated when it was first loaded
+        self.copy_rom(&rom);
+    }
+
+    fn load_fonts(&mut self) {
         self.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
+        let large_start = LARGE_FONT_START as usize;
+        self.ram[large_start..large_start + LARGE_FONTSET_SIZE].copy_from_slice(&LARGE_FONTSET);
     }
 
-    pub fn tick(&mut self) {
+    pub fn tick(&mut self) -> TickResult {
+        self.keys_just_pressed = [false; KEY_COUNT];
+        self.keys_just_released = [false; KEY_COUNT];
+        #[cfg(feature = "std")]
+        self.fire_key_repeats(Instant::now());
+        self.counters.ticks += 1;
+        #[cfg(feature = "std")]
+        self.counters.started_at.get_or_insert_with(Instant::now);
+
+        if self.halted {
+            return TickResult::Halted;
+        }
         if self.waiting_for_key_release.is_some() {
-            return;
+            return TickResult::KeyWait;
+        }
+        if self.waiting_for_vblank {
+            return TickResult::WaitingForVblank;
+        }
+
+        if self.breakpoints.contains(&self.pc) && !self.resuming_from_breakpoint {
+            // the next tick runs the instruction instead of stopping here again
+            self.resuming_from_breakpoint = true;
+            return TickResult::BreakpointHit(self.pc);
         }
+        self.resuming_from_breakpoint = false;
+
+        #[cfg(feature = "history")]
+        self.record_history();
 
         // FETCH
-        let op = self.fetch();
+        let op = match self.fetch() {
+            Ok(op) => op,
+            Err(err) => return TickResult::Error(err),
+        };
 
         // DECODE & EXECUTE
-        self.execute(op);
+        let result = match self.execute(op) {
+            Ok(result) => result,
+            Err(err) => return TickResult::Error(err),
+        };
+
+        self.elapsed_cycles += self.instruction_cycles(op);
+        if result == TickResult::Draw {
+            self.counters.draw_calls += 1;
+        }
+        if let Some(limit) = self.watchdog_limit
+            && self.elapsed_cycles >= limit
+        {
+            self.halted = true;
+            return TickResult::Halted;
+        }
+
+        result
     }
 
-    pub fn get_display(&self) -> &[bool] {
+    /// Runs up to `n` ticks, stopping early on a breakpoint, halt or error.
+    /// Returns the result of the last tick.
+    pub fn tick_n(&mut self, n: usize) -> TickResult {
+        let mut result = TickResult::Ran;
+        for _ in 0..n {
+            result = self.tick();
+            if matches!(
+                result,
+                TickResult::BreakpointHit(_) | TickResult::Halted | TickResult::Error(_)
+            ) {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Makes CXNN deterministic from this point on.
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = rng::seeded_rng(seed);
+    }
+
+    /// Replaces the source CXNN draws from.
+    pub fn set_rng(&mut self, rng: impl RngSource + 'static) {
+        self.rng = Box::new(rng);
+    }
+
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        if !self.breakpoints.contains(&addr) {
+            self.breakpoints.push(addr);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.retain(|bp| *bp != addr);
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Halts the emulator once `get_total_ticks()` reaches `max_cycles` since
+    /// the last reset. That counts executed instructions, or COSMAC VIP
+    /// machine cycles with `set_cycle_accurate_timing` on.
+    pub fn set_watchdog(&mut self, max_cycles: u64) {
+        self.watchdog_limit = Some(max_cycles);
+    }
+
+    pub fn clear_watchdog(&mut self) {
+        self.watchdog_limit = None;
+        self.halted = false;
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn get_total_ticks(&self) -> u64 {
+        self.elapsed_cycles
+    }
+
+    pub fn get_max_stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Number of pixels turned off by the most recent DXYN. VF only records whether this was non-zero.
+    pub fn get_last_collision_count(&self) -> u8 {
+        self.collision_count
+    }
+
+    pub fn screen(&self) -> &S {
         &self.screen
     }
 
+    /// Width of the active display in pixels.
+    pub fn screen_width(&self) -> usize {
+        self.screen.width()
+    }
+
+    /// Height of the active display in pixels.
+    pub fn screen_height(&self) -> usize {
+        self.screen.height()
+    }
+
+    /// The pixel at (`x`, `y`), or `None` if that is off the screen.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<bool> {
+        self.pixel_in_bounds(x, y)
+            .then(|| self.screen.get_pixel(x, y))
+    }
+
+    /// Sets a pixel directly, for debug overlays. Unlike DXYN this neither
+    /// XORs nor touches VF.
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: bool) -> Result<(), ChipError> {
+        if !self.pixel_in_bounds(x, y) {
+            return Err(ChipError::PixelOutOfBounds { x, y });
+        }
+        self.screen.set_pixel(x, y, value);
+        self.screen_changed();
+        Ok(())
+    }
+
+    /// Flips every pixel on the screen. Calling it twice restores the original.
+    pub fn invert_screen(&mut self) {
+        self.screen.invert();
+        self.screen_changed();
+    }
+
+    /// Copies the `w` x `h` rectangle at (`src_x`, `src_y`) to (`dst_x`, `dst_y`).
+    /// Pixels whose source or destination is off the screen are skipped, and
+    /// overlapping rectangles copy as if the source was read out first.
+    /// A host-side tool, there is no opcode for it.
+    pub fn blit_region(
+        &mut self,
+        src_x: usize,
+        src_y: usize,
+        w: usize,
+        h: usize,
+        dst_x: usize,
+        dst_y: usize,
+    ) {
+        let mut pixels = Vec::with_capacity(w * h);
+        for dy in 0..h {
+            for dx in 0..w {
+                let (from_x, from_y) = (src_x + dx, src_y + dy);
+                let (to_x, to_y) = (dst_x + dx, dst_y + dy);
+                if self.pixel_in_bounds(from_x, from_y) && self.pixel_in_bounds(to_x, to_y) {
+                    pixels.push((to_x, to_y, self.screen.get_pixel(from_x, from_y)));
+                }
+            }
+        }
+        for (x, y, on) in pixels {
+            self.screen.set_pixel(x, y, on);
+        }
+        self.screen_changed();
+    }
+
+    fn pixel_in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.screen.width() && y < self.screen.height()
+    }
+
+    /// Where DXYN would start drawing for register values `x` and `y`,
+    /// and whether either had to wrap to get there.
+    pub fn validate_draw_coords(&self, x: u8, y: u8) -> CoordInfo {
+        let (width, height) = (self.screen_width(), self.screen_height());
+        CoordInfo {
+            x_wrapped: x as usize >= width,
+            y_wrapped: y as usize >= height,
+            effective_x: (x as usize % width) as u8,
+            effective_y: (y as usize % height) as u8,
+        }
+    }
+
+    /// True while FX0A has seen a key press and is waiting for that key to be released.
+    pub fn is_waiting_for_key_release(&self) -> bool {
+        self.waiting_for_key_release.is_some()
+    }
+
+    pub fn get_waiting_key_index(&self) -> Option<usize> {
+        self.waiting_for_key_release.map(|k| k as usize)
+    }
+
+    /// True while FX0A is blocking, either for a key press or for the
+    /// pressed key to be released.
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.waiting_key_register.is_some() || self.waiting_for_key_release.is_some()
+    }
+
+    /// X of the FX0A that is blocking, the register the key goes into.
+    /// None after restoring a state that was waiting for a release, save
+    /// states don't record it.
+    pub fn waiting_key_register(&self) -> Option<usize> {
+        self.waiting_key_register.map(|x| x as usize)
+    }
+
     pub fn keypress(&mut self, idx: usize, pressed: bool) {
+        #[cfg(feature = "std")]
+        self.track_key_hold(idx, pressed, Instant::now());
+        self.counters.key_events += 1;
+        self.keys_just_pressed[idx] = pressed && !self.keys[idx];
+        self.keys_just_released[idx] = !pressed && self.keys[idx];
         self.keys[idx] = pressed;
 
-        if !pressed && Some(idx) == self.waiting_for_key_release {
+        if !pressed && Some(idx as u8) == self.waiting_for_key_release {
             self.waiting_for_key_release = None;
+            self.waiting_key_register = None;
         }
     }
 
-    pub fn load_rom(&mut self, data: &[u8]) {
-        let start = START_ADDR as usize;
+    /// Copies `data` into RAM at the start address, 0x200 unless the builder
+    /// changed it. Empty ROMs and ROMs that don't fit are rejected, and with
+    /// `QuirksConfig::strict_rom_validation` so are ROMs that start with an
+    /// unknown opcode. RAM is untouched when loading fails.
+    pub fn load_rom(&mut self, data: &[u8]) -> Result<(), ChipError> {
+        let max = RAM_SIZE - self.start_addr as usize;
+        if data.is_empty() {
+            return Err(ChipError::EmptyRom);
+        }
+        if data.len() > max {
+            return Err(ChipError::RomTooLarge {
+                size: data.len(),
+                max,
+            });
+        }
+        if self.quirks.strict_rom_validation {
+            // a one byte ROM reads as if padded with zero
+            let first = u16::from_be_bytes([data[0], data.get(1).copied().unwrap_or(0)]);
+            if let Instruction::Unknown(op) = self.decode_opcode(first) {
+                return Err(ChipError::InvalidRomStart(op));
+            }
+        }
+        self.copy_rom(data);
+        Ok(())
+    }
+
+    fn copy_rom(&mut self, data: &[u8]) {
+        let start = self.start_addr as usize;
         let end = start + data.len();
         self.ram[start..end].copy_from_slice(data);
+        self.loaded_rom = data.to_vec();
+        self.rom_crc32 = save_state::crc32(data);
+    }
+
+    /// Where ROMs are loaded and execution begins.
+    pub fn start_address(&self) -> u16 {
+        self.start_addr
+    }
+
+    pub fn get_quirks(&self) -> QuirksConfig {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: QuirksConfig) {
+        self.quirks = quirks;
+    }
+
+    pub fn drain_warnings(&mut self) -> Vec<ChipWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Events raised since the last call, currently the timer expiries.
+    pub fn drain_events(&mut self) -> Vec<EmulatorEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn validate_pc(&self) -> Result<(), ChipError> {
+        if self.pc < RAM_SIZE as u16 && self.pc.is_multiple_of(2) {
+            Ok(())
+        } else {
+            Err(ChipError::InvalidProgramCounter(self.pc))
+        }
     }
 
-    fn fetch(&mut self) -> u16 {
+    fn fetch(&mut self) -> Result<u16, ChipError> {
+        self.validate_pc()?;
+        #[cfg(feature = "profiling")]
+        self.pc_coverage.set(self.pc as usize / 2, true);
         let higher_byte = self.ram[self.pc as usize] as u16;
         let lower_byte = self.ram[self.pc as usize + 1] as u16;
+        #[cfg(feature = "mem-log")]
+        {
+            self.log_read(self.pc, higher_byte as u8);
+            self.log_read(self.pc + 1, lower_byte as u8);
+        }
         let op = (higher_byte << 8) | lower_byte;
+        #[cfg(feature = "trace")]
+        self.record_trace(self.pc, op);
         self.pc += 2;
-        op
+        Ok(op)
     }
 
-    fn execute(&mut self, op: u16) {
-        let digit1 = (op & 0xF000) >> 12;
-        let digit2 = (op & 0x0F00) >> 8;
-        let digit3 = (op & 0x00F0) >> 4;
-        let digit4 = op & 0x000F;
+    fn execute(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        #[cfg(feature = "hooks")]
+        self.run_pre_hooks(op);
+
+        // fetch already moved PC past `op`, and jumps move it again
+        let op_addr = self.pc.wrapping_sub(2);
+
+        #[cfg(feature = "profiling")]
+        let started = Instant::now();
+
+        let result = self.dispatch(op);
+
+        #[cfg(feature = "profiling")]
+        self.record_opcode_time(op, started.elapsed());
+
+        let result = result.map_err(|error| self.error_context(op_addr, op, error))?;
 
-        match (digit1, digit2, digit3, digit4) {
+        #[cfg(feature = "hooks")]
+        self.run_post_hooks(op);
+
+        Ok(result)
+    }
+
+    fn error_context(&self, op_addr: u16, op: u16, error: ChipError) -> ChipError {
+        ChipError::Execution {
+            error: Box::new(error),
+            context: ChipErrorContext {
+                pc_at_error: op_addr,
+                call_stack: self.stack[..self.sp as usize].to_vec(),
+                opcode: op,
+            },
+        }
+    }
+
+    // 0NNN family
+    fn op_sys(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        match op {
             // NOP - No Operation
-            (0, 0, 0, 0) => return,
+            0x0000 => (),
             // CLS - clear screen
-            (0, 0, 0xE, 0) => {
-                self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+            0x00E0 => {
+                self.screen.clear();
+                self.screen_changed();
+                return Ok(TickResult::Draw);
             }
-            // RET - return from subroutine
-            (0, 0, 0xE, 0xE) => {
-                let ret_addr = self.pop();
-                self.pc = ret_addr;
-            }
-            // JMP NNN
-            (1, _, _, _) => {
-                let nnn = op & 0x0FFF;
-                self.pc = nnn;
-            }
-            // CALL NNN
-            (2, _, _, _) => {
-                let nnn = op & 0x0FFF;
-                self.push(self.pc);
-                self.pc = nnn;
-            }
-            // SKIP VX == NN
-            (3, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
-                if self.v_reg[x] == nn {
-                    self.pc += 2;
-                }
+            0x00C0..=0x00DF | 0x00FB..=0x00FF if self.mode == Chip8Mode::SuperChip => {
+                return self.op_superchip_sys(op);
             }
-            // SKIP VX != NN
-            (4, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
-                if self.v_reg[x] != nn {
-                    self.pc += 2;
-                }
+            // CHIP-8X - cycle the background colour
+            0x02A0 if self.quirks.chip8x_enabled => return self.op_cycle_background(),
+            // CHIP-8X - foreground colour from color RAM
+            0x02A2 | 0x02A4 | 0x02A6 if self.quirks.chip8x_enabled => {
+                return self.op_set_foreground(op);
             }
-            // SKIP VX == VY
-            (5, _, _, 0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
-                if self.v_reg[x] == self.v_reg[y] {
-                    self.pc += 2;
-                }
-            }
-            // VX = NN
-            (6, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
-                self.v_reg[x] = nn;
-            }
-            // VX += NN
-            (7, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
-                self.v_reg[x] = self.v_reg[x].wrapping_add(nn);
+            // RET - return from subroutine
+            0x00EE => {
+                let ret_addr = self.pop()?;
+                self.pc = ret_addr;
+                self.validate_pc()?;
             }
+            _ => return Err(ChipError::UnknownOpcode(op)),
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // JMP NNN
+    fn op_jp(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let nnn = op & 0x0FFF;
+        self.pc = nnn;
+        self.validate_pc()?;
+        Ok(TickResult::Ran)
+    }
+
+    // CALL NNN
+    fn op_call(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let nnn = op & 0x0FFF;
+        self.push(self.pc)?;
+        self.pc = nnn;
+        self.validate_pc()?;
+        Ok(TickResult::Ran)
+    }
+
+    // SKIP VX == NN
+    fn op_se_byte(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let nn = (op & 0xFF) as u8;
+        if self.v_reg[x] == nn {
+            self.pc += 2;
+            self.validate_pc()?;
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // SKIP VX != NN
+    fn op_sne_byte(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let nn = (op & 0xFF) as u8;
+        if self.v_reg[x] != nn {
+            self.pc += 2;
+            self.validate_pc()?;
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // SKIP VX == VY
+    fn op_se_reg(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        if op & 0x000F == 1 && self.quirks.chip8x_enabled {
+            return self.op_add_nibbles(op);
+        }
+        if op & 0x000F == 2 && self.quirks.chip8x_enabled {
+            return self.op_step_nibbles(op);
+        }
+        // XO-CHIP uses 5XY2 (save VX..VY) and 5XY3 (load VX..VY), which this
+        // emulator doesn't implement yet. The rest of 5XY1-5XYF is undefined
+        // everywhere, so none of them are worth a panic.
+        if op & 0x000F != 0 {
+            return Err(ChipError::UnknownOpcode(op));
+        }
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        if self.v_reg[x] == self.v_reg[y] {
+            self.pc += 2;
+            self.validate_pc()?;
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // VX = NN
+    fn op_ld_byte(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let nn = (op & 0xFF) as u8;
+        self.v_reg[x] = nn;
+        Ok(TickResult::Ran)
+    }
+
+    // VX += NN
+    fn op_add_byte(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let nn = (op & 0xFF) as u8;
+        self.v_reg[x] = self.v_reg[x].wrapping_add(nn);
+        Ok(TickResult::Ran)
+    }
+
+    // 8XYN register arithmetic
+    fn op_alu(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        match op & 0x000F {
             // VX = VY
-            (8, _, _, 0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            0 => {
                 self.v_reg[x] = self.v_reg[y];
             }
             // VX |= VY
-            (8, _, _, 1) => {
-                self.v_reg[0xF] = 0;
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            1 => {
+                if self.quirks.vf_reset_on_logic {
+                    self.v_reg[0xF] = 0;
+                }
                 self.v_reg[x] |= self.v_reg[y];
             }
             // VX &= VY
-            (8, _, _, 2) => {
-                self.v_reg[0xF] = 0;
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            2 => {
+                if self.quirks.vf_reset_on_logic {
+                    self.v_reg[0xF] = 0;
+                }
                 self.v_reg[x] &= self.v_reg[y];
             }
             // VX ^= VY
-            (8, _, _, 3) => {
-                self.v_reg[0xF] = 0;
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            3 => {
+                if self.quirks.vf_reset_on_logic {
+                    self.v_reg[0xF] = 0;
+                }
                 self.v_reg[x] ^= self.v_reg[y];
             }
             // VX += VY (overflowing)
-            (8, _, _, 4) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            4 => {
                 let (new_vx, carry) = self.v_reg[x].overflowing_add(self.v_reg[y]);
                 let new_vf = if carry { 1 } else { 0 };
 
@@ -230,173 +1022,204 @@ impl Emulator {
                 self.v_reg[0xF] = new_vf;
             }
             // VX -= VY (overflowing)
-            (8, _, _, 5) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            5 => {
                 let (new_vx, borrow) = self.v_reg[x].overflowing_sub(self.v_reg[y]);
                 let new_vf = if borrow { 0 } else { 1 };
 
                 self.v_reg[x] = new_vx;
                 self.v_reg[0xF] = new_vf;
             }
-            // VX = VY >> 1
-            (8, _, _, 6) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
-                let lsb = self.v_reg[x] & 0x1;
-                self.v_reg[x] = self.v_reg[y] >> 1;
+            // VX = VY >> 1 (VX >> 1 with shift_uses_vx)
+            6 => {
+                let src = if self.quirks.shift_uses_vx { x } else { y };
+                let lsb = self.v_reg[src] & 0x1;
+                self.v_reg[x] = self.v_reg[src] >> 1;
                 self.v_reg[0xF] = lsb;
             }
             // VY -= VX
-            (8, _, _, 7) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
+            7 => {
                 let (new_vx, borrow) = self.v_reg[y].overflowing_sub(self.v_reg[x]);
                 let new_vf = if borrow { 0 } else { 1 };
 
                 self.v_reg[x] = new_vx;
                 self.v_reg[0xF] = new_vf;
             }
-            // VX = VY << 1
-            (8, _, _, 0xE) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
-                let msb = (self.v_reg[y] >> 7) & 0x1;
-                self.v_reg[x] = self.v_reg[y] << 1;
+            // VX = VY << 1 (VX << 1 with shift_uses_vx)
+            0xE => {
+                let src = if self.quirks.shift_uses_vx { x } else { y };
+                let msb = (self.v_reg[src] >> 7) & 0x1;
+                self.v_reg[x] = self.v_reg[src] << 1;
                 self.v_reg[0xF] = msb;
             }
-            // SKIP VX != VY
-            (9, _, _, 0) => {
-                let x = digit2 as usize;
-                let y = digit3 as usize;
-                if self.v_reg[x] != self.v_reg[y] {
-                    self.pc += 2;
+            _ => return Err(ChipError::UnknownOpcode(op)),
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // SKIP VX != VY
+    fn op_sne_reg(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        if op & 0x000F != 0 {
+            return Err(ChipError::UnknownOpcode(op));
+        }
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let y = ((op & 0x00F0) >> 4) as usize;
+        if self.v_reg[x] != self.v_reg[y] {
+            self.pc += 2;
+            self.validate_pc()?;
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // I = NNN
+    fn op_ld_i(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let nnn = op & 0x0FFF;
+        self.i_reg = nnn;
+        Ok(TickResult::Ran)
+    }
+
+    // JMP V0 + NNN (VX + NNN with jump_uses_vx)
+    fn op_jp_v0(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        // CHIP-8X gave up BNNN for zone colouring
+        if self.quirks.chip8x_enabled {
+            return self.op_color_zones(op);
+        }
+        let nnn = op & 0x0FFF;
+        let x = if self.quirks.jump_uses_vx {
+            ((op & 0x0F00) >> 8) as usize
+        } else {
+            0
+        };
+        self.pc = self.v_reg[x] as u16 + nnn;
+        self.validate_pc()?;
+        Ok(TickResult::Ran)
+    }
+
+    // CXNN - VX = rand() & NN
+    fn op_rnd(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let nn = (op & 0xFF) as u8;
+        let rng = self.rng.next_byte();
+        self.v_reg[x] = rng & nn;
+        Ok(TickResult::Ran)
+    }
+
+    // DRAW!
+    fn op_drw(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        let x_coord = self.v_reg[((op & 0x0F00) >> 8) as usize] as usize % width;
+        let y_coord = self.v_reg[((op & 0x00F0) >> 4) as usize] as usize % height;
+        let num_rows = op & 0x000F;
+
+        // keep track of how many pixels were flipped off.
+        let mut collisions: u8 = 0;
+        if num_rows == 0 && self.mode == Chip8Mode::SuperChip {
+            collisions = self.draw_large_sprite(x_coord, y_coord)?;
+        }
+        // Iterate over each row in the sprite.
+        for y_line in 0..num_rows as usize {
+            // get the memory address where our row's data is stored.
+            let addr = self.i_reg.wrapping_add(y_line as u16);
+            let pixels = self.read_ram(addr)?;
+
+            let mut y = y_coord + y_line;
+            if y >= height {
+                if !self.quirks.sprite_wraps {
+                    continue;
                 }
+                y %= height;
             }
-            // I = NNN
-            (0xA, _, _, _) => {
-                let nnn = op & 0x0FFF;
-                self.i_reg = nnn;
-            }
-            // JMP V0 + NNN
-            (0xB, _, _, _) => {
-                let nnn = op & 0x0FFF;
-                self.pc = (self.v_reg[0] as u16 + nnn).into();
-            }
-            // CXNN - VX = rand() & NN
-            (0xC, _, _, _) => {
-                let x = digit2 as usize;
-                let nn = (op & 0xFF) as u8;
-                let rng: u8 = rand::random();
-                self.v_reg[x] = rng & nn;
-            }
-            // DRAW!
-            (0xD, _, _, _) => {
-                let x_coord = self.v_reg[digit2 as usize] as usize % SCREEN_WIDTH;
-                let y_coord = self.v_reg[digit3 as usize] as usize % SCREEN_HEIGHT;
-                let num_rows = digit4;
-
-                // keep track of whether any pixels were flipped.
-                let mut flipped = false;
-                // Iterate over each row in the sprite.
-                for y_line in 0..num_rows as usize {
-                    // get the memory address where our row's data is stored.
-                    let addr = self.i_reg + y_line as u16;
-                    let pixels = self.ram[addr as usize];
-
-                    let y = y_coord + y_line;
-                    if y >= SCREEN_HEIGHT {
-                        continue;
-                    }
-
-                    // iterate over each column in the current row
-                    for x_line in 0..8 {
-
-
-                        // this fetches the value of the current bit with a mask.
-                        if (pixels & (0b1000_0000 >> x_line)) != 0 {
-                            let x = x_coord + x_line;
-                            if x >= SCREEN_WIDTH {
-                                continue;
-                            }
-                            let idx = x + (SCREEN_WIDTH * y);
-                            flipped |= self.screen[idx];
-                            self.screen[idx] ^= true;
-                        }
-                    }
-                }
-                self.v_reg[0xF] = if flipped { 1 } else { 0 };
-                self.draw_completed = false;
+
+            collisions = collisions.saturating_add(self.screen.xor_row(y, pixels, x_coord));
+            #[cfg(feature = "profiling")]
+            self.record_pixel_flips(y, pixels, x_coord);
+
+            // bits clipped at the right edge come back in on the left
+            if self.quirks.sprite_wraps && x_coord + 8 > width {
+                let wrapped = ((pixels as u16) << (width - x_coord)) as u8;
+                collisions = collisions.saturating_add(self.screen.xor_row(y, wrapped, 0));
+                #[cfg(feature = "profiling")]
+                self.record_pixel_flips(y, wrapped, 0);
             }
+        }
+        self.v_reg[0xF] = if collisions > 0 { 1 } else { 0 };
+        self.collision_count = collisions;
+        // the VIP finished each sprite and then sat out the rest of the frame
+        self.waiting_for_vblank = self.quirks.display_wait;
+        self.screen_changed();
+        Ok(TickResult::Draw)
+    }
+
+    // EXNN key skips
+    fn op_skp(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        let vx = self.v_reg[x];
+        let key = self.is_key_pressed(vx as usize);
+        let skip = match op & 0x00FF {
             // SKIP KEY PRESS
-            (0xE, _, 9, 0xE) => {
-                let x = digit2 as usize;
-                let vx = self.v_reg[x];
-                let key = self.keys[vx as usize];
-                if key {
-                    self.pc += 2;
-                }
-            }
+            0x9E => key,
             // SKIP KEY NOT PRESSED
-            (0xE, _, 0xA, 1) => {
-                let x = digit2 as usize;
-                let vx = self.v_reg[x];
-                let key = self.keys[vx as usize];
-                if !key {
-                    self.pc += 2;
-                }
-            }
+            0xA1 => !key,
+            _ => return Err(ChipError::UnknownOpcode(op)),
+        };
+        if skip {
+            self.pc += 2;
+            self.validate_pc()?;
+        }
+        Ok(TickResult::Ran)
+    }
+
+    // FXNN timers, keys, I and memory
+    fn op_misc(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        if self.mode == Chip8Mode::SuperChip
+            && let Some(result) = self.op_superchip_misc(op)
+        {
+            return Ok(result);
+        }
+        let x = ((op & 0x0F00) >> 8) as usize;
+        match op & 0x00FF {
             // VX = DT
-            (0xF, _, 0, 7) => {
-                let x = digit2 as usize;
+            0x07 => {
                 self.v_reg[x] = self.dt;
             }
             // WAIT KEY
-            (0xF, _, 0, 0xA) => {
-                let x = digit2 as usize;
-                let mut pressed_key = None;
-
-                for i in 0..self.keys.len() {
-                    if self.keys[i] {
-                        pressed_key = Some(i);
-                        break;
-                    }
-                }
+            0x0A => {
+                self.waiting_key_register = Some(x as u8);
+                let pressed_key = self.get_pressed_keys().next();
 
                 if let Some(key_idx) = pressed_key {
                     // Key is pressed, store its value and remember we're waiting for it to be released
                     self.v_reg[x] = key_idx as u8;
-                    self.waiting_for_key_release = Some(key_idx);
+                    self.waiting_for_key_release = Some(key_idx as u8);
                 } else {
                     // No key pressed, repeat this instruction
                     self.pc -= 2;
                 }
+                return Ok(TickResult::KeyWait);
             }
             // DT = VX
-            (0xF, _, 1, 5) => {
-                let x = digit2 as usize;
+            0x15 => {
                 self.dt = self.v_reg[x];
             }
             // ST = VX
-            (0xF, _, 1, 8) => {
-                let x = digit2 as usize;
+            0x18 => {
                 self.st = self.v_reg[x];
             }
             // I += VX
-            (0xF, _, 1, 0xE) => {
-                let x = digit2 as usize;
+            0x1E => {
                 let vx = self.v_reg[x] as u16;
-                self.i_reg = self.i_reg.wrapping_add(vx);
+                let sum = self.i_reg.wrapping_add(vx);
+                if self.quirks.i_overflow_quirk {
+                    self.v_reg[0xF] = if sum > 0xFFF { 1 } else { 0 };
+                }
+                self.i_reg = sum;
             }
             // I = FONT
-            (0xF, _, 2, 9) => {
-                let x = digit2 as usize;
+            0x29 => {
                 let c = self.v_reg[x] as u16;
                 self.i_reg = c * 5; // 5 bytes per font char. '0' is 0*5 in ram, '2' is at 2*5 (10).
             }
             // BCD
-            (0xF, _, 3, 3) => {
-                let x = digit2 as usize;
+            0x33 => {
                 let vx = self.v_reg[x];
                 // fetch the hundreds digit by dividing by 100 and tossing the decimal
                 let hundreds = vx / 100;
@@ -405,38 +1228,672 @@ impl Emulator {
                 // Fetch the ones digit by tossing the hundreds and the tens
                 let ones = vx % 10;
 
-                self.ram[self.i_reg as usize] = hundreds;
-                self.ram[self.i_reg as usize + 1] = tens;
-                self.ram[self.i_reg as usize + 2] = ones;
+                self.write_ram(self.i_reg, hundreds)?;
+                self.write_ram(self.i_reg.wrapping_add(1), tens)?;
+                self.write_ram(self.i_reg.wrapping_add(2), ones)?;
             }
             // FX55 store V0 - VX into I
-            (0xF, _, 5, 5) => {
-                let x = digit2 as usize;
-                let i = self.i_reg as usize;
+            //
+            // order matters: every register is written from the original I and
+            // I only moves once the whole block is done, so for X = F the block
+            // is 16 bytes with VF last and I ends up at I + 16. VF is an
+            // ordinary source here, this opcode never sets a flag.
+            0x55 => {
+                let i = self.i_reg;
                 for idx in 0..=x {
-                    self.ram[i + idx] = self.v_reg[idx];
+                    self.write_ram(i.wrapping_add(idx as u16), self.v_reg[idx])?;
+                }
+                if !self.quirks.memory_leave_i_unchanged {
+                    self.i_reg = i.wrapping_add((x + 1) as u16);
                 }
-                self.i_reg += (x + 1) as u16;
             }
             // FX65 load I into V0 - VX
-            (0xF, _, 6, 5) => {
-                let x = digit2 as usize;
-                let i = self.i_reg as usize;
+            //
+            // same order as FX55. For X = F the last byte lands in VF and stays
+            // there, nothing overwrites it with a flag afterwards.
+            0x65 => {
+                let i = self.i_reg;
                 for idx in 0..=x {
-                    self.v_reg[idx] = self.ram[i + idx];
+                    self.v_reg[idx] = self.read_ram(i.wrapping_add(idx as u16))?;
                 }
-                self.i_reg += (x + 1) as u16;
+                if !self.quirks.memory_leave_i_unchanged {
+                    self.i_reg = i.wrapping_add((x + 1) as u16);
+                }
+            }
+            _ => return Err(ChipError::UnknownOpcode(op)),
+        }
+        Ok(TickResult::Ran)
+    }
+
+    /// Decrements DT and ST by one. Meant to be called at 60 Hz.
+    pub fn tick_timers(&mut self) {
+        self.decrement_timers(1);
+    }
+
+    /// Decrements DT and ST by however many 60 Hz periods fit in `elapsed_ms`,
+    /// carrying the remainder over to the next call.
+    pub fn tick_timers_with_dt(&mut self, elapsed_ms: u32) {
+        let scaled = self.timer_accumulator_ms as u64 + elapsed_ms as u64 * 60;
+        let ticks = scaled / 1000;
+        self.timer_accumulator_ms = (scaled % 1000) as u32;
+        self.decrement_timers(ticks.min(u8::MAX as u64) as u8);
+    }
+
+    /// Signals the 60 Hz display interrupt, releasing a DXYN that is waiting
+    /// for vblank. The timer ticks already do this, so only hosts that drive
+    /// the timers separately from the display need to call it.
+    pub fn trigger_frame_interrupt(&mut self) {
+        self.waiting_for_vblank = false;
+    }
+
+    pub fn is_waiting_for_vblank(&self) -> bool {
+        self.waiting_for_vblank
+    }
+
+    fn decrement_timers(&mut self, ticks: u8) {
+        self.counters.timer_ticks += ticks as u64;
+        // on the VIP the timers counted down in the 60 Hz display interrupt
+        if ticks > 0 {
+            self.trigger_frame_interrupt();
+        }
+        if self.dt > 0 && self.dt <= ticks {
+            self.events.push(EmulatorEvent::DelayTimerExpired);
+        }
+        if self.st > 0 && self.st <= ticks {
+            self.events.push(EmulatorEvent::SoundTimerExpired);
+        }
+        self.dt = self.dt.saturating_sub(ticks);
+        self.st = self.st.saturating_sub(ticks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emulator_with(opcodes: &[u16]) -> Emulator {
+        let rom: Vec<u8> = opcodes.iter().flat_map(|op| op.to_be_bytes()).collect();
+        let mut chip8 = Emulator::new();
+        chip8.load_rom(&rom).unwrap();
+        chip8
+    }
+
+    // FX55 / FX65 at I = 0x300 for X = 0, 1, 8 and F, with and without the increment
+    fn each_store_load_case(mut check: impl FnMut(Emulator, usize, bool)) {
+        for x in [0x0, 0x1, 0x8, 0xF] {
+            for leave_i in [false, true] {
+                let mut chip8 = Emulator::new_with_quirks(QuirksConfig {
+                    memory_leave_i_unchanged: leave_i,
+                    ..QuirksConfig::default()
+                });
+                chip8.load_rom(&[0xA3, 0x00]).unwrap();
+                chip8.tick();
+                check(chip8, x, leave_i);
+            }
+        }
+    }
+
+    #[test]
+    fn fx55_stores_v0_through_vx() {
+        each_store_load_case(|mut chip8, x, leave_i| {
+            for (idx, reg) in chip8.v_reg.iter_mut().enumerate() {
+                *reg = 0x10 + idx as u8;
+            }
+            assert_eq!(chip8.execute(0xF055 | (x as u16) << 8), Ok(TickResult::Ran));
+
+            let stored: Vec<u8> = (0..=x).map(|idx| 0x10 + idx as u8).collect();
+            assert_eq!(&chip8.ram[0x300..=0x300 + x], &stored[..], "X = {x:X}");
+            assert_eq!(chip8.ram[0x301 + x], 0, "X = {x:X} wrote past VX");
+            let expected_i = if leave_i { 0x300 } else { 0x301 + x as u16 };
+            assert_eq!(chip8.get_i(), expected_i, "X = {x:X}, leave I = {leave_i}");
+        });
+    }
+
+    #[test]
+    fn fx65_loads_v0_through_vx() {
+        each_store_load_case(|mut chip8, x, leave_i| {
+            for idx in 0..16 {
+                chip8.ram[0x300 + idx] = 0xA0 + idx as u8;
+            }
+            assert_eq!(chip8.execute(0xF065 | (x as u16) << 8), Ok(TickResult::Ran));
+
+            for (idx, reg) in chip8.get_registers().iter().enumerate() {
+                // for X = F the last byte lands in VF
+                let expected = if idx <= x { 0xA0 + idx as u8 } else { 0 };
+                assert_eq!(*reg, expected, "X = {x:X}, V{idx:X}");
+            }
+            let expected_i = if leave_i { 0x300 } else { 0x301 + x as u16 };
+            assert_eq!(chip8.get_i(), expected_i, "X = {x:X}, leave I = {leave_i}");
+        });
+    }
+
+    #[test]
+    fn store_load_at_the_end_of_ram() {
+        let mut chip8 = emulator_with(&[0x0000]);
+        // the last two bytes of RAM are fine, I ends up just past them
+        chip8.i_reg = 0xFFE;
+        assert_eq!(chip8.execute(0xF155), Ok(TickResult::Ran));
+        assert_eq!(chip8.get_i(), 0x1000);
+
+        chip8.i_reg = 0xFFE;
+        assert_eq!(
+            chip8.execute(0xF265).unwrap_err().inner(),
+            &ChipError::InvalidMemoryAccess(0x1000)
+        );
+        // I at the very top of the address space fails instead of overflowing
+        chip8.i_reg = 0xFFFF;
+        assert_eq!(
+            chip8.execute(0xFF55).unwrap_err().inner(),
+            &ChipError::InvalidMemoryAccess(0xFFFF)
+        );
+    }
+
+    #[test]
+    fn draw_at_the_end_of_ram() {
+        let mut chip8 = emulator_with(&[0x0000]);
+        chip8.i_reg = 0xFFE;
+        assert_eq!(chip8.execute(0xD002), Ok(TickResult::Draw));
+
+        chip8.i_reg = 0xFFE;
+        assert_eq!(
+            chip8.execute(0xD003).unwrap_err().inner(),
+            &ChipError::InvalidMemoryAccess(0x1000)
+        );
+        chip8.i_reg = 0xFFFF;
+        assert_eq!(
+            chip8.execute(0xD00F).unwrap_err().inner(),
+            &ChipError::InvalidMemoryAccess(0xFFFF)
+        );
+    }
+
+    #[test]
+    fn fx0a_waits_until_the_key_is_released() {
+        // LD V3, K
+        let mut chip8 = emulator_with(&[0xF30A, 0x1202]);
+        assert!(!chip8.is_waiting_for_key());
+
+        chip8.step().unwrap();
+        assert!(chip8.is_waiting_for_key());
+        assert_eq!(chip8.waiting_key_register(), Some(3));
+
+        chip8.keypress(0x7, true);
+        chip8.step().unwrap();
+        assert!(chip8.is_waiting_for_key());
+        assert_eq!(chip8.waiting_key_register(), Some(3));
+
+        chip8.keypress(0x7, false);
+        assert!(!chip8.is_waiting_for_key());
+        assert_eq!(chip8.waiting_key_register(), None);
+        assert_eq!(chip8.get_registers()[3], 0x7);
+    }
+
+    #[test]
+    fn step_reports_the_instruction_that_ran() {
+        // LD V3, 0x0F
+        let mut chip8 = emulator_with(&[0x630F]);
+        let step = chip8.step().unwrap();
+        assert_eq!(step.pc_before, 0x200);
+        assert_eq!(step.pc_after, 0x202);
+        assert_eq!(step.registers_before[3], 0);
+        assert_eq!(step.registers_after[3], 0x0F);
+        assert_eq!(step.registers_changed, vec![(3, 0, 0x0F)]);
+        assert_eq!(
+            step.instruction,
+            Some(Instruction::LoadByte { vx: 3, byte: 15 })
+        );
+        assert_eq!(step.mnemonic.as_deref(), Some("LD V3, 0x0F"));
+    }
+
+    #[test]
+    fn step_reports_no_instruction_while_blocked() {
+        // LD V3, K; LD V0, 0x00; DRW V0, V0, 1
+        let mut chip8 = emulator_with(&[0xF30A, 0x6000, 0xD001]);
+        chip8.keypress(0x7, true);
+        assert!(chip8.step().unwrap().instruction.is_some());
+
+        let step = chip8.step().unwrap();
+        assert_eq!(step.result, TickResult::KeyWait);
+        assert_eq!(step.instruction, None);
+        assert_eq!(step.mnemonic, None);
+        assert_eq!(step.opcode, 0x6000);
+
+        chip8.keypress(0x7, false);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        let step = chip8.step().unwrap();
+        assert_eq!(step.result, TickResult::WaitingForVblank);
+        assert_eq!(step.instruction, None);
+    }
+
+    #[test]
+    fn watchdog_counts_instructions() {
+        // JP 0x200
+        let mut chip8 = emulator_with(&[0x1200]);
+        chip8.set_watchdog(100);
+        let mut ticks = 0;
+        while chip8.tick() != TickResult::Halted {
+            ticks += 1;
+        }
+        assert_eq!(ticks, 99);
+        assert_eq!(chip8.get_total_ticks(), 100);
+    }
+
+    #[test]
+    fn watchdog_counts_vip_cycles_when_cycle_accurate() {
+        let mut chip8 = emulator_with(&[0x1200]);
+        chip8.set_cycle_accurate_timing(true);
+        chip8.set_watchdog(100);
+        let mut ticks = 0;
+        while chip8.tick() != TickResult::Halted {
+            ticks += 1;
+        }
+        // a jump costs 12 cycles, the ninth one reaches 108
+        assert_eq!(ticks, 8);
+        assert_eq!(chip8.get_total_ticks(), 108);
+        assert!(chip8.is_halted());
+    }
+
+    #[test]
+    fn reset_stops_waiting_for_key() {
+        let mut chip8 = emulator_with(&[0xF30A]);
+        chip8.keypress(0x7, true);
+        chip8.step().unwrap();
+        chip8.reset();
+        assert!(!chip8.is_waiting_for_key());
+        assert_eq!(chip8.waiting_key_register(), None);
+    }
+
+    #[test]
+    fn tick_results() {
+        // LD V0, 0x01; DRW V0, V0, 1; LD V1, K
+        let mut chip8 = emulator_with(&[0x6001, 0xD001, 0xF10A]);
+        assert_eq!(chip8.tick(), TickResult::Ran);
+        assert_eq!(chip8.tick(), TickResult::Draw);
+        assert_eq!(chip8.tick(), TickResult::WaitingForVblank);
+        chip8.trigger_frame_interrupt();
+        assert_eq!(chip8.tick(), TickResult::KeyWait);
+
+        // JP 0x202; JP 0x202
+        let mut chip8 = emulator_with(&[0x1202, 0x1202]);
+        chip8.add_breakpoint(0x202);
+        assert_eq!(chip8.tick(), TickResult::Ran);
+        assert_eq!(chip8.tick(), TickResult::BreakpointHit(0x202));
+        chip8.set_watchdog(2);
+        assert_eq!(chip8.tick(), TickResult::Halted);
+        assert_eq!(chip8.tick(), TickResult::Halted);
+
+        // RET
+        let mut chip8 = emulator_with(&[0x00EE]);
+        assert!(matches!(chip8.tick(), TickResult::Error(_)));
+    }
+
+    #[test]
+    fn large_zero_is_symmetric_with_a_hole() {
+        let zero = render_font_char(0x0, true);
+        for row in &zero {
+            let mirrored: Vec<bool> = row.iter().rev().copied().collect();
+            assert_eq!(row.as_slice(), mirrored.as_slice());
+        }
+        assert!(zero[0].iter().all(|on| *on));
+        assert!(zero[9].iter().all(|on| *on));
+        for row in &zero[2..8] {
+            assert!(row[0] && row[7]);
+            assert!(row[2..6].iter().all(|on| !on));
+        }
+    }
+
+    #[test]
+    fn large_glyphs_are_distinct_and_loaded() {
+        let glyphs: Vec<String> = (0..16).map(render_large_font_char).collect();
+        for (c, glyph) in glyphs.iter().enumerate() {
+            assert_eq!(glyph.lines().count(), 10);
+            assert!(glyph.contains('#') && glyph.contains('.'), "{:X}", c);
+            assert_eq!(glyphs.iter().filter(|g| *g == glyph).count(), 1, "{:X}", c);
+        }
+
+        let mut chip8 = Emulator::new_with_rom(roms::COUNTER_ROM).unwrap();
+        chip8.ram[LARGE_FONT_START as usize] = 0;
+        chip8.reset();
+        let start = LARGE_FONT_START as usize;
+        assert_eq!(
+            &chip8.ram[start..start + LARGE_FONTSET_SIZE],
+            &LARGE_FONTSET
+        );
+    }
+
+    #[test]
+    fn jump_to_an_invalid_address_is_an_error() {
+        // JP 0xFFF
+        let mut chip8 = emulator_with(&[0x1FFF]);
+        match chip8.tick() {
+            TickResult::Error(err) => {
+                assert_eq!(err.inner(), &ChipError::InvalidProgramCounter(0xFFF))
             }
-            (_, _, _, _) => unimplemented!("Unimplemented OpCode: {}", op),
+            other => panic!("expected an invalid PC, got {:?}", other),
         }
+
+        // JP 0x200
+        let mut chip8 = emulator_with(&[0x1200]);
+        assert_eq!(chip8.tick(), TickResult::Ran);
+        assert_eq!(chip8.get_pc(), 0x200);
     }
 
-    pub fn tick_timers(&mut self)    {
-        if self.dt > 0 {
-            self.dt -= 1;
+    #[test]
+    fn add_i_overflow_sets_vf_with_the_quirk() {
+        for (quirk, flag) in [(true, 1), (false, 0)] {
+            let mut chip8 = Emulator::new_with_quirks(QuirksConfig {
+                i_overflow_quirk: quirk,
+                ..QuirksConfig::default()
+            });
+            chip8.i_reg = 0xFF0;
+            chip8.v_reg[0] = 0x10;
+            chip8.execute(0xF01E).unwrap();
+            assert_eq!(chip8.v_reg[0xF], flag, "quirk {}", quirk);
+            assert_eq!(chip8.i_reg, 0x1000);
         }
-        if self.st > 0 {
-            self.st -= 1;
+    }
+
+    #[test]
+    fn return_with_an_empty_stack_underflows() {
+        // RET
+        let mut chip8 = emulator_with(&[0x00EE]);
+        match chip8.tick() {
+            TickResult::Error(err) => assert_eq!(err.inner(), &ChipError::StackUnderflow),
+            other => panic!("expected a stack underflow, got {:?}", other),
         }
+        assert_eq!(chip8.get_sp(), 0);
+    }
+
+    #[test]
+    fn collision_count_counts_erased_pixels() {
+        let mut chip8 = Emulator::new_with_rom(roms::COUNTER_ROM).unwrap();
+        chip8.ram[0x300] = 0xFF;
+        chip8.i_reg = 0x300;
+        chip8.v_reg[1] = 5;
+
+        // DRW V0, V0, 1 at (0, 0), then DRW V1, V0, 1 at (5, 0)
+        chip8.execute(0xD001).unwrap();
+        assert_eq!(chip8.get_last_collision_count(), 0);
+        chip8.execute(0xD101).unwrap();
+        assert_eq!(chip8.get_last_collision_count(), 3);
+        assert_eq!(chip8.v_reg[0xF], 1);
+    }
+
+    #[test]
+    fn fx0a_waits_for_release_after_the_press() {
+        // LD V2, K; LD V1, 0x01
+        let mut chip8 = emulator_with(&[0xF20A, 0x6101]);
+        assert_eq!(chip8.tick(), TickResult::KeyWait);
+        assert!(!chip8.is_waiting_for_key_release());
+
+        chip8.keypress(0x9, true);
+        chip8.tick();
+        assert!(chip8.is_waiting_for_key_release());
+        assert_eq!(chip8.get_waiting_key_index(), Some(0x9));
+        assert_eq!(chip8.tick(), TickResult::KeyWait);
+
+        chip8.keypress(0x9, false);
+        assert!(!chip8.is_waiting_for_key_release());
+        assert_eq!(chip8.tick(), TickResult::Ran);
+        assert_eq!(chip8.v_reg[2], 0x9);
+        assert_eq!(chip8.v_reg[1], 0x01);
+    }
+
+    #[test]
+    fn is_key_pressed_checks_one_key() {
+        let mut chip8 = Emulator::new_with_rom(roms::COUNTER_ROM).unwrap();
+        assert!(!chip8.is_any_key_pressed());
+
+        chip8.keypress(5, true);
+        assert!(chip8.is_key_pressed(5));
+        assert!(!chip8.is_key_pressed(6));
+        assert!(!chip8.is_key_pressed(KEY_COUNT));
+        assert!(chip8.is_any_key_pressed());
+        assert_eq!(chip8.get_pressed_keys().collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[test]
+    fn small_zero_starts_with_a_full_row() {
+        let zero = render_font_char(0x0, false);
+        assert_eq!(
+            zero[0],
+            [true, true, true, true, false, false, false, false]
+        );
+        assert!(zero[5..].iter().flatten().all(|on| !on));
+        assert_eq!(
+            font_char_to_string(0x0, false),
+            "####....\n#..#....\n#..#....\n#..#....\n####...."
+        );
+    }
+
+    #[test]
+    fn shifts_under_both_quirks() {
+        // V2 = 0x81, V3 = 0x42, then (op, shift_uses_vx, V2, VF)
+        let cases = [
+            (0x8236, false, 0x21, 0),
+            (0x8236, true, 0x40, 1),
+            (0x823E, false, 0x84, 0),
+            (0x823E, true, 0x02, 1),
+            (0x8226, false, 0x40, 1),
+            (0x8226, true, 0x40, 1),
+            (0x822E, false, 0x02, 1),
+            (0x822E, true, 0x02, 1),
+        ];
+        for (op, shift_uses_vx, result, flag) in cases {
+            let mut chip8 = Emulator::new_with_quirks(QuirksConfig {
+                shift_uses_vx,
+                ..QuirksConfig::default()
+            });
+            chip8.v_reg[2] = 0x81;
+            chip8.v_reg[3] = 0x42;
+            chip8.execute(op).unwrap();
+            assert_eq!(chip8.v_reg[2], result, "{:04X} {}", op, shift_uses_vx);
+            assert_eq!(chip8.v_reg[0xF], flag, "{:04X} {} VF", op, shift_uses_vx);
+            assert_eq!(chip8.v_reg[3], 0x42, "{:04X} {} VY", op, shift_uses_vx);
+        }
+    }
+
+    #[test]
+    fn delay_timer_expiry_is_reported_once() {
+        // LD V0, 0x03; LD DT, V0
+        let mut chip8 = emulator_with(&[0x6003, 0xF015]);
+        chip8.tick_n(2);
+        chip8.drain_events();
+
+        chip8.tick_timers();
+        assert!(chip8.drain_events().is_empty());
+        chip8.tick_timers();
+        assert!(chip8.drain_events().is_empty());
+        chip8.tick_timers();
+        assert_eq!(chip8.drain_events(), vec![EmulatorEvent::DelayTimerExpired]);
+        chip8.tick_timers();
+        assert!(chip8.drain_events().is_empty());
+    }
+
+    #[test]
+    fn clones_run_the_same_ticks() {
+        // RND V0, 0xFF; LD F, V0; DRW V1, V1, 5; SKP V2; JP 0x200; ADD V1, 0x01; JP 0x200
+        let mut chip8 = emulator_with(&[0xC0FF, 0xF029, 0xD115, 0xE29E, 0x1200, 0x7101, 0x1200]);
+        chip8.seed_rng(42);
+        chip8.tick_n(3);
+        let mut clone = chip8.clone();
+
+        for frame in 0..50 {
+            for emulator in [&mut chip8, &mut clone] {
+                emulator.keypress(0, frame % 7 == 0);
+            }
+            for _ in 0..5 {
+                assert_eq!(chip8.tick(), clone.tick(), "frame {}", frame);
+            }
+            chip8.tick_timers();
+            clone.tick_timers();
+        }
+        assert!(chip8 == clone);
+        assert_eq!(chip8.get_display(), clone.get_display());
+    }
+
+    #[test]
+    fn timers_follow_elapsed_milliseconds() {
+        // LD V0, 0x20; LD DT, V0; LD ST, V0
+        let mut chip8 = emulator_with(&[0x6020, 0xF015, 0xF018]);
+        chip8.tick_n(3);
+
+        chip8.tick_timers_with_dt(100);
+        assert_eq!(chip8.get_dt(), 0x20 - 6);
+        assert_eq!(chip8.get_st(), 0x20 - 6);
+
+        // 10 ms is 0.6 of a tick, the remainder carries over
+        chip8.tick_timers_with_dt(10);
+        assert_eq!(chip8.get_dt(), 0x20 - 6);
+        chip8.tick_timers_with_dt(10);
+        assert_eq!(chip8.get_dt(), 0x20 - 7);
+        // 0.2 left over plus 0.96
+        chip8.tick_timers_with_dt(16);
+        assert_eq!(chip8.get_dt(), 0x20 - 8);
+        chip8.tick_timers_with_dt(0);
+        assert_eq!(chip8.get_dt(), 0x20 - 8);
+    }
+
+    #[test]
+    fn new_uses_the_default_quirks() {
+        let mut plain = Emulator::new();
+        let mut explicit = Emulator::new_with_quirks(DEFAULT_QUIRKS);
+        assert_eq!(plain.get_quirks(), explicit.get_quirks());
+        for chip8 in [&mut plain, &mut explicit] {
+            chip8.load_rom(roms::COUNTER_ROM).unwrap();
+            chip8.tick_n(100);
+        }
+        assert!(plain == explicit);
+        assert_eq!(plain.save_state(), explicit.save_state());
+    }
+
+    #[test]
+    fn draw_coords_past_the_edge_wrap() {
+        let chip8 = Emulator::new();
+        assert_eq!(
+            chip8.validate_draw_coords(65, 33),
+            CoordInfo {
+                x_wrapped: true,
+                y_wrapped: true,
+                effective_x: 1,
+                effective_y: 1,
+            }
+        );
+        assert_eq!(
+            chip8.validate_draw_coords(63, 31),
+            CoordInfo {
+                x_wrapped: false,
+                y_wrapped: false,
+                effective_x: 63,
+                effective_y: 31,
+            }
+        );
+        assert_eq!((chip8.screen_width(), chip8.screen_height()), (64, 32));
+    }
+
+    #[test]
+    fn undefined_5xyn_is_an_unknown_opcode() {
+        let mut chip8 = emulator_with(&[0x5001]);
+        match chip8.tick() {
+            TickResult::Error(err) => assert_eq!(err.inner(), &ChipError::UnknownOpcode(0x5001)),
+            other => panic!("expected an unknown opcode, got {:?}", other),
+        }
+
+        // 5XY1 adds nibbles on CHIP-8X, 5XY4 is undefined there too
+        let chip8x = QuirksConfig {
+            chip8x_enabled: true,
+            ..QuirksConfig::default()
+        };
+        let mut chip8 = Emulator::new_with_quirks(chip8x);
+        chip8.load_rom(&[0x50, 0x01, 0x50, 0x04]).unwrap();
+        assert_eq!(chip8.tick(), TickResult::Ran);
+        match chip8.tick() {
+            TickResult::Error(err) => assert_eq!(err.inner(), &ChipError::UnknownOpcode(0x5004)),
+            other => panic!("expected an unknown opcode, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn get_pixel_is_bounds_checked() {
+        let mut chip8 = Emulator::new();
+        assert_eq!(chip8.get_pixel(0, 0), Some(false));
+        assert_eq!(chip8.get_pixel(63, 31), Some(false));
+        assert_eq!(chip8.get_pixel(64, 0), None);
+        assert_eq!(chip8.get_pixel(0, 32), None);
+
+        chip8.set_pixel(63, 31, true).unwrap();
+        assert_eq!(chip8.get_pixel(63, 31), Some(true));
+        // SAFETY: (63, 31) is on the 64x32 screen
+        assert!(unsafe { chip8.get_pixel_unchecked(63, 31) });
+        assert_eq!(
+            chip8.set_pixel(64, 0, true),
+            Err(ChipError::PixelOutOfBounds { x: 64, y: 0 })
+        );
+    }
+
+    #[test]
+    fn blit_copies_and_clips() {
+        let mut chip8 = Emulator::new();
+        // a 4x4 checkerboard in the top left corner
+        for (x, y) in (0..4).flat_map(|y| (0..4).map(move |x| (x, y))) {
+            chip8.set_pixel(x, y, (x + y) % 2 == 0).unwrap();
+        }
+        chip8.blit_region(0, 0, 4, 4, 10, 10);
+        for (x, y) in (0..4).flat_map(|y| (0..4).map(move |x| (x, y))) {
+            assert_eq!(chip8.get_pixel(x, y), Some((x + y) % 2 == 0));
+            assert_eq!(chip8.get_pixel(10 + x, 10 + y), Some((x + y) % 2 == 0));
+        }
+
+        // only the two columns left of the right edge are written
+        chip8.blit_region(0, 0, 4, 1, 62, 20);
+        assert_eq!(chip8.get_pixel(62, 20), Some(true));
+        assert_eq!(chip8.get_pixel(63, 20), Some(false));
+        assert_eq!(chip8.get_pixel(0, 21), Some(false));
+        assert_eq!(chip8.get_pixel(1, 21), Some(false));
+    }
+
+    #[test]
+    fn invert_screen_twice_is_a_no_op() {
+        // draws the 0 glyph in the top left corner
+        let mut chip8 = Emulator::new_with_rom(roms::DRAW_ROM).unwrap();
+        chip8.tick_n(4);
+        let before = chip8.get_display().to_vec();
+        chip8.invert_screen();
+        assert_eq!(chip8.get_pixel(1, 1), Some(true));
+        assert_eq!(chip8.get_pixel(0, 0), Some(false));
+        chip8.invert_screen();
+        assert_eq!(chip8.get_display(), &before[..]);
+    }
+
+    #[test]
+    fn display_wait_caps_draws_at_the_frame_rate() {
+        const CPU_HZ: u64 = 500;
+        const FRAME_HZ: u64 = 60;
+        // DRW V0, V0, 1; JP 0x200
+        let mut chip8 = emulator_with(&[0xD001, 0x1200]);
+        assert!(chip8.get_quirks().display_wait);
+
+        let (mut draws, mut waits) = (0, 0);
+        for tick in 1..=CPU_HZ {
+            match chip8.tick() {
+                TickResult::Draw => draws += 1,
+                TickResult::WaitingForVblank => waits += 1,
+                _ => (),
+            }
+            // one interrupt each time a frame boundary passes
+            if tick * FRAME_HZ / CPU_HZ > (tick - 1) * FRAME_HZ / CPU_HZ {
+                chip8.trigger_frame_interrupt();
+            }
+        }
+        assert!(draws <= FRAME_HZ, "{} draws in one second", draws);
+        assert!(draws >= FRAME_HZ - 1, "{} draws in one second", draws);
+        assert_eq!(chip8.execution_summary().draw_calls, draws);
+        assert!(waits > 0);
+
+        // without the quirk the loop draws on every other tick
+        let mut chip8 = emulator_with(&[0xD001, 0x1200]);
+        chip8.set_quirks(QuirksConfig::chip48());
+        chip8.tick_n(CPU_HZ as usize);
+        assert_eq!(chip8.execution_summary().draw_calls, CPU_HZ / 2);
     }
 }
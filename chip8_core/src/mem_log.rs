@@ -0,0 +1,76 @@
+use crate::{Emulator, RAM_SIZE, ScreenBuffer};
+
+/// Every RAM access made while logging was active, in execution order.
+/// Instruction fetches are recorded as two single-byte reads.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MemoryAccessLog {
+    pub reads: Vec<(u16, u8)>,
+    pub writes: Vec<(u16, u8)>,
+}
+
+impl MemoryAccessLog {
+    /// Number of reads and writes per address.
+    pub fn heatmap(&self) -> [u32; RAM_SIZE] {
+        let mut counts = [0; RAM_SIZE];
+        for (addr, _) in self.reads.iter().chain(&self.writes) {
+            counts[*addr as usize % RAM_SIZE] += 1;
+        }
+        counts
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Starts a fresh log, discarding any log already in progress.
+    pub fn start_logging(&mut self) {
+        self.mem_log = Some(MemoryAccessLog::default());
+    }
+
+    /// Stops logging and returns everything recorded since `start_logging`.
+    pub fn stop_logging(&mut self) -> MemoryAccessLog {
+        self.mem_log.take().unwrap_or_default()
+    }
+
+    /// Access counts for the log in progress, all zero when not logging.
+    pub fn get_access_heatmap(&self) -> [u32; RAM_SIZE] {
+        self.mem_log
+            .as_ref()
+            .map_or([0; RAM_SIZE], MemoryAccessLog::heatmap)
+    }
+
+    pub(crate) fn log_read(&mut self, addr: u16, val: u8) {
+        if let Some(log) = self.mem_log.as_mut() {
+            log.reads.push((addr, val));
+        }
+    }
+
+    pub(crate) fn log_write(&mut self, addr: u16, val: u8) {
+        if let Some(log) = self.mem_log.as_mut() {
+            log.writes.push((addr, val));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::roms::DRAW_ROM;
+    use crate::{Emulator, FONTSET, FONTSET_SIZE, QuirksConfig, RAM_SIZE};
+
+    #[test]
+    fn draw_rom_only_reads_the_font() {
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+        chip8.load_rom(DRAW_ROM).unwrap();
+        chip8.start_logging();
+        chip8.tick_n(200);
+
+        let heatmap = chip8.get_access_heatmap();
+        assert!(heatmap[..FONTSET_SIZE].iter().any(|&count| count > 0));
+
+        let log = chip8.stop_logging();
+        let in_font = |addr: u16| (addr as usize) < FONTSET_SIZE;
+        assert!(log.writes.iter().all(|&(addr, _)| !in_font(addr)));
+        for &(addr, val) in log.reads.iter().filter(|&&(addr, _)| in_font(addr)) {
+            assert_eq!(val, FONTSET[addr as usize]);
+        }
+        assert_eq!(chip8.get_access_heatmap(), [0; RAM_SIZE]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{ChipError, ChipWarning, Emulator, RAM_SIZE, ScreenBuffer};
+
+pub type MmioRead = Box<dyn Fn(u16) -> u8 + Send>;
+pub type MmioWrite = Box<dyn Fn(u16, u8) + Send>;
+
+/// Example peripheral: every byte written in `[CHAR_OUTPUT_START, CHAR_OUTPUT_END)`
+/// is appended to the buffer returned by `get_mmio_output()`.
+pub const CHAR_OUTPUT_START: u16 = 0xF00;
+pub const CHAR_OUTPUT_END: u16 = 0xF10;
+
+#[derive(Clone)]
+pub(crate) struct MmioRegion {
+    start: u16,
+    end: u16,
+    device: MmioDevice,
+}
+
+// callbacks are shared with clones of the emulator, it is the same device
+#[derive(Clone)]
+enum MmioDevice {
+    Callbacks {
+        read: Arc<Mutex<MmioRead>>,
+        write: Arc<Mutex<MmioWrite>>,
+    },
+    CharOutput,
+}
+
+impl MmioRegion {
+    fn contains(&self, addr: u16) -> bool {
+        (self.start..self.end).contains(&addr)
+    }
+
+    fn overlaps(&self, start: u16, end: u16) -> bool {
+        start < self.end && self.start < end
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Routes every opcode RAM access within `[start, end)` to `read` / `write`
+    /// instead of `ram`. Instruction fetches always bypass MMIO.
+    pub fn register_mmio(
+        &mut self,
+        start: u16,
+        end: u16,
+        read: MmioRead,
+        write: MmioWrite,
+    ) -> Result<(), ChipError> {
+        let device = MmioDevice::Callbacks {
+            read: Arc::new(Mutex::new(read)),
+            write: Arc::new(Mutex::new(write)),
+        };
+        self.add_mmio_region(start, end, device)
+    }
+
+    pub fn register_char_output(&mut self) -> Result<(), ChipError> {
+        self.add_mmio_region(CHAR_OUTPUT_START, CHAR_OUTPUT_END, MmioDevice::CharOutput)
+    }
+
+    pub fn clear_mmio(&mut self) {
+        self.mmio.clear();
+    }
+
+    pub fn get_mmio_output(&self) -> &[char] {
+        &self.mmio_output
+    }
+
+    fn add_mmio_region(
+        &mut self,
+        start: u16,
+        end: u16,
+        device: MmioDevice,
+    ) -> Result<(), ChipError> {
+        if start >= end || self.mmio.iter().any(|r| r.overlaps(start, end)) {
+            return Err(ChipError::InvalidMmioRegion { start, end });
+        }
+        self.mmio.push(MmioRegion { start, end, device });
+        Ok(())
+    }
+
+    // addresses past the end of RAM are only valid inside an MMIO region
+    pub(crate) fn read_ram(&mut self, addr: u16) -> Result<u8, ChipError> {
+        let val = match self.mmio.iter().find(|r| r.contains(addr)) {
+            Some(MmioRegion {
+                device: MmioDevice::Callbacks { read, .. },
+                ..
+            }) => read.lock().unwrap_or_else(PoisonError::into_inner)(addr),
+            Some(MmioRegion {
+                device: MmioDevice::CharOutput,
+                ..
+            }) => 0,
+            None => match self.color_ram_index(addr) {
+                Some(index) => self.color_ram[index],
+                None => *self
+                    .ram
+                    .get(addr as usize)
+                    .ok_or(ChipError::InvalidMemoryAccess(addr))?,
+            },
+        };
+        #[cfg(feature = "mem-log")]
+        self.log_read(addr, val);
+        Ok(val)
+    }
+
+    pub(crate) fn write_ram(&mut self, addr: u16, val: u8) -> Result<(), ChipError> {
+        let mapped = self.mmio.iter().any(|r| r.contains(addr));
+        if !mapped && addr as usize >= RAM_SIZE {
+            return Err(ChipError::InvalidMemoryAccess(addr));
+        }
+        let color_ram = self.color_ram_index(addr);
+        if self.quirks.detect_self_modification
+            && color_ram.is_none()
+            && (self.start_addr as usize..self.start_addr as usize + self.loaded_rom.len())
+                .contains(&(addr as usize))
+        {
+            self.warnings.push(ChipWarning::SelfModification(addr));
+        }
+        #[cfg(feature = "mem-log")]
+        self.log_write(addr, val);
+
+        match self.mmio.iter().find(|r| r.contains(addr)) {
+            Some(MmioRegion {
+                device: MmioDevice::Callbacks { write, .. },
+                ..
+            }) => write.lock().unwrap_or_else(PoisonError::into_inner)(addr, val),
+            Some(MmioRegion {
+                device: MmioDevice::CharOutput,
+                ..
+            }) => self.mmio_output.push(char::from(val)),
+            None => match color_ram {
+                Some(index) => self.color_ram[index] = val,
+                None => self.ram[addr as usize] = val,
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuirksConfig;
+
+    #[test]
+    fn bcd_into_char_output() {
+        let mut chip8: Emulator = Emulator::new();
+        // LD V0, 0x7B (123); LD I, 0xF00; BCD V0
+        chip8
+            .load_rom(&[0x60, 0x7B, 0xAF, 0x00, 0xF0, 0x33])
+            .unwrap();
+        chip8.register_char_output().unwrap();
+        chip8.tick_n(3);
+
+        assert_eq!(chip8.get_mmio_output(), &['\u{1}', '\u{2}', '\u{3}']);
+        assert_eq!(&chip8.ram[0xF00..0xF03], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn writes_into_the_rom_are_reported() {
+        let mut chip8: Emulator = Emulator::new_with_quirks(QuirksConfig {
+            detect_self_modification: true,
+            ..QuirksConfig::default()
+        });
+        // LD I, 0x202; LD [I], V1
+        chip8.load_rom(&[0xA2, 0x02, 0xF1, 0x55]).unwrap();
+        chip8.tick_n(2);
+
+        assert_eq!(
+            chip8.drain_warnings(),
+            vec![
+                ChipWarning::SelfModification(0x202),
+                ChipWarning::SelfModification(0x203)
+            ]
+        );
+        assert!(chip8.drain_warnings().is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use bitvec::slice::BitSlice;
+
+use crate::{Emulator, ScreenBuffer};
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// How many times DXYN has toggled each pixel, row-major like `get_display`.
+    /// Pixels that flip every frame are the ones that flicker. Counts are kept
+    /// per resolution, this returns the ones for the active one.
+    pub fn get_pixel_flip_counts(&self) -> &[u32] {
+        &self.pixel_flip_count[..self.screen.width() * self.screen.height()]
+    }
+
+    pub fn reset_pixel_flip_counts(&mut self) {
+        self.pixel_flip_count.fill(0);
+    }
+
+    /// Wall-clock nanoseconds spent executing each opcode family, indexed by
+    /// the first nibble. Includes the time for instructions that failed.
+    pub fn get_opcode_timings(&self) -> &[u64; 16] {
+        &self.opcode_time_ns
+    }
+
+    /// How many times each opcode family ran, indexed like `get_opcode_timings`.
+    pub fn get_opcode_counts(&self) -> &[u64; 16] {
+        &self.opcode_calls
+    }
+
+    /// Clears both the timings and the counts.
+    pub fn reset_opcode_timings(&mut self) {
+        self.opcode_time_ns = [0; 16];
+        self.opcode_calls = [0; 16];
+    }
+
+    /// One bit per instruction slot, bit n standing for address 2n. A bit is
+    /// set once an instruction has been fetched from that address.
+    pub fn get_coverage(&self) -> &BitSlice {
+        &self.pc_coverage
+    }
+
+    /// Percentage of the instruction slots in the first `rom_len` bytes of
+    /// the program that have run. Sprites and other data count as never run.
+    pub fn get_coverage_percentage(&self, rom_len: usize) -> f64 {
+        let first = self.start_addr as usize / 2;
+        let last = (first + rom_len.div_ceil(2)).min(self.pc_coverage.len());
+        if last <= first {
+            return 0.0;
+        }
+        let covered = self.pc_coverage[first..last].count_ones();
+        covered as f64 * 100.0 / (last - first) as f64
+    }
+
+    pub fn reset_coverage(&mut self) {
+        self.pc_coverage.fill(false);
+    }
+
+    pub(crate) fn record_opcode_time(&mut self, op: u16, elapsed: Duration) {
+        let family = (op >> 12) as usize;
+        self.opcode_calls[family] += 1;
+        self.opcode_time_ns[family] += elapsed.as_nanos() as u64;
+    }
+
+    // every set bit in a sprite row toggles its pixel, whether it was on or off
+    pub(crate) fn record_pixel_flips(&mut self, y: usize, data: u8, x_offset: usize) {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        for bit in 0..8 {
+            let x = x_offset + bit;
+            if x >= width || y >= height {
+                break;
+            }
+            if data & (0b1000_0000 >> bit) != 0 {
+                self.pixel_flip_count[x + width * y] += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::roms::DRAW_ROM;
+    use crate::{Emulator, FONTSET, FONTSET_SIZE, QuirksConfig};
+
+    #[test]
+    fn coverage_after_draw_rom() {
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+        chip8.load_rom(DRAW_ROM).unwrap();
+        assert_eq!(chip8.get_coverage().count_ones(), 0);
+
+        chip8.tick_n(1000);
+        let coverage = chip8.get_coverage();
+        assert_eq!(coverage.count_ones(), DRAW_ROM.len() / 2);
+        assert!(coverage[0x200 / 2] && coverage[0x20C / 2]);
+        assert!(coverage[..FONTSET_SIZE / 2].not_any());
+        assert_eq!(chip8.get_coverage_percentage(DRAW_ROM.len()), 100.0);
+        // the same slots with twice the room around them
+        assert_eq!(chip8.get_coverage_percentage(2 * DRAW_ROM.len()), 50.0);
+
+        chip8.reset_coverage();
+        assert!(chip8.get_coverage().not_any());
+    }
+
+    #[test]
+    fn drawing_a_sprite_twice_flips_each_of_its_pixels_twice() {
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+        // LD F, V0; DRW V0, V0, 5; DRW V0, V0, 5
+        chip8
+            .load_rom(&[0xF0, 0x29, 0xD0, 0x05, 0xD0, 0x05])
+            .unwrap();
+        chip8.tick_n(3);
+
+        let glyph = &FONTSET[..5];
+        let flips = chip8.get_pixel_flip_counts();
+        let width = chip8.screen_width();
+        for (y, row) in glyph.iter().enumerate() {
+            for x in 0..8 {
+                let expected = if row & (0x80 >> x) != 0 { 2 } else { 0 };
+                assert_eq!(flips[x + width * y], expected, "pixel ({x}, {y})");
+            }
+        }
+        assert_eq!(
+            flips.iter().sum::<u32>(),
+            2 * glyph.iter().map(|row| row.count_ones()).sum::<u32>()
+        );
+        assert!(chip8.get_display().iter().all(|&pixel| !pixel));
+    }
+
+    #[test]
+    fn opcode_timings_never_decrease() {
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+        chip8.load_rom(DRAW_ROM).unwrap();
+
+        let mut previous = *chip8.get_opcode_timings();
+        let mut previous_counts = *chip8.get_opcode_counts();
+        for _ in 0..500 {
+            chip8.tick();
+            let timings = *chip8.get_opcode_timings();
+            let counts = *chip8.get_opcode_counts();
+            for family in 0..16 {
+                assert!(timings[family] >= previous[family], "family {family:X}");
+                assert!(
+                    counts[family] >= previous_counts[family],
+                    "family {family:X}"
+                );
+            }
+            assert_eq!(
+                counts.iter().sum::<u64>(),
+                previous_counts.iter().sum::<u64>() + 1
+            );
+            (previous, previous_counts) = (timings, counts);
+        }
+
+        chip8.reset_opcode_timings();
+        assert_eq!(chip8.get_opcode_timings(), &[0; 16]);
+        assert_eq!(chip8.get_opcode_counts(), &[0; 16]);
+    }
+}
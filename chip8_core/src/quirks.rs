@@ -0,0 +1,317 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Quirks used by `Emulator::new()` and `QuirksConfig::default()`, matching the COSMAC VIP.
+pub const DEFAULT_QUIRKS: QuirksConfig = QuirksConfig {
+    detect_self_modification: false,
+    i_overflow_quirk: false,
+    vf_reset_on_logic: true,
+    shift_uses_vx: false,
+    memory_leave_i_unchanged: false,
+    jump_uses_vx: false,
+    sprite_wraps: false,
+    display_wait: true,
+    chip8x_enabled: false,
+    strict_rom_validation: false,
+};
+
+/// Behavioural switches that differ between CHIP-8 interpreters, plus
+/// optional diagnostics. Defaults to the original COSMAC VIP behaviour, see `DEFAULT_QUIRKS`.
+///
+/// Parses from and prints as a comma separated list of tokens, e.g.
+/// `"chip48,clip-sprites"`. Presets (`cosmac-vip`, `chip48`, `superchip`,
+/// `xochip`) set every behavioural quirk, later tokens override them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuirksConfig {
+    /// Report a `ChipWarning::SelfModification` whenever an opcode writes
+    /// into the bytes that were loaded from the ROM. Defaults to `false`.
+    pub detect_self_modification: bool,
+    /// FX1E sets VF to 1 when I + VX goes past 0xFFF and to 0 otherwise,
+    /// as the Amiga interpreter did. When off, VF is left untouched. Defaults to `false`.
+    pub i_overflow_quirk: bool,
+    /// 8XY1/8XY2/8XY3 reset VF to 0. Defaults to `true`.
+    pub vf_reset_on_logic: bool,
+    /// 8XY6/8XYE shift VX in place instead of shifting VY into VX. Defaults to `false`.
+    pub shift_uses_vx: bool,
+    /// FX55/FX65 leave I alone instead of advancing it past the last register. Defaults to `false`.
+    pub memory_leave_i_unchanged: bool,
+    /// BNNN jumps to VX + NNN, X being the top nibble of NNN, instead of V0 + NNN. Defaults to `false`.
+    pub jump_uses_vx: bool,
+    /// Sprites that run off an edge reappear on the other side instead of
+    /// being clipped. Defaults to `false`.
+    pub sprite_wraps: bool,
+    /// After DXYN the CPU waits for the next 60 Hz frame interrupt, as the VIP
+    /// waited for vertical blank, see `Emulator::trigger_frame_interrupt`. Defaults to `true`.
+    pub display_wait: bool,
+    /// Run the CHIP-8X colour instructions: 02A0, 02A2, 02A4, 02A6, 5XY1,
+    /// 5XY2 and BXYN, which replaces BNNN, and map color RAM at 0x2C0.
+    /// Defaults to `false`.
+    pub chip8x_enabled: bool,
+    /// `load_rom` rejects ROMs whose first instruction is not a known
+    /// opcode, which catches most files that aren't CHIP-8 programs. Defaults to `false`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strict_rom_validation: bool,
+}
+
+/// Shorter name for `QuirksConfig`, as taken by `Emulator::with_quirks`.
+pub type Quirks = QuirksConfig;
+
+impl Default for QuirksConfig {
+    fn default() -> Self {
+        DEFAULT_QUIRKS
+    }
+}
+
+impl QuirksConfig {
+    /// The original interpreter, same as `DEFAULT_QUIRKS`.
+    pub const fn cosmac_vip() -> Self {
+        DEFAULT_QUIRKS
+    }
+
+    /// The HP-48 interpreter most 90s ROMs were written for.
+    pub const fn chip48() -> Self {
+        QuirksConfig {
+            vf_reset_on_logic: false,
+            shift_uses_vx: true,
+            memory_leave_i_unchanged: true,
+            jump_uses_vx: true,
+            sprite_wraps: false,
+            display_wait: false,
+            ..DEFAULT_QUIRKS
+        }
+    }
+
+    /// SUPER-CHIP 1.1, which kept CHIP-48's choices.
+    pub const fn superchip() -> Self {
+        Self::chip48()
+    }
+
+    pub const fn xochip() -> Self {
+        QuirksConfig {
+            vf_reset_on_logic: false,
+            shift_uses_vx: false,
+            memory_leave_i_unchanged: false,
+            jump_uses_vx: false,
+            sprite_wraps: true,
+            display_wait: false,
+            ..DEFAULT_QUIRKS
+        }
+    }
+
+    pub fn with_vf_reset(mut self, on: bool) -> Self {
+        self.vf_reset_on_logic = on;
+        self
+    }
+
+    pub fn with_shift_vx(mut self, on: bool) -> Self {
+        self.shift_uses_vx = on;
+        self
+    }
+
+    pub fn with_load_store_inc(mut self, on: bool) -> Self {
+        self.memory_leave_i_unchanged = !on;
+        self
+    }
+
+    pub fn with_jump_vx(mut self, on: bool) -> Self {
+        self.jump_uses_vx = on;
+        self
+    }
+
+    pub fn with_clip_sprites(mut self, on: bool) -> Self {
+        self.sprite_wraps = !on;
+        self
+    }
+
+    pub fn with_display_wait(mut self, on: bool) -> Self {
+        self.display_wait = on;
+        self
+    }
+
+    // presets only touch the behavioural quirks, diagnostics and CHIP-8X carry over
+    fn with_preset(self, preset: QuirksConfig) -> Self {
+        QuirksConfig {
+            detect_self_modification: self.detect_self_modification,
+            i_overflow_quirk: self.i_overflow_quirk,
+            chip8x_enabled: self.chip8x_enabled,
+            strict_rom_validation: self.strict_rom_validation,
+            ..preset
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseQuirksError {
+    UnknownToken(String),
+}
+
+impl fmt::Display for ParseQuirksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseQuirksError::UnknownToken(token) => write!(f, "unknown quirk '{}'", token),
+        }
+    }
+}
+
+impl std::error::Error for ParseQuirksError {}
+
+/// Starts from `DEFAULT_QUIRKS` and applies each token in order.
+impl FromStr for QuirksConfig {
+    type Err = ParseQuirksError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut quirks = DEFAULT_QUIRKS;
+        for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            quirks = match token {
+                "cosmac-vip" => quirks.with_preset(QuirksConfig::cosmac_vip()),
+                "chip48" => quirks.with_preset(QuirksConfig::chip48()),
+                "superchip" => quirks.with_preset(QuirksConfig::superchip()),
+                "xochip" => quirks.with_preset(QuirksConfig::xochip()),
+                "vf-reset" => quirks.with_vf_reset(true),
+                "no-vf-reset" => quirks.with_vf_reset(false),
+                "shift-vx" => quirks.with_shift_vx(true),
+                "shift-vy" => quirks.with_shift_vx(false),
+                "clip-sprites" => quirks.with_clip_sprites(true),
+                "wrap-sprites" => quirks.with_clip_sprites(false),
+                "load-store-inc" => quirks.with_load_store_inc(true),
+                "load-store-noinc" => quirks.with_load_store_inc(false),
+                "jump-v0" => quirks.with_jump_vx(false),
+                "jump-vx" => quirks.with_jump_vx(true),
+                "display-wait" => quirks.with_display_wait(true),
+                "no-display-wait" => quirks.with_display_wait(false),
+                "detect-self-modification" => QuirksConfig {
+                    detect_self_modification: true,
+                    ..quirks
+                },
+                "i-overflow" => QuirksConfig {
+                    i_overflow_quirk: true,
+                    ..quirks
+                },
+                "chip8x" => QuirksConfig {
+                    chip8x_enabled: true,
+                    ..quirks
+                },
+                "strict-rom" => QuirksConfig {
+                    strict_rom_validation: true,
+                    ..quirks
+                },
+                _ => return Err(ParseQuirksError::UnknownToken(token.to_string())),
+            };
+        }
+        Ok(quirks)
+    }
+}
+
+/// Lists every behavioural quirk so the output parses back to the same config.
+impl fmt::Display for QuirksConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = vec![
+            if self.vf_reset_on_logic {
+                "vf-reset"
+            } else {
+                "no-vf-reset"
+            },
+            if self.shift_uses_vx {
+                "shift-vx"
+            } else {
+                "shift-vy"
+            },
+            if self.sprite_wraps {
+                "wrap-sprites"
+            } else {
+                "clip-sprites"
+            },
+            if self.memory_leave_i_unchanged {
+                "load-store-noinc"
+            } else {
+                "load-store-inc"
+            },
+            if self.jump_uses_vx {
+                "jump-vx"
+            } else {
+                "jump-v0"
+            },
+            if self.display_wait {
+                "display-wait"
+            } else {
+                "no-display-wait"
+            },
+        ];
+        if self.detect_self_modification {
+            tokens.push("detect-self-modification");
+        }
+        if self.i_overflow_quirk {
+            tokens.push("i-overflow");
+        }
+        if self.chip8x_enabled {
+            tokens.push("chip8x");
+        }
+        if self.strict_rom_validation {
+            tokens.push("strict-rom");
+        }
+        write!(f, "{}", tokens.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_preset_with_overrides() {
+        let quirks: QuirksConfig = "chip48,clip-sprites".parse().unwrap();
+        assert_eq!(quirks, QuirksConfig::chip48().with_clip_sprites(true));
+
+        let quirks: QuirksConfig = " xochip , no-vf-reset, i-overflow ".parse().unwrap();
+        assert_eq!(
+            quirks,
+            QuirksConfig {
+                i_overflow_quirk: true,
+                ..QuirksConfig::xochip().with_vf_reset(false)
+            }
+        );
+    }
+
+    #[test]
+    fn presets_round_trip_through_display() {
+        for (name, preset) in [
+            ("cosmac-vip", QuirksConfig::cosmac_vip()),
+            ("chip48", QuirksConfig::chip48()),
+            ("superchip", QuirksConfig::superchip()),
+            ("xochip", QuirksConfig::xochip()),
+        ] {
+            assert_eq!(name.parse::<QuirksConfig>(), Ok(preset), "{}", name);
+            assert_eq!(
+                preset.to_string().parse::<QuirksConfig>(),
+                Ok(preset),
+                "{}",
+                name
+            );
+        }
+
+        let diagnostics = QuirksConfig {
+            detect_self_modification: true,
+            i_overflow_quirk: true,
+            chip8x_enabled: true,
+            strict_rom_validation: true,
+            ..QuirksConfig::chip48()
+        };
+        assert_eq!(diagnostics.to_string().parse(), Ok(diagnostics));
+    }
+
+    #[test]
+    fn unknown_quirk_is_rejected() {
+        assert_eq!(
+            "chip48,no-such-quirk".parse::<QuirksConfig>(),
+            Err(ParseQuirksError::UnknownToken("no-such-quirk".to_string()))
+        );
+        assert_eq!(
+            ParseQuirksError::UnknownToken("x".to_string()).to_string(),
+            "unknown quirk 'x'"
+        );
+    }
+}
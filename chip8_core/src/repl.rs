@@ -0,0 +1,337 @@
+//! A line-based debugger for the terminal, built on `step()` and the
+//! disassembler. `chip8-run --repl` starts one on stdin and stdout.
+//!
+//! Commands:
+//!
+//! ```text
+//! step [N]        run N instructions (default 1), printing each. An empty line steps once.
+//! XXXX            write the opcode XXXX (four hex digits) at PC and run it
+//! state           registers, I, PC, SP, timers and the next instruction
+//! mem ADDR [N]    N bytes from ADDR, 16 by default
+//! dis [ADDR] [N]  disassemble N instructions from ADDR, PC and 8 by default
+//! break ADDR      stop `continue` when PC reaches ADDR
+//! continue        run until a breakpoint, an error or the program halts
+//! reset           reload the ROM and start over, breakpoints are kept
+//! quit            also on end of input
+//! ```
+//!
+//! Addresses and opcodes are hex, with or without `0x`. There is no
+//! assembler yet, so instructions are entered as opcodes.
+
+use std::io::{self, BufRead, Write};
+
+use crate::{Emulator, RAM_SIZE, REPLAY_TICKS_PER_FRAME, StepInfo, TickResult};
+
+const DEFAULT_MEM_BYTES: usize = 16;
+const DEFAULT_DIS_LINES: usize = 8;
+// `continue` gives up after this many ticks, so a loop without a breakpoint can't hang
+const CONTINUE_LIMIT: u64 = 1_000_000;
+
+/// An emulator driven by typed commands, see the module docs.
+pub struct Repl {
+    chip8: Emulator,
+    // ticks since the timers last ran, they tick once a frame like chip8-run
+    ticks: u64,
+}
+
+impl Repl {
+    /// Takes an emulator with its ROM already loaded.
+    pub fn new(chip8: Emulator) -> Self {
+        Repl { chip8, ticks: 0 }
+    }
+
+    pub fn emulator(&self) -> &Emulator {
+        &self.chip8
+    }
+
+    /// Reads commands until `quit` or the end of `input`, prompting with `chip8> `.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "chip8> ")?;
+        output.flush()?;
+        for line in input.lines() {
+            if !self.execute(&line?, &mut output)? {
+                return Ok(());
+            }
+            write!(output, "chip8> ")?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+
+    /// Runs one command line. Returns false once the session should end.
+    pub fn execute(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("step");
+        let args: Vec<&str> = words.collect();
+        match (command, args.as_slice()) {
+            ("quit" | "q", []) => return Ok(false),
+            ("step" | "s", []) => self.step(1, out)?,
+            ("step" | "s", [n]) => match n.parse() {
+                Ok(n) => self.step(n, out)?,
+                Err(_) => writeln!(out, "step needs a count")?,
+            },
+            ("state", []) => self.print_state(out)?,
+            ("mem", [addr]) => self.print_mem(addr, None, out)?,
+            ("mem", [addr, n]) => self.print_mem(addr, Some(n), out)?,
+            ("dis", []) => self.print_disassembly(self.chip8.pc, DEFAULT_DIS_LINES, out)?,
+            ("dis", [addr]) => match parse_hex(addr) {
+                Some(addr) => self.print_disassembly(addr, DEFAULT_DIS_LINES, out)?,
+                None => writeln!(out, "dis needs a hex address")?,
+            },
+            ("dis", [addr, n]) => match (parse_hex(addr), n.parse()) {
+                (Some(addr), Ok(n)) => self.print_disassembly(addr, n, out)?,
+                _ => writeln!(out, "dis needs a hex address and a count")?,
+            },
+            ("break" | "b", [addr]) => match parse_hex(addr).filter(|&a| (a as usize) < RAM_SIZE) {
+                Some(addr) => {
+                    self.chip8.add_breakpoint(addr);
+                    writeln!(out, "breakpoint at {:#05X}", addr)?;
+                }
+                None => writeln!(out, "break needs a hex address below {:#X}", RAM_SIZE)?,
+            },
+            ("continue" | "c", []) => self.run_to_break(out)?,
+            ("reset", []) => {
+                self.chip8.soft_reset();
+                self.ticks = 0;
+                writeln!(out, "reset, PC = {:#05X}", self.chip8.pc)?;
+            }
+            ("help", []) => writeln!(
+                out,
+                "step [N], XXXX, state, mem ADDR [N], dis [ADDR] [N], break ADDR, continue, reset, quit"
+            )?,
+            (op, []) if op.len() == 4 && parse_hex(op).is_some() => {
+                self.inject(parse_hex(op).unwrap_or_default(), out)?
+            }
+            _ => writeln!(out, "unknown command: {}, try help", line.trim())?,
+        }
+        Ok(true)
+    }
+
+    fn step(&mut self, n: u64, out: &mut impl Write) -> io::Result<()> {
+        for _ in 0..n {
+            if !self.step_once(out)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // prints what one step did, false if it failed or the program halted
+    fn step_once(&mut self, out: &mut impl Write) -> io::Result<bool> {
+        let result = self.chip8.step();
+        self.tick_timers();
+        match result {
+            Ok(step) => {
+                write_step(out, &step)?;
+                Ok(step.result != TickResult::Halted)
+            }
+            Err(err) => {
+                writeln!(out, "error: {}", err)?;
+                Ok(false)
+            }
+        }
+    }
+
+    fn inject(&mut self, op: u16, out: &mut impl Write) -> io::Result<()> {
+        let pc = self.chip8.pc as usize;
+        if pc + 1 >= RAM_SIZE {
+            return writeln!(out, "PC {:#05X} is at the end of RAM", pc);
+        }
+        let [hi, lo] = op.to_be_bytes();
+        self.chip8.ram[pc] = hi;
+        self.chip8.ram[pc + 1] = lo;
+        self.step_once(out).map(|_| ())
+    }
+
+    fn run_to_break(&mut self, out: &mut impl Write) -> io::Result<()> {
+        for _ in 0..CONTINUE_LIMIT {
+            let result = self.chip8.tick();
+            self.tick_timers();
+            match result {
+                TickResult::BreakpointHit(addr) => {
+                    writeln!(out, "stopped at breakpoint {:#05X}", addr)?;
+                    return self.print_next(out);
+                }
+                TickResult::Halted => return writeln!(out, "halted"),
+                TickResult::Error(err) => return writeln!(out, "error: {}", err),
+                _ => (),
+            }
+        }
+        writeln!(out, "still running after {} ticks", CONTINUE_LIMIT)?;
+        self.print_next(out)
+    }
+
+    fn tick_timers(&mut self) {
+        self.ticks += 1;
+        if self.ticks.is_multiple_of(REPLAY_TICKS_PER_FRAME as u64) {
+            self.chip8.tick_timers();
+        }
+    }
+
+    fn print_state(&self, out: &mut impl Write) -> io::Result<()> {
+        let chip8 = &self.chip8;
+        for (row, regs) in chip8.get_registers().chunks(8).enumerate() {
+            let cells: Vec<String> = regs
+                .iter()
+                .enumerate()
+                .map(|(i, v)| format!("V{:X}={:02X}", row * 8 + i, v))
+                .collect();
+            writeln!(out, "{}", cells.join(" "))?;
+        }
+        writeln!(
+            out,
+            "I={:03X} PC={:03X} SP={} DT={:02X} ST={:02X}",
+            chip8.get_i(),
+            chip8.get_pc(),
+            chip8.get_sp(),
+            chip8.get_dt(),
+            chip8.get_st()
+        )?;
+        self.print_next(out)
+    }
+
+    fn print_next(&self, out: &mut impl Write) -> io::Result<()> {
+        self.print_disassembly(self.chip8.pc, 1, out)
+    }
+
+    fn print_mem(&self, addr: &str, n: Option<&str>, out: &mut impl Write) -> io::Result<()> {
+        let n = n.map_or(Ok(DEFAULT_MEM_BYTES), str::parse);
+        let (Some(addr), Ok(n)) = (parse_hex(addr), n) else {
+            return writeln!(out, "mem needs a hex address and a count");
+        };
+        let start = (addr as usize).min(RAM_SIZE);
+        let end = (start + n).min(RAM_SIZE);
+        for (row, bytes) in (start..end).collect::<Vec<_>>().chunks(16).enumerate() {
+            let hex: Vec<String> = bytes
+                .iter()
+                .map(|&a| format!("{:02X}", self.chip8.ram[a]))
+                .collect();
+            writeln!(out, "{:#05X}  {}", start + row * 16, hex.join(" "))?;
+        }
+        Ok(())
+    }
+
+    fn print_disassembly(&self, addr: u16, n: usize, out: &mut impl Write) -> io::Result<()> {
+        for i in 0..n {
+            let at = addr as usize + 2 * i;
+            if at + 1 >= RAM_SIZE {
+                break;
+            }
+            let op = u16::from_be_bytes([self.chip8.ram[at], self.chip8.ram[at + 1]]);
+            let marker = if at == self.chip8.pc as usize {
+                '>'
+            } else {
+                ' '
+            };
+            writeln!(
+                out,
+                "{} {:#05X}  {:04X}  {}",
+                marker,
+                at,
+                op,
+                self.chip8.decode_opcode(op)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// the same layout as the desktop's --debug stepping
+fn write_step(out: &mut impl Write, step: &StepInfo) -> io::Result<()> {
+    let Some(mnemonic) = &step.mnemonic else {
+        return writeln!(out, "{:#05X}  {:?}", step.pc_before, step.result);
+    };
+    writeln!(
+        out,
+        "{:#05X}  {:04X}  {:<16} -> {:#05X}",
+        step.pc_before, step.opcode, mnemonic, step.pc_after
+    )?;
+    for (idx, old, new) in &step.registers_changed {
+        writeln!(out, "    V{:X}: {:#04X} -> {:#04X}", idx, old, new)?;
+    }
+    if step.draw_occurred {
+        writeln!(out, "    screen redrawn")?;
+    }
+    if step.waiting_for_vblank {
+        writeln!(out, "    waiting for vblank")?;
+    }
+    if let Some(key) = step.waiting_for_key_release {
+        writeln!(out, "    waiting for key {:X} to be released", key)?;
+    }
+    Ok(())
+}
+
+fn parse_hex(text: &str) -> Option<u16> {
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u16::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuirksConfig;
+    use crate::roms::COUNTER_ROM;
+
+    fn session(input: &str) -> (Repl, String) {
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+        chip8.load_rom(COUNTER_ROM).unwrap();
+        let mut repl = Repl::new(chip8);
+        let mut output = Vec::new();
+        repl.run(input.as_bytes(), &mut output).unwrap();
+        (repl, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn steps_and_shows_the_changes() {
+        let (repl, out) = session("step\n\nstate\n");
+        assert!(out.contains("0x200  6000  LD V0, 0x00"), "{}", out);
+        assert!(out.contains("0x202  7001  ADD V0, 0x01"), "{}", out);
+        assert!(out.contains("    V0: 0x00 -> 0x01"), "{}", out);
+        assert!(out.contains("V0=01 V1=00"), "{}", out);
+        assert!(out.contains("PC=204"), "{}", out);
+        assert_eq!(repl.emulator().get_pc(), 0x204);
+    }
+
+    #[test]
+    fn injected_opcodes_run_at_pc() {
+        let (repl, out) = session("6342\nA123\nmem 200 4\n");
+        assert!(out.contains("6342  LD V3, 0x42"), "{}", out);
+        assert!(out.contains("    V3: 0x00 -> 0x42"), "{}", out);
+        assert_eq!(repl.emulator().get_i(), 0x123);
+        assert!(out.contains("0x200  63 42 A1 23"), "{}", out);
+    }
+
+    #[test]
+    fn continue_stops_at_a_breakpoint() {
+        let (repl, out) = session("break 204\ncontinue\n");
+        assert!(out.contains("breakpoint at 0x204"), "{}", out);
+        assert!(out.contains("stopped at breakpoint 0x204"), "{}", out);
+        assert!(out.contains("> 0x204"), "{}", out);
+        assert_eq!(repl.emulator().get_pc(), 0x204);
+    }
+
+    #[test]
+    fn reset_reloads_the_rom() {
+        let (repl, out) = session("6342\nreset\ndis 200 1\n");
+        assert!(out.contains("reset, PC = 0x200"), "{}", out);
+        assert!(out.contains("> 0x200  6000  LD V0, 0x00"), "{}", out);
+        assert_eq!(repl.emulator().get_registers()[3], 0);
+    }
+
+    #[test]
+    fn errors_do_not_end_the_session() {
+        let (repl, out) = session("bogus\nmem zz\n5001\nquit\nstep\n");
+        assert!(out.contains("unknown command: bogus"), "{}", out);
+        assert!(
+            out.contains("mem needs a hex address and a count"),
+            "{}",
+            out
+        );
+        assert!(out.contains("error: "), "{}", out);
+        // nothing ran after quit
+        assert_eq!(repl.emulator().get_total_ticks(), 0);
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{ChipError, Emulator, KEY_COUNT, QuirksConfig, ScreenBuffer, TickResult};
+
+/// Instructions run per replay frame, the desktop frontend's default speed.
+/// The timers tick once at the end of every frame.
+pub const REPLAY_TICKS_PER_FRAME: usize = 10;
+
+/// A recorded session that can be played back to reproduce a bug.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReplayFile {
+    pub rom_sha256: [u8; 32],
+    pub quirks: QuirksConfig,
+    pub rng_seed: u64,
+    /// Applied at the start of their frame, in order.
+    pub input_events: Vec<InputEvent>,
+    /// Checked at the end of the given frame.
+    pub assertion_frames: Vec<(u64, ScreenAssertion)>,
+    /// `get_screen_hash` at the end of each frame, from `replay_frame_hashes`.
+    /// Playback fails at the first frame that hashes differently.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub frame_hashes: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputEvent {
+    pub frame: u64,
+    pub key: u8,
+    pub pressed: bool,
+}
+
+/// Expected values for a handful of pixels, as `(x, y, on)`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScreenAssertion {
+    pub pixels: Vec<(u8, u8, bool)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The loaded ROM is not the one the replay was recorded with.
+    RomMismatch,
+    /// The emulator failed at this frame.
+    Chip { frame: u64, error: ChipError },
+    /// Pixels that did not match, as `(x, y, expected)`.
+    AssertionFailed {
+        frame: u64,
+        mismatches: Vec<(u8, u8, bool)>,
+    },
+    /// The screen stopped matching the recorded `frame_hashes` at this frame.
+    Diverged {
+        frame: u64,
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::RomMismatch => write!(f, "loaded ROM does not match the replay"),
+            ReplayError::Chip { frame, error } => write!(f, "frame {}: {}", frame, error),
+            ReplayError::AssertionFailed { frame, mismatches } => {
+                write!(f, "frame {}: {} pixel(s) differ:", frame, mismatches.len())?;
+                for (x, y, expected) in mismatches {
+                    write!(
+                        f,
+                        " ({}, {}) expected {}",
+                        x,
+                        y,
+                        if *expected { "on" } else { "off" }
+                    )?;
+                }
+                Ok(())
+            }
+            ReplayError::Diverged {
+                frame,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "frame {}: screen hash {:016X}, expected {:016X}",
+                frame, actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Plays `replay` against the ROM that was just loaded and returns the
+    /// final display, row-major like `get_display`. Stops at the first
+    /// failed assertion.
+    pub fn play_replay(&mut self, replay: &ReplayFile) -> Result<Vec<bool>, ReplayError> {
+        self.run_replay(replay, |_| ())?;
+
+        let width = self.screen.width();
+        Ok((0..self.screen.height())
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.screen.get_pixel(x, y))
+            .collect())
+    }
+
+    /// Plays `replay` like `play_replay` and returns the screen hash at the
+    /// end of every frame. Store them in `frame_hashes` to catch later runs
+    /// that diverge, or compare two lists directly.
+    pub fn replay_frame_hashes(&mut self, replay: &ReplayFile) -> Result<Vec<u64>, ReplayError> {
+        let mut hashes = Vec::new();
+        self.run_replay(replay, |hash| hashes.push(hash))?;
+        Ok(hashes)
+    }
+
+    // calls on_frame with the screen hash at the end of each frame
+    fn run_replay(
+        &mut self,
+        replay: &ReplayFile,
+        mut on_frame: impl FnMut(u64),
+    ) -> Result<(), ReplayError> {
+        if <[u8; 32]>::from(Sha256::digest(&self.loaded_rom)) != replay.rom_sha256 {
+            return Err(ReplayError::RomMismatch);
+        }
+        self.set_quirks(replay.quirks);
+        self.seed_rng(replay.rng_seed);
+
+        let last_frame = replay
+            .input_events
+            .iter()
+            .map(|event| event.frame)
+            .chain(replay.assertion_frames.iter().map(|(frame, _)| *frame))
+            .chain((replay.frame_hashes.len() as u64).checked_sub(1))
+            .max();
+
+        for frame in 0..=last_frame.unwrap_or(0) {
+            for event in replay.input_events.iter().filter(|e| e.frame == frame) {
+                if (event.key as usize) < KEY_COUNT {
+                    self.keypress(event.key as usize, event.pressed);
+                }
+            }
+            for _ in 0..REPLAY_TICKS_PER_FRAME {
+                if let TickResult::Error(error) = self.tick() {
+                    return Err(ReplayError::Chip { frame, error });
+                }
+            }
+            self.tick_timers();
+
+            for (_, assertion) in replay.assertion_frames.iter().filter(|(f, _)| *f == frame) {
+                let mismatches: Vec<(u8, u8, bool)> = assertion
+                    .pixels
+                    .iter()
+                    .filter(|(x, y, on)| self.pixel_or_off(*x as usize, *y as usize) != *on)
+                    .copied()
+                    .collect();
+                if !mismatches.is_empty() {
+                    return Err(ReplayError::AssertionFailed { frame, mismatches });
+                }
+            }
+
+            let actual = self.get_screen_hash();
+            if let Some(&expected) = replay.frame_hashes.get(frame as usize)
+                && expected != actual
+            {
+                return Err(ReplayError::Diverged {
+                    frame,
+                    expected,
+                    actual,
+                });
+            }
+            on_frame(actual);
+        }
+        Ok(())
+    }
+
+    // assertions come from a file, so positions off the screen read as off
+    fn pixel_or_off(&self, x: usize, y: usize) -> bool {
+        x < self.screen.width() && y < self.screen.height() && self.screen.get_pixel(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roms::DRAW_ROM;
+
+    fn draw_rom_emulator() -> Emulator {
+        let mut chip8 = Emulator::new();
+        chip8.load_rom(DRAW_ROM).unwrap();
+        chip8
+    }
+
+    // DRAW_ROM shows one digit per frame, the VIP display wait holds each
+    // DXYN until the timers tick at the end of the frame
+    fn draw_rom_replay(assertion_frames: Vec<(u64, ScreenAssertion)>) -> ReplayFile {
+        ReplayFile {
+            rom_sha256: Sha256::digest(DRAW_ROM).into(),
+            quirks: QuirksConfig::default(),
+            rng_seed: 1,
+            input_events: Vec::new(),
+            assertion_frames,
+            frame_hashes: Vec::new(),
+        }
+    }
+
+    fn pixels(pixels: &[(u8, u8, bool)]) -> ScreenAssertion {
+        ScreenAssertion {
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn passing_assertions() {
+        let replay = draw_rom_replay(vec![
+            // the top left corner of the 0 glyph, and its hole
+            (0, pixels(&[(0, 0, true), (1, 1, false)])),
+            // the stem of the 1
+            (1, pixels(&[(0, 0, false), (2, 0, true), (2, 4, true)])),
+        ]);
+        let display = draw_rom_emulator().play_replay(&replay).unwrap();
+        assert!(!display[0]);
+        assert!(display[2]);
+    }
+
+    #[test]
+    fn failing_assertion_reports_the_frame_and_pixels() {
+        let replay = draw_rom_replay(vec![
+            (0, pixels(&[(0, 0, true)])),
+            (1, pixels(&[(0, 0, true), (2, 0, true), (3, 1, true)])),
+        ]);
+        assert_eq!(
+            draw_rom_emulator().play_replay(&replay),
+            Err(ReplayError::AssertionFailed {
+                frame: 1,
+                mismatches: vec![(0, 0, true), (3, 1, true)],
+            })
+        );
+    }
+
+    #[test]
+    fn other_rom_is_rejected() {
+        let mut replay = draw_rom_replay(Vec::new());
+        replay.rom_sha256 = Sha256::digest(crate::roms::COUNTER_ROM).into();
+        assert_eq!(
+            draw_rom_emulator().play_replay(&replay),
+            Err(ReplayError::RomMismatch)
+        );
+    }
+
+    #[test]
+    fn tampered_frame_hash_diverges() {
+        let mut replay = draw_rom_replay(Vec::new());
+        replay
+            .assertion_frames
+            .push((3, ScreenAssertion::default()));
+        let hashes = draw_rom_emulator().replay_frame_hashes(&replay).unwrap();
+        assert_eq!(hashes.len(), 4);
+        // a new digit every frame
+        assert_ne!(hashes[0], hashes[1]);
+
+        replay.frame_hashes = hashes.clone();
+        assert!(draw_rom_emulator().play_replay(&replay).is_ok());
+
+        replay.frame_hashes[2] ^= 1;
+        assert_eq!(
+            draw_rom_emulator().play_replay(&replay),
+            Err(ReplayError::Diverged {
+                frame: 2,
+                expected: hashes[2] ^ 1,
+                actual: hashes[2],
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn survives_json() {
+        let mut replay = draw_rom_replay(vec![(0, pixels(&[(0, 0, true)]))]);
+        replay.input_events.push(InputEvent {
+            frame: 0,
+            key: 5,
+            pressed: true,
+        });
+        let json = serde_json::to_string(&replay).unwrap();
+        let parsed: ReplayFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, replay);
+        assert!(draw_rom_emulator().play_replay(&parsed).is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+//! Random number sources for CXNN, see `Emulator::with_rng`.
+
+#[cfg(feature = "rand")]
+use rand::rngs::SmallRng;
+#[cfg(feature = "rand")]
+use rand::{Rng, SeedableRng};
+
+/// Supplies the random bytes CXNN masks with NN. Sources must also be
+/// `Clone`, a cloned emulator gets a copy of its source.
+pub trait RngSource: Send + CloneRngSource {
+    fn next_byte(&mut self) -> u8;
+}
+
+/// Boxes a copy of a source, implemented for every `Clone` `RngSource`.
+pub trait CloneRngSource {
+    fn clone_box(&self) -> Box<dyn RngSource>;
+}
+
+impl<T: RngSource + Clone + 'static> CloneRngSource for T {
+    fn clone_box(&self) -> Box<dyn RngSource> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn RngSource> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+/// `rand`'s small, fast generator. The default source when the "rand"
+/// feature is on, which it is by default.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone)]
+pub struct SmallRngSource(SmallRng);
+
+#[cfg(feature = "rand")]
+impl SmallRngSource {
+    pub fn from_seed(seed: u64) -> Self {
+        SmallRngSource(SmallRng::seed_from_u64(seed))
+    }
+
+    /// Seeded from OS entropy.
+    pub fn from_entropy() -> Self {
+        SmallRngSource(SmallRng::from_os_rng())
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RngSource for SmallRngSource {
+    fn next_byte(&mut self) -> u8 {
+        self.0.random()
+    }
+}
+
+/// Always returns the same byte, for tests that need a known CXNN result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantRng(pub u8);
+
+impl RngSource for ConstantRng {
+    fn next_byte(&mut self) -> u8 {
+        self.0
+    }
+}
+
+/// A 64-bit linear congruential generator. Not much randomness, but it
+/// needs no dependencies and is the default source without "rand".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcgRng {
+    state: u64,
+    seed: u64,
+}
+
+impl LcgRng {
+    pub fn new(seed: u64) -> Self {
+        LcgRng { state: seed, seed }
+    }
+
+    /// The seed this generator started from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngSource for LcgRng {
+    fn next_byte(&mut self) -> u8 {
+        // Knuth's MMIX constants. The low bits of an LCG repeat quickly, so
+        // the byte comes from the top.
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.state >> 56) as u8
+    }
+}
+
+/// The source behind `EmulatorBuilder::rng_seed` and `Emulator::seed_rng`.
+pub(crate) fn seeded_rng(seed: u64) -> Box<dyn RngSource> {
+    #[cfg(feature = "rand")]
+    return Box::new(SmallRngSource::from_seed(seed));
+    #[cfg(not(feature = "rand"))]
+    Box::new(LcgRng::new(seed))
+}
+
+/// The source unseeded emulators start with.
+pub(crate) fn entropy_rng() -> Box<dyn RngSource> {
+    #[cfg(feature = "rand")]
+    return Box::new(SmallRngSource::from_entropy());
+    // the clock is the only entropy there is without rand
+    #[cfg(not(feature = "rand"))]
+    {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Box::new(LcgRng::new(nanos))
+    }
+}
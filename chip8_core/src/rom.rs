@@ -0,0 +1,192 @@
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Chip8Mode, ChipError, Emulator, Instruction, PROGRAM_SIZE, QuirksConfig, START_ADDR,
+    ScreenBuffer, lookup_rom,
+};
+
+/// Largest ROM that fits between the default start address (0x200) and the end of RAM.
+pub const MAX_ROM_SIZE: usize = PROGRAM_SIZE;
+
+/// Instruction set a ROM appears to target, based on the opcodes it contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Chip8,
+    SuperChip,
+    XoChip,
+    /// ETI-660 programs start at 0x600. Never detected, the opcodes are the same as CHIP-8.
+    Eti660,
+}
+
+impl Variant {
+    /// Where ROMs for this variant are loaded, see `EmulatorBuilder::start_address`.
+    pub fn start_address(&self) -> u16 {
+        match self {
+            Variant::Eti660 => 0x600,
+            Variant::Chip8 | Variant::SuperChip | Variant::XoChip => START_ADDR,
+        }
+    }
+
+    /// The mode that runs this variant. XO-CHIP's own opcodes are not
+    /// supported, but it builds on SUPER-CHIP.
+    pub fn mode(&self) -> Chip8Mode {
+        match self {
+            Variant::SuperChip | Variant::XoChip => Chip8Mode::SuperChip,
+            Variant::Chip8 | Variant::Eti660 => Chip8Mode::Standard,
+        }
+    }
+
+    /// The quirks preset for this variant's original interpreter.
+    pub fn quirks(&self) -> QuirksConfig {
+        match self {
+            Variant::Chip8 | Variant::Eti660 => QuirksConfig::cosmac_vip(),
+            Variant::SuperChip => QuirksConfig::superchip(),
+            Variant::XoChip => QuirksConfig::xochip(),
+        }
+    }
+
+    /// Scans every aligned 16-bit word for extension opcodes. Data bytes are
+    /// scanned too, so this can over-report on ROMs with embedded sprites.
+    pub fn detect(rom: &[u8]) -> Variant {
+        let mut variant = Variant::Chip8;
+        for word in rom.chunks_exact(2) {
+            let op = u16::from_be_bytes([word[0], word[1]]);
+            if is_xochip_opcode(op) {
+                return Variant::XoChip;
+            }
+            if is_superchip_opcode(op) {
+                variant = Variant::SuperChip;
+            }
+        }
+        variant
+    }
+
+    /// Whether the original interpreter for this variant runs `op`. CHIP-8X
+    /// isn't a variant, its opcodes are unsupported everywhere.
+    pub fn supports_opcode(&self, op: u16) -> bool {
+        let known = !matches!(Instruction::decode(op), Instruction::Unknown(_));
+        match self {
+            Variant::Chip8 | Variant::Eti660 => {
+                known && !is_superchip_opcode(op) && !is_xochip_opcode(op)
+            }
+            Variant::SuperChip => known && !is_xochip_opcode(op),
+            Variant::XoChip => known || is_xochip_opcode(op),
+        }
+    }
+}
+
+fn is_superchip_opcode(op: u16) -> bool {
+    op & 0xFFF0 == 0x00C0
+        || matches!(op, 0x00FB..=0x00FF)
+        || op & 0xF00F == 0xD000
+        || matches!(op & 0xF0FF, 0xF030 | 0xF075 | 0xF085)
+}
+
+fn is_xochip_opcode(op: u16) -> bool {
+    op & 0xFFF0 == 0x00D0
+        || matches!(op & 0xF00F, 0x5002 | 0x5003)
+        || matches!(op, 0xF000 | 0xF002)
+        || matches!(op & 0xF0FF, 0xF001 | 0xF03A)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomInfo {
+    pub size: usize,
+    pub sha256: [u8; 32],
+    pub detected_variant: Variant,
+    /// The catalog entry's quirks, otherwise the preset for `detected_variant`.
+    pub suggested_quirks: QuirksConfig,
+    /// Title from `ROM_CATALOG`, if the hash matched an entry.
+    pub known_title: Option<&'static str>,
+}
+
+impl RomInfo {
+    pub fn from_rom(data: &[u8]) -> RomInfo {
+        let sha256 = Sha256::digest(data).into();
+        let known = lookup_rom(&sha256);
+        let detected_variant = Variant::detect(data);
+        RomInfo {
+            size: data.len(),
+            sha256,
+            detected_variant,
+            suggested_quirks: known.map_or_else(|| detected_variant.quirks(), |entry| entry.quirks),
+            known_title: known.map(|entry| entry.title),
+        }
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Loads `data` like `load_rom` and reports what is known about the ROM.
+    pub fn load_rom_checked(&mut self, data: &[u8]) -> Result<RomInfo, ChipError> {
+        self.load_rom(data)?;
+        Ok(RomInfo::from_rom(data))
+    }
+
+    /// Same as `load_rom_checked`.
+    pub fn load_rom_from_bytes(&mut self, data: &[u8]) -> Result<RomInfo, ChipError> {
+        self.load_rom_checked(data)
+    }
+
+    /// Reads a ROM from disk and loads it with `load_rom_checked`.
+    #[cfg(feature = "std")]
+    pub fn load_rom_from_file(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<RomInfo, ChipError> {
+        let data = std::fs::read(path).map_err(|err| ChipError::RomReadFailed(err.kind()))?;
+        self.load_rom_checked(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggested_quirks_follow_the_detected_variant() {
+        // LD V0, 0x01
+        let info = RomInfo::from_rom(&[0x60, 0x01]);
+        assert_eq!(info.detected_variant, Variant::Chip8);
+        assert_eq!(info.suggested_quirks, QuirksConfig::cosmac_vip());
+
+        // HIGH; LD V0, 0x01
+        let info = RomInfo::from_rom(&[0x00, 0xFF, 0x60, 0x01]);
+        assert_eq!(info.known_title, None);
+        assert_eq!(info.detected_variant, Variant::SuperChip);
+        assert_eq!(info.suggested_quirks, QuirksConfig::superchip());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn loads_from_a_file() {
+        let dir = std::env::temp_dir();
+        let good = dir.join("chip8_core_load_rom_from_file.ch8");
+        let oversize = dir.join("chip8_core_load_rom_from_file_oversize.ch8");
+        std::fs::write(&good, crate::roms::COUNTER_ROM).unwrap();
+        std::fs::write(&oversize, vec![0; MAX_ROM_SIZE + 1]).unwrap();
+
+        let mut chip8 = Emulator::new();
+        let loaded = chip8.load_rom_from_file(&good);
+        let too_large = chip8.load_rom_from_file(&oversize);
+        std::fs::remove_file(&good).unwrap();
+        std::fs::remove_file(&oversize).unwrap();
+
+        let info = loaded.unwrap();
+        assert_eq!(info.size, crate::roms::COUNTER_ROM.len());
+        assert_eq!(info, RomInfo::from_rom(crate::roms::COUNTER_ROM));
+        assert_eq!(
+            too_large,
+            Err(ChipError::RomTooLarge {
+                size: MAX_ROM_SIZE + 1,
+                max: MAX_ROM_SIZE,
+            })
+        );
+        // the failed load left the first ROM in place
+        chip8.tick_n(4);
+        assert_eq!(chip8.get_registers()[0], 1);
+        assert_eq!(
+            chip8.load_rom_from_file(dir.join("chip8_core_no_such_rom.ch8")),
+            Err(ChipError::RomReadFailed(std::io::ErrorKind::NotFound))
+        );
+    }
+}
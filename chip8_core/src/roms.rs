@@ -0,0 +1,68 @@
+//! Small built-in programs for tests, benchmarks and examples.
+
+/// Counts up in V0 forever, copying it into DT each time round.
+///
+/// ```text
+/// 0x200: 6000  LD V0, 0x00
+/// 0x202: 7001  ADD V0, 0x01
+/// 0x204: F015  LD DT, V0
+/// 0x206: 1202  JP 0x202
+/// ```
+///
+/// Each iteration is three instructions, so after `n >= 1` cycles
+/// V0 is `((n + 1) / 3) % 256` and DT holds V0's value from the last `LD DT`.
+pub const COUNTER_ROM: &[u8] = &[0x60, 0x00, 0x70, 0x01, 0xF0, 0x15, 0x12, 0x02];
+
+/// Draws the font digit for V0 at the top left, then moves on to the next
+/// digit, cycling through 0-F.
+///
+/// ```text
+/// 0x200: 630F  LD V3, 0x0F
+/// 0x202: 00E0  CLS
+/// 0x204: F029  LD F, V0
+/// 0x206: D125  DRW V1, V2, 5
+/// 0x208: 7001  ADD V0, 0x01
+/// 0x20A: 8032  AND V0, V3
+/// 0x20C: 1202  JP 0x202
+/// ```
+///
+/// Each iteration is six instructions, so after `1 + 6 * k` cycles V0 is
+/// `k % 16` and the screen shows the glyph for `(k - 1) % 16` (for `k >= 1`).
+pub const DRAW_ROM: &[u8] = &[
+    0x63, 0x0F, 0x00, 0xE0, 0xF0, 0x29, 0xD1, 0x25, 0x70, 0x01, 0x80, 0x32, 0x12, 0x02,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Emulator, QuirksConfig};
+
+    #[test]
+    fn counter_rom_counts_once_per_iteration() {
+        let mut chip8 = Emulator::new_with_rom(COUNTER_ROM).unwrap();
+        for n in 1..=1000usize {
+            chip8.tick();
+            assert_eq!(chip8.get_registers()[0], ((n + 1) / 3 % 256) as u8, "{}", n);
+            assert_eq!(chip8.get_dt(), (n / 3 % 256) as u8, "{}", n);
+        }
+    }
+
+    #[test]
+    fn draw_rom_steps_through_the_digits() {
+        // no display wait, so every tick runs an instruction
+        let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+        chip8.load_rom(DRAW_ROM).unwrap();
+        chip8.tick();
+        for k in 1..=40usize {
+            chip8.tick_n(6);
+            assert_eq!(chip8.get_registers()[0], (k % 16) as u8, "{}", k);
+            // LD V0, digit; LD F, V0; DRW V1, V1, 5
+            let mut glyph = Emulator::new();
+            glyph
+                .load_rom(&[0x60, ((k - 1) % 16) as u8, 0xF0, 0x29, 0xD1, 0x15])
+                .unwrap();
+            glyph.tick_n(3);
+            assert!(chip8.get_display() == glyph.get_display(), "{}", k);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::screen_len;
+use crate::{ChipError, Emulator, KEY_COUNT, RAM_SIZE, REGISTER_COUNT, ScreenBuffer};
+
+/// File extension for save states written by `save_state_json`.
+pub const JSON_SAVE_STATE_EXTENSION: &str = "ch8state";
+
+/// The machine state as plain fields, for serializing with serde. Unlike
+/// the byte format of `save_state` it records which ROM was loaded, and
+/// `restore_save_state` refuses states taken with a different one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SaveState {
+    /// CRC-32 of the ROM as it was loaded, before it ran.
+    pub rom_crc32: u32,
+    pub pc: u16,
+    pub ram: Vec<u8>,
+    /// SUPER-CHIP 128x64 mode, `screen` is 128 pixels wide when set.
+    pub hires: bool,
+    /// Row-major like `get_display`.
+    pub screen: Vec<bool>,
+    pub v_reg: [u8; REGISTER_COUNT],
+    pub i_reg: u16,
+    pub stack: Vec<u16>,
+    pub sp: u16,
+    pub keys: [bool; KEY_COUNT],
+    pub dt: u8,
+    pub st: u8,
+    pub waiting_for_vblank: bool,
+    pub waiting_for_key_release: Option<u8>,
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    pub fn to_save_state(&self) -> SaveState {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        SaveState {
+            rom_crc32: self.rom_crc32,
+            pc: self.pc,
+            ram: self.ram.to_vec(),
+            hires: self.is_hires(),
+            screen: (0..width * height)
+                .map(|i| self.screen.get_pixel(i % width, i / width))
+                .collect(),
+            v_reg: self.v_reg,
+            i_reg: self.i_reg,
+            stack: self.stack.clone(),
+            sp: self.sp,
+            keys: self.keys,
+            dt: self.dt,
+            st: self.st,
+            waiting_for_vblank: self.waiting_for_vblank,
+            waiting_for_key_release: self.waiting_for_key_release,
+        }
+    }
+
+    /// Fails with `SaveStateRomMismatch` if `state` was taken with another
+    /// ROM loaded, and with `InvalidSaveState` if it doesn't fit this
+    /// emulator's RAM, stack or screen. Nothing is changed on failure.
+    pub fn restore_save_state(&mut self, state: &SaveState) -> Result<(), ChipError> {
+        if state.rom_crc32 != self.rom_crc32 {
+            return Err(ChipError::SaveStateRomMismatch {
+                expected: self.rom_crc32,
+                found: state.rom_crc32,
+            });
+        }
+        if state.ram.len() != RAM_SIZE
+            || state.stack.len() != self.stack.len()
+            || state.sp as usize > self.stack.len()
+        {
+            return Err(ChipError::InvalidSaveState);
+        }
+
+        if state.hires && !self.screen.supports_hires() {
+            // a high resolution state on a screen that only does 64x32
+            return Err(ChipError::InvalidSaveState);
+        }
+        if state.screen.len() != screen_len(state.hires) {
+            return Err(ChipError::InvalidSaveState);
+        }
+
+        self.screen.set_hires(state.hires);
+        let width = self.screen.width();
+        for (i, pixel) in state.screen.iter().enumerate() {
+            self.screen.set_pixel(i % width, i / width, *pixel);
+        }
+        self.screen_changed();
+
+        self.pc = state.pc;
+        self.ram.copy_from_slice(&state.ram);
+        self.v_reg = state.v_reg;
+        self.i_reg = state.i_reg;
+        self.stack.copy_from_slice(&state.stack);
+        self.sp = state.sp;
+        self.keys = state.keys;
+        self.dt = state.dt;
+        self.st = state.st;
+        self.waiting_for_vblank = state.waiting_for_vblank;
+        self.waiting_for_key_release = state.waiting_for_key_release;
+        self.waiting_key_register = None;
+        Ok(())
+    }
+
+    /// `to_save_state` as JSON.
+    #[cfg(feature = "json-saves")]
+    pub fn save_state_json(&self) -> Result<Vec<u8>, ChipError> {
+        serde_json::to_vec(&self.to_save_state()).map_err(|_| ChipError::InvalidSaveState)
+    }
+
+    /// Restores JSON written by `save_state_json`, see `restore_save_state`.
+    #[cfg(feature = "json-saves")]
+    pub fn load_state_json(&mut self, data: &[u8]) -> Result<(), ChipError> {
+        let state: SaveState =
+            serde_json::from_slice(data).map_err(|_| ChipError::InvalidSaveState)?;
+        self.restore_save_state(&state)
+    }
+}
+
+// CRC-32 as used by zip and PNG, bit by bit since it only runs once per ROM
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_pointer_past_the_stack_is_rejected() {
+        let mut chip8 = Emulator::new();
+        chip8.load_rom(&[0x12, 0x00]).unwrap();
+        let mut state = chip8.to_save_state();
+        state.sp = chip8.stack.len() as u16 + 1;
+        state.pc = 0x0300;
+        assert_eq!(
+            chip8.restore_save_state(&state),
+            Err(ChipError::InvalidSaveState)
+        );
+        assert_eq!(chip8.get_pc(), 0x0200);
+    }
+}
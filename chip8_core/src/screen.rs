@@ -0,0 +1,278 @@
+use crate::{HIRES_HEIGHT, HIRES_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Pixel storage used by the emulator's display.
+/// Coordinates are always in range, DXYN wraps and clips before calling in.
+pub trait ScreenBuffer {
+    fn set_pixel(&mut self, x: usize, y: usize, on: bool);
+
+    fn get_pixel(&self, x: usize, y: usize) -> bool;
+
+    /// XORs an 8-pixel sprite row into row `y` starting at column `x_offset`.
+    /// Pixels past the right edge are clipped. Returns how many lit pixels were turned off.
+    fn xor_row(&mut self, y: usize, data: u8, x_offset: usize) -> u8 {
+        let mut collisions = 0;
+        for bit in 0..8 {
+            let x = x_offset + bit;
+            if x >= self.width() {
+                break;
+            }
+            if data & (0b1000_0000 >> bit) != 0 {
+                let was_on = self.get_pixel(x, y);
+                collisions += was_on as u8;
+                self.set_pixel(x, y, !was_on);
+            }
+        }
+        collisions
+    }
+
+    fn clear(&mut self);
+
+    /// Flips every pixel.
+    fn invert(&mut self) {
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let on = self.get_pixel(x, y);
+                self.set_pixel(x, y, !on);
+            }
+        }
+    }
+
+    /// Switches between 64x32 and the SUPER-CHIP 128x64 resolution and clears
+    /// the screen. Buffers that only store 64x32 can keep the default, which
+    /// stays in low resolution.
+    fn set_hires(&mut self, hires: bool) {
+        let _ = hires;
+        self.clear();
+    }
+
+    /// Whether `set_hires(true)` switches to 128x64. Must be overridden
+    /// together with `set_hires`.
+    fn supports_hires(&self) -> bool {
+        false
+    }
+
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+}
+
+/// Result of `Emulator::validate_draw_coords`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoordInfo {
+    pub x_wrapped: bool,
+    pub y_wrapped: bool,
+    pub effective_x: u8,
+    pub effective_y: u8,
+}
+
+/// One `bool` per pixel in row-major order, the layout returned by `get_display`.
+/// Room is kept for 128x64, rows are `width()` pixels apart in either resolution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoolArrayScreen {
+    pixels: [bool; HIRES_WIDTH * HIRES_HEIGHT],
+    hires: bool,
+}
+
+impl BoolArrayScreen {
+    /// The pixels of the active resolution.
+    pub fn pixels(&self) -> &[bool] {
+        &self.pixels[..self.width() * self.height()]
+    }
+}
+
+impl Default for BoolArrayScreen {
+    fn default() -> Self {
+        BoolArrayScreen {
+            pixels: [false; HIRES_WIDTH * HIRES_HEIGHT],
+            hires: false,
+        }
+    }
+}
+
+impl ScreenBuffer for BoolArrayScreen {
+    fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let width = self.width();
+        self.pixels[x + width * y] = on;
+    }
+
+    fn get_pixel(&self, x: usize, y: usize) -> bool {
+        self.pixels[x + self.width() * y]
+    }
+
+    fn clear(&mut self) {
+        self.pixels.fill(false);
+    }
+
+    fn invert(&mut self) {
+        let len = self.width() * self.height();
+        self.pixels[..len]
+            .iter_mut()
+            .for_each(|pixel| *pixel = !*pixel);
+    }
+
+    fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.clear();
+    }
+
+    fn supports_hires(&self) -> bool {
+        true
+    }
+
+    fn width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            SCREEN_WIDTH
+        }
+    }
+
+    fn height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            SCREEN_HEIGHT
+        }
+    }
+}
+
+/// One `u128` per row, the most significant bit is the leftmost pixel.
+/// A sprite row is drawn with a single shift and XOR. In low resolution only
+/// the top 64 bits of the first 32 rows are used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedBitScreen {
+    rows: [u128; HIRES_HEIGHT],
+    hires: bool,
+}
+
+impl PackedBitScreen {
+    // the bits of a row that are on screen
+    fn row_mask(&self) -> u128 {
+        if self.hires { u128::MAX } else { !0u128 << 64 }
+    }
+}
+
+impl Default for PackedBitScreen {
+    fn default() -> Self {
+        PackedBitScreen {
+            rows: [0; HIRES_HEIGHT],
+            hires: false,
+        }
+    }
+}
+
+impl ScreenBuffer for PackedBitScreen {
+    fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let mask = 1u128 << (127 - x);
+        if on {
+            self.rows[y] |= mask;
+        } else {
+            self.rows[y] &= !mask;
+        }
+    }
+
+    fn get_pixel(&self, x: usize, y: usize) -> bool {
+        self.rows[y] & (1u128 << (127 - x)) != 0
+    }
+
+    fn xor_row(&mut self, y: usize, data: u8, x_offset: usize) -> u8 {
+        // shifting right and masking drops any bits that fall off the right edge
+        let mask = (((data as u128) << 120) >> x_offset) & self.row_mask();
+        let collisions = (self.rows[y] & mask).count_ones() as u8;
+        self.rows[y] ^= mask;
+        collisions
+    }
+
+    fn clear(&mut self) {
+        self.rows.fill(0);
+    }
+
+    fn invert(&mut self) {
+        let (mask, height) = (self.row_mask(), self.height());
+        self.rows[..height].iter_mut().for_each(|row| *row ^= mask);
+    }
+
+    fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.clear();
+    }
+
+    fn supports_hires(&self) -> bool {
+        true
+    }
+
+    fn width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            SCREEN_WIDTH
+        }
+    }
+
+    fn height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            SCREEN_HEIGHT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(screen: &impl ScreenBuffer, y: usize) -> Vec<usize> {
+        (0..screen.width())
+            .filter(|x| screen.get_pixel(*x, y))
+            .collect()
+    }
+
+    fn xor_collides_and_clips(mut screen: impl ScreenBuffer) {
+        assert_eq!(screen.xor_row(3, 0b1111_0000, 10), 0);
+        assert_eq!(lit(&screen, 3), [10, 11, 12, 13]);
+
+        // two of the four overlap
+        assert_eq!(screen.xor_row(3, 0b0011_1100, 10), 2);
+        assert_eq!(lit(&screen, 3), [10, 11, 14, 15]);
+
+        // only the first two bits fit before the right edge
+        assert_eq!(screen.xor_row(5, 0b1111_1111, 62), 0);
+        assert_eq!(lit(&screen, 5), [62, 63]);
+        assert_eq!(screen.xor_row(5, 0b1100_0000, 62), 2);
+        assert!(lit(&screen, 5).is_empty());
+        assert!(lit(&screen, 6).is_empty());
+    }
+
+    fn double_invert_restores(mut screen: impl ScreenBuffer) {
+        screen.set_pixel(1, 2, true);
+        screen.invert();
+        assert!(screen.get_pixel(0, 0));
+        assert!(!screen.get_pixel(1, 2));
+        assert!(screen.get_pixel(63, 31));
+
+        screen.invert();
+        for y in 0..screen.height() {
+            let expected: &[usize] = if y == 2 { &[1] } else { &[] };
+            assert_eq!(lit(&screen, y), expected, "row {}", y);
+        }
+    }
+
+    #[test]
+    fn xor_row_counts_collisions_and_clips_at_the_right_edge() {
+        xor_collides_and_clips(BoolArrayScreen::default());
+        xor_collides_and_clips(PackedBitScreen::default());
+    }
+
+    #[test]
+    fn inverting_twice_restores_the_screen() {
+        double_invert_restores(BoolArrayScreen::default());
+        double_invert_restores(PackedBitScreen::default());
+
+        // high resolution inverts the whole 128x64
+        let mut screen = PackedBitScreen::default();
+        screen.set_hires(true);
+        screen.invert();
+        assert!(screen.get_pixel(127, 63));
+    }
+}
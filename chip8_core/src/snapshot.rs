@@ -0,0 +1,139 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{ChipError, CowRam, Emulator, REGISTER_COUNT, ScreenBuffer};
+
+/// CPU state plus the RAM and screen bytes that differ from the last `checkpoint()`.
+/// Only valid for the emulator and checkpoint it was taken from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmulatorSnapshot {
+    pub base_hash: u64,
+    pub pc: u16,
+    pub v_reg: [u8; REGISTER_COUNT],
+    pub i_reg: u16,
+    pub sp: u16,
+    pub dt: u8,
+    pub st: u8,
+    pub stack: Vec<u16>,
+    /// SUPER-CHIP 128x64 mode. When it differs from the checkpoint,
+    /// `screen_diff` holds every lit pixel instead of the changes.
+    pub hires: bool,
+    pub screen_diff: Vec<(usize, bool)>,
+    pub ram_diff: Vec<(usize, u8)>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Checkpoint {
+    hash: u64,
+    ram: CowRam,
+    screen: Vec<bool>,
+    hires: bool,
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Records the current RAM and screen as the baseline for `take_snapshot`.
+    pub fn checkpoint(&mut self) {
+        let screen = self.screen_pixels();
+        let mut hasher = DefaultHasher::new();
+        self.ram.hash(&mut hasher);
+        screen.hash(&mut hasher);
+
+        self.checkpoint = Some(Checkpoint {
+            hash: hasher.finish(),
+            ram: self.ram.clone(),
+            screen,
+            hires: self.is_hires(),
+        });
+    }
+
+    /// Captures the current state relative to the last checkpoint, taking one first if needed.
+    pub fn take_snapshot(&mut self) -> EmulatorSnapshot {
+        if self.checkpoint.is_none() {
+            self.checkpoint();
+        }
+        let base = self.checkpoint.as_ref().expect("checkpoint was just taken");
+
+        // still sharing the checkpoint's copy means nothing was written since
+        let ram_diff = if self.ram.shares_with(&base.ram) {
+            Vec::new()
+        } else {
+            self.ram
+                .iter()
+                .zip(base.ram.iter())
+                .enumerate()
+                .filter(|(_, (now, then))| now != then)
+                .map(|(i, (now, _))| (i, *now))
+                .collect()
+        };
+        let hires = self.is_hires();
+        let screen_diff = self
+            .screen_pixels()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, now)| {
+                if base.hires == hires {
+                    *now != base.screen[*i]
+                } else {
+                    *now
+                }
+            })
+            .collect();
+
+        EmulatorSnapshot {
+            base_hash: base.hash,
+            pc: self.pc,
+            v_reg: self.v_reg,
+            i_reg: self.i_reg,
+            sp: self.sp,
+            dt: self.dt,
+            st: self.st,
+            stack: self.stack.clone(),
+            hires,
+            screen_diff,
+            ram_diff,
+        }
+    }
+
+    /// Fails with `InvalidSaveState` if `snap` was not taken against the current checkpoint.
+    pub fn restore_snapshot(&mut self, snap: &EmulatorSnapshot) -> Result<(), ChipError> {
+        let Some(base) = self.checkpoint.as_ref() else {
+            return Err(ChipError::InvalidSaveState);
+        };
+        if base.hash != snap.base_hash || snap.stack.len() != self.stack.len() {
+            return Err(ChipError::InvalidSaveState);
+        }
+
+        self.ram = base.ram.clone();
+        for (addr, byte) in &snap.ram_diff {
+            self.ram[*addr] = *byte;
+        }
+        // switching resolution clears the screen, so the diff is all there is
+        self.screen.set_hires(snap.hires);
+        let width = self.screen.width();
+        if base.hires == snap.hires {
+            for (i, pixel) in base.screen.iter().enumerate() {
+                self.screen.set_pixel(i % width, i / width, *pixel);
+            }
+        }
+        for (i, pixel) in &snap.screen_diff {
+            self.screen.set_pixel(i % width, i / width, *pixel);
+        }
+        self.screen_changed();
+
+        self.pc = snap.pc;
+        self.v_reg = snap.v_reg;
+        self.i_reg = snap.i_reg;
+        self.sp = snap.sp;
+        self.dt = snap.dt;
+        self.st = snap.st;
+        self.stack.copy_from_slice(&snap.stack);
+        Ok(())
+    }
+
+    fn screen_pixels(&self) -> Vec<bool> {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        (0..width * height)
+            .map(|i| self.screen.get_pixel(i % width, i / width))
+            .collect()
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{
+    ChipError, Emulator, HIRES_HEIGHT, HIRES_WIDTH, RAM_SIZE, REGISTER_COUNT, SCREEN_HEIGHT,
+    SCREEN_WIDTH, ScreenBuffer,
+};
+
+/// File extension for save states written by `save_state_to_file`.
+pub const SAVE_STATE_EXTENSION: &str = "c8s";
+/// File extension for save states written by `save_state_to_file_compressed`.
+pub const COMPRESSED_SAVE_STATE_EXTENSION: &str = "c8z";
+
+// first four bytes of every LZ4 frame. A raw state can't start with them,
+// they would decode as a PC of 0x2204, outside RAM
+#[cfg(feature = "std")]
+const LZ4_FRAME_MAGIC: [u8; 4] = [0x04, 0x22, 0x4D, 0x18];
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Serializes the machine state (CPU, RAM, screen and timers) to bytes.
+    /// Host-side configuration such as quirks, hooks and MMIO is not included,
+    /// and neither are the mode or the RPL flags. The screen is stored at the
+    /// active resolution, which is told apart on load by the length.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.state_len(self.is_hires()));
+        buf.extend_from_slice(&self.pc.to_le_bytes());
+        buf.extend_from_slice(&self.ram[..]);
+        for y in 0..self.screen.height() {
+            for x in 0..self.screen.width() {
+                buf.push(self.screen.get_pixel(x, y) as u8);
+            }
+        }
+        buf.extend_from_slice(&self.v_reg);
+        buf.extend_from_slice(&self.i_reg.to_le_bytes());
+        for addr in &self.stack {
+            buf.extend_from_slice(&addr.to_le_bytes());
+        }
+        buf.extend_from_slice(&self.sp.to_le_bytes());
+        buf.push(self.dt);
+        buf.push(self.st);
+        // stored inverted, this byte used to be the old draw_completed flag
+        buf.push(!self.waiting_for_vblank as u8);
+        // 0xFF marks "not waiting", any other value is the key index.
+        buf.push(self.waiting_for_key_release.unwrap_or(0xFF));
+        buf
+    }
+
+    /// Restores a state produced by `save_state` on an emulator with the same stack size.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), ChipError> {
+        let hires = if data.len() == self.state_len(false) {
+            false
+        } else if data.len() == self.state_len(true) {
+            true
+        } else {
+            return Err(ChipError::InvalidSaveState);
+        };
+        if hires && !self.screen.supports_hires() {
+            // a high resolution state on a screen that only does 64x32
+            return Err(ChipError::InvalidSaveState);
+        }
+        // the stack pointer sits just before the timers and the two flag bytes
+        let sp_at = data.len() - 6;
+        let sp = u16::from_le_bytes([data[sp_at], data[sp_at + 1]]);
+        if sp as usize > self.stack.len() {
+            return Err(ChipError::InvalidSaveState);
+        }
+
+        let mut reader = Reader { data, pos: 0 };
+
+        self.pc = reader.u16();
+        self.ram.copy_from_slice(reader.bytes(RAM_SIZE));
+        self.screen.set_hires(hires);
+        let (width, height) = (self.screen.width(), self.screen.height());
+        for (i, byte) in reader.bytes(width * height).iter().enumerate() {
+            self.screen.set_pixel(i % width, i / width, *byte != 0);
+        }
+        self.screen_changed();
+        self.v_reg.copy_from_slice(reader.bytes(REGISTER_COUNT));
+        self.i_reg = reader.u16();
+        for slot in self.stack.iter_mut() {
+            *slot = reader.u16();
+        }
+        self.sp = reader.u16();
+        self.dt = reader.u8();
+        self.st = reader.u8();
+        self.waiting_for_vblank = reader.u8() == 0;
+        self.waiting_for_key_release = match reader.u8() {
+            0xFF => None,
+            k => Some(k),
+        };
+        self.waiting_key_register = None;
+        Ok(())
+    }
+
+    /// Writes `save_state` to `path` as is.
+    #[cfg(feature = "std")]
+    pub fn save_state_to_file(&self, path: impl AsRef<std::path::Path>) -> Result<(), ChipError> {
+        std::fs::write(path, self.save_state())
+            .map_err(|err| ChipError::SaveStateFileFailed(err.kind()))
+    }
+
+    /// Writes `save_state` to `path` as an LZ4 frame. Mostly empty RAM and
+    /// screen compress well, a small ROM's state shrinks by over 90%.
+    #[cfg(feature = "compressed-saves")]
+    pub fn save_state_to_file_compressed(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), ChipError> {
+        use std::io::Write;
+
+        let to_err = |err: std::io::Error| ChipError::SaveStateFileFailed(err.kind());
+        let file = std::fs::File::create(path).map_err(to_err)?;
+        let mut encoder = lz4_flex::frame::FrameEncoder::new(file);
+        encoder.write_all(&self.save_state()).map_err(to_err)?;
+        encoder
+            .finish()
+            .map_err(|_| ChipError::SaveStateFileFailed(std::io::ErrorKind::Other))?;
+        Ok(())
+    }
+
+    /// Loads a file written by either `save_state_to_file` or
+    /// `save_state_to_file_compressed`, telling them apart by the LZ4 magic
+    /// number rather than the extension. Compressed files need the
+    /// "compressed-saves" feature and are rejected as invalid without it.
+    #[cfg(feature = "std")]
+    pub fn load_state_from_file(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), ChipError> {
+        let data = std::fs::read(path).map_err(|err| ChipError::SaveStateFileFailed(err.kind()))?;
+        if !data.starts_with(&LZ4_FRAME_MAGIC) {
+            return self.load_state(&data);
+        }
+
+        #[cfg(feature = "compressed-saves")]
+        {
+            use std::io::Read;
+
+            let mut state = Vec::with_capacity(self.state_len(self.is_hires()));
+            lz4_flex::frame::FrameDecoder::new(&data[..])
+                .read_to_end(&mut state)
+                .map_err(|_| ChipError::InvalidSaveState)?;
+            self.load_state(&state)
+        }
+        #[cfg(not(feature = "compressed-saves"))]
+        Err(ChipError::InvalidSaveState)
+    }
+
+    fn state_len(&self, hires: bool) -> usize {
+        2 + RAM_SIZE + screen_len(hires) + REGISTER_COUNT + 2 + 2 * self.stack.len() + 2 + 4
+    }
+}
+
+pub(crate) fn screen_len(hires: bool) -> usize {
+    if hires {
+        HIRES_WIDTH * HIRES_HEIGHT
+    } else {
+        SCREEN_WIDTH * SCREEN_HEIGHT
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> &'a [u8] {
+        let bytes = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        bytes
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.bytes(1)[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        let bytes = self.bytes(2);
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrong_length_leaves_state_untouched() {
+        let mut chip8 = Emulator::new();
+        chip8.load_rom(&[0x12, 0x00]).unwrap();
+        let before = chip8.save_state();
+        let mut state = before.clone();
+        state[0] = 0x34;
+        state.pop();
+        assert_eq!(chip8.load_state(&state), Err(ChipError::InvalidSaveState));
+        assert_eq!(chip8.save_state(), before);
+    }
+
+    #[test]
+    fn hires_state_on_lores_screen_leaves_state_untouched() {
+        let mut hires = Emulator::new();
+        hires.screen.set_hires(true);
+        let state = hires.save_state();
+
+        let mut chip8: Emulator<LoresScreen> = Emulator::with_screen(LoresScreen::default());
+        chip8.load_rom(&[0x12, 0x00]).unwrap();
+        chip8.screen.set_pixel(3, 4, true);
+        let before = chip8.save_state();
+        assert_eq!(chip8.load_state(&state), Err(ChipError::InvalidSaveState));
+        assert_eq!(chip8.save_state(), before);
+    }
+
+    #[test]
+    fn stack_pointer_past_the_stack_is_rejected() {
+        let mut chip8 = Emulator::new();
+        chip8.load_rom(&[0x12, 0x00]).unwrap();
+        let before = chip8.save_state();
+        let mut state = before.clone();
+        let sp_at = state.len() - 6;
+        state[sp_at..sp_at + 2].copy_from_slice(&(chip8.stack.len() as u16 + 1).to_le_bytes());
+        assert_eq!(chip8.load_state(&state), Err(ChipError::InvalidSaveState));
+        assert_eq!(chip8.save_state(), before);
+
+        // a full stack is fine
+        state[sp_at..sp_at + 2].copy_from_slice(&(chip8.stack.len() as u16).to_le_bytes());
+        assert_eq!(chip8.load_state(&state), Ok(()));
+        assert_eq!(chip8.get_sp() as usize, chip8.stack.len());
+    }
+
+    #[cfg(feature = "compressed-saves")]
+    #[test]
+    fn compressed_save_round_trips() {
+        let mut chip8 = Emulator::new();
+        chip8.load_rom(crate::roms::DRAW_ROM).unwrap();
+        chip8.tick_n(20);
+        chip8.tick_timers();
+        chip8.tick_n(20);
+
+        let dir = std::env::temp_dir();
+        let raw_path = dir.join("chip8_core_round_trip.c8s");
+        let compressed_path = dir.join("chip8_core_round_trip.c8z");
+        chip8.save_state_to_file(&raw_path).unwrap();
+        chip8
+            .save_state_to_file_compressed(&compressed_path)
+            .unwrap();
+        let raw_len = std::fs::metadata(&raw_path).unwrap().len();
+        let compressed_len = std::fs::metadata(&compressed_path).unwrap().len();
+        assert!(
+            compressed_len * 10 < raw_len,
+            "{} of {} bytes",
+            compressed_len,
+            raw_len
+        );
+
+        let mut restored = Emulator::new();
+        let loaded = restored.load_state_from_file(&compressed_path);
+        std::fs::remove_file(&raw_path).unwrap();
+        std::fs::remove_file(&compressed_path).unwrap();
+        loaded.unwrap();
+        assert_eq!(restored.save_state(), chip8.save_state());
+        assert_eq!(restored.get_pc(), chip8.get_pc());
+        assert_eq!(restored.get_registers(), chip8.get_registers());
+        assert_eq!(restored.get_display(), chip8.get_display());
+
+        // both run on identically from here
+        chip8.tick_n(50);
+        restored.tick_n(50);
+        assert_eq!(restored.save_state(), chip8.save_state());
+    }
+
+    // a screen that keeps the default, low resolution only `set_hires`
+    struct LoresScreen([bool; SCREEN_WIDTH * SCREEN_HEIGHT]);
+
+    impl Default for LoresScreen {
+        fn default() -> Self {
+            LoresScreen([false; SCREEN_WIDTH * SCREEN_HEIGHT])
+        }
+    }
+
+    impl ScreenBuffer for LoresScreen {
+        fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+            self.0[y * SCREEN_WIDTH + x] = on;
+        }
+
+        fn get_pixel(&self, x: usize, y: usize) -> bool {
+            self.0[y * SCREEN_WIDTH + x]
+        }
+
+        fn clear(&mut self) {
+            self.0 = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        }
+
+        fn width(&self) -> usize {
+            SCREEN_WIDTH
+        }
+
+        fn height(&self) -> usize {
+            SCREEN_HEIGHT
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{ChipError, Emulator, Instruction, ScreenBuffer, TickResult};
+
+/// What a single `step()` did, for debuggers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepInfo {
+    pub pc_before: u16,
+    /// The opcode at `pc_before`, whether or not it ran.
+    pub opcode: u16,
+    /// The instruction that ran, `None` when the tick ran nothing because
+    /// the emulator was halted, waiting for a key release or vblank, or
+    /// stopped at a breakpoint.
+    pub instruction: Option<Instruction>,
+    /// The instruction's `Display` text, e.g. `LD V3, 0x42`. `None` whenever
+    /// `instruction` is.
+    pub mnemonic: Option<String>,
+    pub pc_after: u16,
+    pub registers_before: [u8; 16],
+    pub registers_after: [u8; 16],
+    /// `(index, old, new)` for each V register the step changed.
+    pub registers_changed: Vec<(usize, u8, u8)>,
+    /// VF changed.
+    pub flags_changed: bool,
+    pub draw_occurred: bool,
+    /// A DXYN is waiting for the next frame interrupt, see `QuirksConfig::display_wait`.
+    pub waiting_for_vblank: bool,
+    /// FX0A got this key and is waiting for it to be released.
+    pub waiting_for_key_release: Option<u8>,
+    /// Never `TickResult::Error`, `step()` returns those as `Err`.
+    pub result: TickResult,
+}
+
+/// Same type as `StepInfo`.
+pub type StepResult = StepInfo;
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Runs one `tick()` and reports what changed. Copies the registers
+    /// before and after, so only use this when single-stepping.
+    pub fn step(&mut self) -> Result<StepInfo, ChipError> {
+        let pc_before = self.pc;
+        let opcode = self.peek_opcode(pc_before);
+        let registers_before = self.v_reg;
+        let cycles_before = self.elapsed_cycles;
+
+        let result = self.tick();
+        if let TickResult::Error(err) = result {
+            return Err(err);
+        }
+
+        // only an executed instruction adds to the cycle count
+        let instruction =
+            (self.elapsed_cycles != cycles_before).then(|| self.decode_opcode(opcode));
+        let registers_changed = registers_before
+            .iter()
+            .zip(self.v_reg.iter())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(idx, (old, new))| (idx, *old, *new))
+            .collect();
+        Ok(StepInfo {
+            pc_before,
+            opcode,
+            instruction,
+            mnemonic: instruction.map(|instruction| instruction.to_string()),
+            pc_after: self.pc,
+            registers_before,
+            registers_after: self.v_reg,
+            registers_changed,
+            flags_changed: registers_before[0xF] != self.v_reg[0xF],
+            draw_occurred: result == TickResult::Draw,
+            waiting_for_vblank: self.waiting_for_vblank,
+            waiting_for_key_release: self.waiting_for_key_release,
+            result,
+        })
+    }
+
+    // reads the opcode at `addr` without going through mmio or the access log
+    fn peek_opcode(&self, addr: u16) -> u16 {
+        let addr = addr as usize;
+        match self.ram.get(addr..addr + 2) {
+            Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]),
+            None => 0,
+        }
+    }
+}
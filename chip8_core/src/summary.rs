@@ -0,0 +1,99 @@
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{Emulator, REGISTER_COUNT, ScreenBuffer};
+
+/// Running totals behind `execution_summary`, cleared by `reset`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunCounters {
+    pub(crate) ticks: u64,
+    pub(crate) timer_ticks: u64,
+    pub(crate) key_events: u64,
+    pub(crate) draw_calls: u64,
+    #[cfg(feature = "std")]
+    pub(crate) started_at: Option<Instant>,
+}
+
+/// Where a run ended up, for headless runs and comparing emulator versions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ExecutionSummary {
+    /// Every `tick()` call, including ones that stopped at a key wait or breakpoint.
+    pub total_ticks: u64,
+    /// Instructions actually executed.
+    pub total_cycles: u64,
+    /// Seconds since the first tick. Always 0 without the "std" feature.
+    pub wall_clock_secs: f64,
+    pub cycles_per_second: f64,
+    /// 60 Hz timer decrements.
+    pub timer_ticks: u64,
+    pub key_events: u64,
+    /// Instructions that changed the screen (DXYN and CLS).
+    pub draw_calls: u64,
+    pub final_pc: u16,
+    pub registers: [u8; REGISTER_COUNT],
+    pub i_reg: u16,
+    /// The final screen as rows of `#` and `.`.
+    pub screen: String,
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    pub fn execution_summary(&self) -> ExecutionSummary {
+        #[cfg(feature = "std")]
+        let wall_clock_secs = self
+            .counters
+            .started_at
+            .map_or(0.0, |start| start.elapsed().as_secs_f64());
+        #[cfg(not(feature = "std"))]
+        let wall_clock_secs = 0.0;
+
+        ExecutionSummary {
+            total_ticks: self.counters.ticks,
+            total_cycles: self.elapsed_cycles,
+            wall_clock_secs,
+            cycles_per_second: if wall_clock_secs > 0.0 {
+                self.elapsed_cycles as f64 / wall_clock_secs
+            } else {
+                0.0
+            },
+            timer_ticks: self.counters.timer_ticks,
+            key_events: self.counters.key_events,
+            draw_calls: self.counters.draw_calls,
+            final_pc: self.pc,
+            registers: self.v_reg,
+            i_reg: self.i_reg,
+            screen: self.screen_to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roms;
+
+    #[test]
+    fn total_ticks_counts_every_tick_call() {
+        let mut chip8 = Emulator::new_with_rom(roms::COUNTER_ROM).unwrap();
+        for _ in 0..25 {
+            chip8.tick();
+        }
+        let summary = chip8.execution_summary();
+        assert_eq!(summary.total_ticks, 25);
+        assert_eq!(summary.total_cycles, chip8.get_total_ticks());
+    }
+
+    #[test]
+    fn ticks_stopped_at_a_breakpoint_still_count() {
+        let mut chip8 = Emulator::new_with_rom(roms::COUNTER_ROM).unwrap();
+        chip8.add_breakpoint(0x200);
+        chip8.tick();
+        chip8.tick();
+        let summary = chip8.execution_summary();
+        assert_eq!(summary.total_ticks, 2);
+        assert_eq!(summary.total_cycles, 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! SUPER-CHIP 1.1: scrolling, a 128x64 high resolution mode, 16x16 sprites,
+//! the large font and the RPL user flags. Enabled with `Chip8Mode::SuperChip`.
+
+use crate::{ChipError, Emulator, HIRES_WIDTH, LARGE_FONT_START, ScreenBuffer, TickResult};
+
+/// How many RPL user flags FX75/FX85 can save and load.
+pub const RPL_FLAG_COUNT: usize = 8;
+
+/// Instruction set the emulator runs. SUPER-CHIP opcodes are unknown in
+/// `Standard` mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Chip8Mode {
+    #[default]
+    Standard,
+    SuperChip,
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    pub fn get_mode(&self) -> Chip8Mode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: Chip8Mode) {
+        self.mode = mode;
+    }
+
+    /// True while the screen is in the SUPER-CHIP 128x64 resolution.
+    pub fn is_hires(&self) -> bool {
+        self.screen.width() == HIRES_WIDTH
+    }
+
+    /// The flags saved by FX75. They survive `reset()`, like the HP-48
+    /// calculator registers they are named after.
+    pub fn get_rpl_flags(&self) -> &[u8; RPL_FLAG_COUNT] {
+        &self.rpl_flags
+    }
+
+    pub fn set_rpl_flags(&mut self, flags: [u8; RPL_FLAG_COUNT]) {
+        self.rpl_flags = flags;
+    }
+
+    /// Writes the RPL flags to `path`, 8 bytes with nothing else.
+    #[cfg(feature = "std")]
+    pub fn save_rpl_flags_to_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), ChipError> {
+        std::fs::write(path, self.rpl_flags)
+            .map_err(|err| ChipError::RplFlagsFileFailed(err.kind()))
+    }
+
+    /// Reads flags written by `save_rpl_flags_to_file`. Shorter files only
+    /// set the first flags, longer ones are cut off.
+    #[cfg(feature = "std")]
+    pub fn load_rpl_flags_from_file(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), ChipError> {
+        let data = std::fs::read(path).map_err(|err| ChipError::RplFlagsFileFailed(err.kind()))?;
+        let len = data.len().min(RPL_FLAG_COUNT);
+        self.rpl_flags[..len].copy_from_slice(&data[..len]);
+        Ok(())
+    }
+
+    // 00CN, 00DN, 00FB, 00FC, 00FD, 00FE and 00FF
+    pub(crate) fn op_superchip_sys(&mut self, op: u16) -> Result<TickResult, ChipError> {
+        match op {
+            // scroll down N rows
+            0x00C0..=0x00CF => self.scroll(0, (op & 0x000F) as isize),
+            // scroll up N rows, SUPER-CHIP 1.0 put this in 00CN as well but
+            // 1.1 took that for scrolling down, so it uses the XO-CHIP 00DN
+            0x00D0..=0x00DF => self.scroll(0, -((op & 0x000F) as isize)),
+            // scroll right or left 4 pixels
+            0x00FB => self.scroll(4, 0),
+            0x00FC => self.scroll(-4, 0),
+            // EXIT - stop the interpreter
+            0x00FD => {
+                self.halted = true;
+                return Ok(TickResult::Halted);
+            }
+            // LOW / HIGH - switch resolution, which clears the screen
+            0x00FE | 0x00FF => {
+                self.screen.set_hires(op == 0x00FF);
+                self.screen_changed();
+            }
+            _ => return Err(ChipError::UnknownOpcode(op)),
+        }
+        Ok(TickResult::Draw)
+    }
+
+    // moves every pixel by dx, dy, pixels scrolled in are off
+    fn scroll(&mut self, dx: isize, dy: isize) {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        // rows are overwritten in place, so read ahead of the direction of travel
+        let rows: Vec<usize> = if dy > 0 {
+            (0..height).rev().collect()
+        } else {
+            (0..height).collect()
+        };
+        for y in rows {
+            let from_y = y as isize - dy;
+            let row: Vec<bool> = (0..width)
+                .map(|x| {
+                    let from_x = x as isize - dx;
+                    (0..height as isize).contains(&from_y)
+                        && (0..width as isize).contains(&from_x)
+                        && self.screen.get_pixel(from_x as usize, from_y as usize)
+                })
+                .collect();
+            for (x, on) in row.into_iter().enumerate() {
+                self.screen.set_pixel(x, y, on);
+            }
+        }
+        self.screen_changed();
+    }
+
+    // DXY0 - 16x16 sprite, two bytes per row, clipped at the edges.
+    // Returns how many pixels were turned off.
+    pub(crate) fn draw_large_sprite(
+        &mut self,
+        x_coord: usize,
+        y_coord: usize,
+    ) -> Result<u8, ChipError> {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        let mut collisions: u8 = 0;
+        for y_line in 0..16 {
+            let y = y_coord + y_line;
+            if y >= height {
+                break;
+            }
+            let addr = self.i_reg.wrapping_add(2 * y_line as u16);
+            for half in 0..2 {
+                let x = x_coord + 8 * half;
+                if x >= width {
+                    break;
+                }
+                let pixels = self.read_ram(addr.wrapping_add(half as u16))?;
+                collisions = collisions.saturating_add(self.screen.xor_row(y, pixels, x));
+                #[cfg(feature = "profiling")]
+                self.record_pixel_flips(y, pixels, x);
+            }
+        }
+        Ok(collisions)
+    }
+
+    // FX30, FX75 and FX85. Returns None for opcodes outside SUPER-CHIP.
+    pub(crate) fn op_superchip_misc(&mut self, op: u16) -> Option<TickResult> {
+        let x = ((op & 0x0F00) >> 8) as usize;
+        match op & 0x00FF {
+            // I = large font glyph for the low nibble of VX
+            0x30 => {
+                self.i_reg = LARGE_FONT_START + (self.v_reg[x] & 0xF) as u16 * 10;
+            }
+            // save V0..VX to the RPL flags, X above 7 stops at V7
+            0x75 => {
+                let count = (x + 1).min(RPL_FLAG_COUNT);
+                self.rpl_flags[..count].copy_from_slice(&self.v_reg[..count]);
+            }
+            // load V0..VX from the RPL flags
+            0x85 => {
+                let count = (x + 1).min(RPL_FLAG_COUNT);
+                self.v_reg[..count].copy_from_slice(&self.rpl_flags[..count]);
+            }
+            _ => return None,
+        }
+        Some(TickResult::Ran)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_up_moves_every_row_up_one() {
+        let mut chip8 = Emulator::new();
+        chip8.set_mode(Chip8Mode::SuperChip);
+        let (width, height) = (chip8.screen.width(), chip8.screen.height());
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                chip8.screen.set_pixel(x, y, true);
+            }
+        }
+
+        assert_eq!(chip8.op_superchip_sys(0x00D1), Ok(TickResult::Draw));
+        for y in 0..height {
+            // the even rows were lit, one row up they are the odd ones. The
+            // bottom row is scrolled in blank
+            let lit = y % 2 == 1 && y != height - 1;
+            for x in 0..width {
+                assert_eq!(chip8.screen.get_pixel(x, y), lit, "pixel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn large_sprite_past_end_of_ram_is_an_error() {
+        let mut chip8 = Emulator::new();
+        chip8.set_mode(Chip8Mode::SuperChip);
+        chip8.i_reg = 0x0FF0;
+        assert_eq!(
+            chip8.draw_large_sprite(0, 0),
+            Err(ChipError::InvalidMemoryAccess(0x1000))
+        );
+    }
+
+    #[test]
+    fn large_sprite_at_top_of_address_space_does_not_overflow() {
+        let mut chip8 = Emulator::new();
+        chip8.set_mode(Chip8Mode::SuperChip);
+        chip8.i_reg = 0xFFFE;
+        assert_eq!(
+            chip8.draw_large_sprite(0, 0),
+            Err(ChipError::InvalidMemoryAccess(0xFFFE))
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Helpers for writing opcode tests, enabled by the "test-utils" feature.
+//!
+//! ```ignore
+//! use chip8_core::testing::{assert_screen_matches, run_opcodes};
+//!
+//! // I = FONT(V0); DRW V0, V0, 5
+//! let chip8 = run_opcodes(&[0xF029, 0xD005], 2);
+//! assert_screen_matches(&chip8, "
+//!     ####
+//!     #..#
+//!     #..#
+//!     #..#
+//!     ####
+//! ");
+//! ```
+
+use crate::{Emulator, ScreenBuffer};
+
+/// An emulator with `opcodes` loaded as the ROM, nothing executed yet.
+/// Panics if `opcodes` is empty or doesn't fit in RAM.
+pub fn opcode_test(opcodes: &[u16]) -> Emulator {
+    let rom: Vec<u8> = opcodes.iter().flat_map(|op| op.to_be_bytes()).collect();
+    let mut chip8 = Emulator::new();
+    chip8.load_rom(&rom).expect("opcodes should fit in RAM");
+    chip8
+}
+
+/// Like `opcode_test`, then ticks `n_ticks` times.
+pub fn run_opcodes(opcodes: &[u16], n_ticks: usize) -> Emulator {
+    let mut chip8 = opcode_test(opcodes);
+    for _ in 0..n_ticks {
+        chip8.tick();
+    }
+    chip8
+}
+
+/// Checks the top left corner of the screen against rows of `#` (on) and
+/// `.` (off). Leading whitespace and blank lines are ignored, pixels outside
+/// the pattern are not checked.
+///
+/// # Panics
+///
+/// If any pixel in the pattern differs, showing the expected and actual corner.
+pub fn assert_screen_matches(emu: &Emulator, expected: &str) {
+    let rows: Vec<&str> = expected
+        .lines()
+        .map(str::trim)
+        .filter(|row| !row.is_empty())
+        .collect();
+    let screen = emu.screen();
+
+    let actual: Vec<String> = rows
+        .iter()
+        .enumerate()
+        .map(|(y, row)| {
+            (0..row.chars().count())
+                .map(|x| {
+                    let on = x < screen.width() && y < screen.height() && screen.get_pixel(x, y);
+                    if on { '#' } else { '.' }
+                })
+                .collect()
+        })
+        .collect();
+
+    if actual.iter().zip(&rows).any(|(a, e)| a != e) {
+        panic!(
+            "screen does not match\nexpected:\n{}\nactual:\n{}",
+            rows.join("\n"),
+            actual.join("\n")
+        );
+    }
+}
+
+/// Both screens side by side in one grid, for failure messages: `#` is lit
+/// in both, `.` dark in both, `+` lit only in `actual` and `-` lit only in
+/// `expected`. Both are row-major, `width` pixels to a row.
+pub fn render_screen_diff(expected: &[bool], actual: &[bool], width: usize) -> String {
+    let rows = expected.chunks(width).zip(actual.chunks(width));
+    rows.map(|(expected, actual)| {
+        expected
+            .iter()
+            .zip(actual)
+            .map(|(&e, &a)| match (e, a) {
+                (true, true) => '#',
+                (false, false) => '.',
+                (false, true) => '+',
+                (true, false) => '-',
+            })
+            .collect::<String>()
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Checks every pixel of the display against `golden`, row-major like
+/// `get_display`.
+///
+/// # Panics
+///
+/// If the sizes differ or any pixel does, showing the `render_screen_diff`.
+pub fn assert_display_eq(emu: &Emulator, golden: &[bool]) {
+    let actual = emu.get_display();
+    assert_eq!(
+        golden.len(),
+        actual.len(),
+        "golden screen is the wrong size"
+    );
+    let wrong = golden.iter().zip(actual).filter(|(e, a)| e != a).count();
+    if wrong > 0 {
+        panic!(
+            "{} pixels differ (+ lit only in actual, - lit only in golden)\n{}",
+            wrong,
+            render_screen_diff(golden, actual, emu.screen().width())
+        );
+    }
+}
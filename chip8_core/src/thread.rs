@@ -0,0 +1,212 @@
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{Emulator, EmulatorBuilder, EmulatorEvent, KEY_COUNT, QuirksConfig, TickResult};
+
+const TIMER_PERIOD: Duration = Duration::from_micros(16_667);
+
+#[derive(Debug, Clone)]
+pub struct ThreadConfig {
+    /// Instructions executed per second.
+    pub clock_hz: u32,
+    pub rom: Vec<u8>,
+    pub quirks: QuirksConfig,
+    pub rng_seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmulatorCommand {
+    /// Keys outside 0..16 are dropped.
+    Keypress {
+        key: usize,
+        pressed: bool,
+    },
+    /// Resets the machine and reloads the ROM.
+    Reset,
+    /// Answered with `EmulatorEvent::StateSaved`.
+    SaveState,
+    LoadState(Vec<u8>),
+    Stop,
+}
+
+impl Emulator {
+    /// Runs an emulator on its own thread, paced with `thread::sleep`. The
+    /// thread exits on `EmulatorCommand::Stop` or when either channel is dropped.
+    pub fn run_in_thread(
+        config: ThreadConfig,
+    ) -> (
+        JoinHandle<()>,
+        Sender<EmulatorCommand>,
+        Receiver<EmulatorEvent>,
+    ) {
+        let (command_tx, command_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let handle = thread::spawn(move || run(config, command_rx, event_tx));
+        (handle, command_tx, event_rx)
+    }
+}
+
+fn run(config: ThreadConfig, commands: Receiver<EmulatorCommand>, events: Sender<EmulatorEvent>) {
+    let mut builder = EmulatorBuilder::new().quirks(config.quirks);
+    if let Some(seed) = config.rng_seed {
+        builder = builder.rng_seed(seed);
+    }
+    let mut chip8 = builder.build();
+    if let Err(err) = chip8.load_rom_checked(&config.rom) {
+        let _ = events.send(EmulatorEvent::Error(err));
+        return;
+    }
+
+    let cycle = Duration::from_secs_f64(1.0 / config.clock_hz.max(1) as f64);
+    let mut next_timer_tick = Instant::now() + TIMER_PERIOD;
+    // set once the emulator halts or errors, cleared by Reset / LoadState
+    let mut stopped = false;
+    let mut sounding = false;
+
+    loop {
+        loop {
+            let command = match commands.try_recv() {
+                Ok(command) => command,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            };
+
+            let sent = match command {
+                EmulatorCommand::Keypress { key, pressed } => {
+                    if key < KEY_COUNT {
+                        chip8.keypress(key, pressed);
+                    }
+                    Ok(())
+                }
+                EmulatorCommand::Reset => {
+                    chip8.soft_reset();
+                    stopped = false;
+                    events.send(EmulatorEvent::Draw(chip8.get_display().to_vec()))
+                }
+                EmulatorCommand::SaveState => {
+                    events.send(EmulatorEvent::StateSaved(chip8.save_state()))
+                }
+                EmulatorCommand::LoadState(data) => match chip8.load_state(&data) {
+                    Ok(()) => {
+                        stopped = false;
+                        events.send(EmulatorEvent::Draw(chip8.get_display().to_vec()))
+                    }
+                    Err(err) => events.send(EmulatorEvent::Error(err)),
+                },
+                EmulatorCommand::Stop => return,
+            };
+            if sent.is_err() {
+                return;
+            }
+        }
+
+        if !stopped {
+            let sent = match chip8.tick() {
+                TickResult::Draw => events.send(EmulatorEvent::Draw(chip8.get_display().to_vec())),
+                TickResult::Halted => {
+                    stopped = true;
+                    events.send(EmulatorEvent::Halted)
+                }
+                TickResult::Error(err) => {
+                    stopped = true;
+                    events.send(EmulatorEvent::Error(err))
+                }
+                _ => Ok(()),
+            };
+            if sent.is_err() {
+                return;
+            }
+        }
+
+        if Instant::now() >= next_timer_tick {
+            chip8.tick_timers();
+            next_timer_tick += TIMER_PERIOD;
+
+            for event in chip8.drain_events() {
+                if events.send(event).is_err() {
+                    return;
+                }
+            }
+
+            if (chip8.get_st() > 0) != sounding {
+                sounding = !sounding;
+                if events.send(EmulatorEvent::Sound(sounding)).is_err() {
+                    return;
+                }
+            }
+        }
+
+        thread::sleep(cycle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // LD V0, K; LD F, V0; DRW V0, V0, 5; JP 0x200
+    const KEY_ROM: [u8; 8] = [0xF0, 0x0A, 0xF0, 0x29, 0xD0, 0x05, 0x12, 0x00];
+
+    fn start() -> (
+        JoinHandle<()>,
+        Sender<EmulatorCommand>,
+        Receiver<EmulatorEvent>,
+    ) {
+        Emulator::run_in_thread(ThreadConfig {
+            clock_hz: 10_000,
+            rom: KEY_ROM.to_vec(),
+            // no display wait, so a draw never holds up the next FX0A
+            quirks: QuirksConfig::chip48(),
+            rng_seed: None,
+        })
+    }
+
+    fn press_and_wait_for_draw(
+        commands: &Sender<EmulatorCommand>,
+        events: &Receiver<EmulatorEvent>,
+        key: usize,
+    ) {
+        // held for a few cycles so FX0A sees the press before the release
+        for pressed in [true, false] {
+            commands
+                .send(EmulatorCommand::Keypress { key, pressed })
+                .unwrap();
+            thread::sleep(Duration::from_millis(2));
+        }
+        loop {
+            match events.recv_timeout(Duration::from_secs(1)) {
+                Ok(EmulatorEvent::Draw(_)) => return,
+                Ok(_) => (),
+                Err(err) => panic!("no draw after key {}: {}", key, err),
+            }
+        }
+    }
+
+    #[test]
+    fn answers_every_key_event() {
+        let (handle, commands, events) = start();
+        for n in 0..100 {
+            press_and_wait_for_draw(&commands, &events, n % KEY_COUNT);
+        }
+        commands.send(EmulatorCommand::Stop).unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn out_of_range_keys_are_dropped() {
+        let (handle, commands, events) = start();
+        commands
+            .send(EmulatorCommand::Keypress {
+                key: KEY_COUNT,
+                pressed: true,
+            })
+            .unwrap();
+        // still running and still reading keys
+        press_and_wait_for_draw(&commands, &events, 3);
+        commands.send(EmulatorCommand::Stop).unwrap();
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+//! Approximate COSMAC VIP instruction timings, used when cycle accurate
+//! timing is on, see `Emulator::set_cycle_accurate_timing`. The VIP had no
+//! SUPER-CHIP instructions, they are counted like a NOP. The CHIP-8X figures
+//! are guesses from the closest CHIP-8 instruction.
+
+use crate::{Emulator, Instruction, ScreenBuffer};
+
+/// Machine cycles the VIP interpreter spent on `instruction`, fetch and
+/// decode included. The figures are rounded from timing analyses of the
+/// original interpreter and are best treated as relative weights: DXYN
+/// and FX33 also varied with the data on the real machine.
+pub fn cycle_cost(instruction: &Instruction) -> u8 {
+    match *instruction {
+        Instruction::Nop
+        | Instruction::Unknown(_)
+        | Instruction::ScrollDown(_)
+        | Instruction::ScrollUp(_)
+        | Instruction::ScrollRight
+        | Instruction::ScrollLeft
+        | Instruction::Exit
+        | Instruction::LowRes
+        | Instruction::HighRes
+        | Instruction::LoadLargeFont { .. }
+        | Instruction::StoreFlags { .. }
+        | Instruction::LoadFlags { .. } => 4,
+        Instruction::LoadByte { .. } => 6,
+        Instruction::Ret
+        | Instruction::SkipEqByte { .. }
+        | Instruction::SkipNeByte { .. }
+        | Instruction::AddByte { .. }
+        | Instruction::LoadDelay { .. }
+        | Instruction::SetDelay { .. }
+        | Instruction::SetSound { .. } => 10,
+        Instruction::Jump(_)
+        | Instruction::LoadI(_)
+        | Instruction::CycleBackground
+        | Instruction::SetForeground(_)
+        | Instruction::LoadReg { .. }
+        | Instruction::Or { .. }
+        | Instruction::And { .. }
+        | Instruction::Xor { .. }
+        | Instruction::ShiftRight { .. }
+        | Instruction::ShiftLeft { .. }
+        | Instruction::AddI { .. } => 12,
+        Instruction::SkipEqReg { .. }
+        | Instruction::SkipNeReg { .. }
+        | Instruction::AddReg { .. }
+        | Instruction::SubReg { .. }
+        | Instruction::SubN { .. }
+        | Instruction::SkipKeyPressed { .. }
+        | Instruction::SkipKeyNotPressed { .. }
+        | Instruction::AddNibbles { .. }
+        | Instruction::StepNibbles { .. } => 14,
+        Instruction::LoadFont { .. } => 16,
+        Instruction::WaitKey { .. } => 18,
+        Instruction::JumpV0(_) => 22,
+        Instruction::Cls => 24,
+        Instruction::Call(_) => 26,
+        Instruction::Random { .. } => 36,
+        Instruction::Bcd { .. } | Instruction::ColorZones { .. } => 40,
+        // the register copies loop once per register, X + 1 times
+        Instruction::StoreRegs { vx } | Instruction::LoadRegs { vx } => 14 + 2 * (vx & 0xF),
+        // a fixed setup cost plus one pass per sprite row
+        Instruction::Draw { n, .. } => 23 + 3 * (n & 0xF),
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Counts each instruction as its `cycle_cost` instead of 1. Affects
+    /// `get_total_ticks`, the watchdog and the execution summary. Off by default.
+    pub fn set_cycle_accurate_timing(&mut self, enabled: bool) {
+        self.cycle_accurate_timing = enabled;
+    }
+
+    pub(crate) fn instruction_cycles(&self, op: u16) -> u64 {
+        if self.cycle_accurate_timing {
+            cycle_cost(&self.decode_opcode(op)) as u64
+        } else {
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roms::COUNTER_ROM;
+
+    #[test]
+    fn long_draws_cost_more_than_a_nop() {
+        let draw = Instruction::Draw {
+            vx: 0,
+            vy: 0,
+            n: 15,
+        };
+        assert!(cycle_cost(&draw) > cycle_cost(&Instruction::Nop));
+        assert!(cycle_cost(&draw) > cycle_cost(&Instruction::Draw { vx: 0, vy: 0, n: 1 }));
+    }
+
+    #[test]
+    fn elapsed_cycles_add_up_the_costs() {
+        let mut chip8 = Emulator::new_with_rom(COUNTER_ROM).unwrap();
+        chip8.set_cycle_accurate_timing(true);
+        chip8.tick_n(7);
+        // LD V0 (6), ADD (10), LD DT (10), JP (12), then ADD, LD DT, JP again
+        assert_eq!(chip8.get_total_ticks(), 6 + 2 * (10 + 10 + 12));
+
+        // one per instruction without it
+        let mut chip8 = Emulator::new_with_rom(COUNTER_ROM).unwrap();
+        chip8.tick_n(7);
+        assert_eq!(chip8.get_total_ticks(), 7);
+    }
+}
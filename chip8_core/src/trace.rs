@@ -0,0 +1,84 @@
+//! The last few instructions executed, for working out how a ROM got into
+//! a bad state. See `Emulator::enable_trace`.
+
+use std::collections::VecDeque;
+use std::collections::vec_deque::Iter;
+
+use crate::{Emulator, ScreenBuffer};
+
+/// Ring buffer of `(pc, opcode)` pairs, oldest first. Once full, each new
+/// instruction pushes out the oldest one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    entries: VecDeque<(u16, u16)>,
+    capacity: usize,
+}
+
+impl ExecutionTrace {
+    pub fn new(capacity: usize) -> Self {
+        ExecutionTrace {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, (u16, u16)> {
+        self.entries.iter()
+    }
+
+    /// The newest `n` entries, still oldest first.
+    pub fn last(&self, n: usize) -> Iter<'_, (u16, u16)> {
+        self.entries.range(self.entries.len().saturating_sub(n)..)
+    }
+
+    fn record(&mut self, pc: u16, op: u16) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((pc, op));
+    }
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Starts recording the last `capacity` instructions fetched, replacing
+    /// any trace in progress. An instruction that fails is still recorded.
+    pub fn enable_trace(&mut self, capacity: usize) {
+        self.trace = Some(ExecutionTrace::new(capacity));
+    }
+
+    pub fn disable_trace(&mut self) {
+        self.trace = None;
+    }
+
+    /// None unless `enable_trace` was called.
+    pub fn get_trace(&self) -> Option<&ExecutionTrace> {
+        self.trace.as_ref()
+    }
+
+    pub(crate) fn record_trace(&mut self, pc: u16, op: u16) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(pc, op);
+        }
+    }
+
+    pub(crate) fn clear_trace(&mut self) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.entries.clear();
+        }
+    }
+}
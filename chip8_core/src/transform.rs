@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+
+use crate::{BoolArrayScreen, Emulator, ScreenBuffer};
+
+/// Orientation applied by `get_display_transformed`. Rotations are clockwise
+/// and `Rotate90`/`Rotate270` swap the width and height, 32 wide by 64 tall
+/// in low resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTransform {
+    #[default]
+    Normal,
+    FlipH,
+    FlipV,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl<S: ScreenBuffer> Emulator<S> {
+    /// Mirrors the screen left to right.
+    pub fn flip_horizontal(&mut self) {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        for y in 0..height {
+            for x in 0..width / 2 {
+                let left = self.screen.get_pixel(x, y);
+                let right = self.screen.get_pixel(width - 1 - x, y);
+                self.screen.set_pixel(x, y, right);
+                self.screen.set_pixel(width - 1 - x, y, left);
+            }
+        }
+        self.screen_changed();
+    }
+
+    /// Mirrors the screen top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        for y in 0..height / 2 {
+            for x in 0..width {
+                let top = self.screen.get_pixel(x, y);
+                let bottom = self.screen.get_pixel(x, height - 1 - y);
+                self.screen.set_pixel(x, y, bottom);
+                self.screen.set_pixel(x, height - 1 - y, top);
+            }
+        }
+        self.screen_changed();
+    }
+}
+
+impl Emulator<BoolArrayScreen> {
+    /// Returns the display as `get_display` would, reoriented for the frontend.
+    /// The emulator's own screen is not modified.
+    pub fn get_display_transformed(&self, transform: DisplayTransform) -> Cow<'_, [bool]> {
+        let (w, h) = (self.screen.width(), self.screen.height());
+        let pixel = |x: usize, y: usize| self.screen.get_pixel(x, y);
+
+        let out: Vec<bool> = match transform {
+            DisplayTransform::Normal => return Cow::Borrowed(self.get_display()),
+            DisplayTransform::FlipH => (0..w * h).map(|i| pixel(w - 1 - i % w, i / w)).collect(),
+            DisplayTransform::FlipV => (0..w * h).map(|i| pixel(i % w, h - 1 - i / w)).collect(),
+            DisplayTransform::Rotate180 => (0..w * h)
+                .map(|i| pixel(w - 1 - i % w, h - 1 - i / w))
+                .collect(),
+            // output rows are h pixels wide
+            DisplayTransform::Rotate90 => (0..w * h).map(|i| pixel(i / h, h - 1 - i % h)).collect(),
+            DisplayTransform::Rotate270 => {
+                (0..w * h).map(|i| pixel(w - 1 - i / h, i % h)).collect()
+            }
+        };
+        Cow::Owned(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERN: [&str; 4] = ["#...", "##..", "#.#.", "####"];
+
+    fn with_pattern() -> Emulator {
+        let mut chip8 = Emulator::new();
+        for (y, row) in PATTERN.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                chip8.screen.set_pixel(x, y, c == '#');
+            }
+        }
+        chip8
+    }
+
+    // the 4x4 block with its top left corner at (x0, y0)
+    fn block(pixels: &[bool], width: usize, x0: usize, y0: usize) -> Vec<String> {
+        (y0..y0 + 4)
+            .map(|y| {
+                (x0..x0 + 4)
+                    .map(|x| if pixels[x + width * y] { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flips_a_known_pattern() {
+        let mut chip8 = with_pattern();
+        chip8.flip_horizontal();
+        assert_eq!(
+            block(chip8.get_display(), 64, 60, 0),
+            ["...#", "..##", ".#.#", "####"]
+        );
+        assert_eq!(chip8.get_display().iter().filter(|on| **on).count(), 9);
+
+        let mut chip8 = with_pattern();
+        chip8.flip_vertical();
+        assert_eq!(
+            block(chip8.get_display(), 64, 0, 28),
+            ["####", "#.#.", "##..", "#..."]
+        );
+    }
+
+    #[test]
+    fn transformed_display_leaves_the_screen_alone() {
+        let chip8 = with_pattern();
+        let flipped = chip8.get_display_transformed(DisplayTransform::FlipH);
+        assert_eq!(block(&flipped, 64, 60, 0), ["...#", "..##", ".#.#", "####"]);
+        let rotated = chip8.get_display_transformed(DisplayTransform::Rotate180);
+        assert_eq!(
+            block(&rotated, 64, 60, 28),
+            ["####", ".#.#", "..##", "...#"]
+        );
+        // rotated a quarter turn clockwise the output is 32 wide
+        let rotated = chip8.get_display_transformed(DisplayTransform::Rotate90);
+        assert_eq!(block(&rotated, 32, 28, 0), ["####", "#.#.", "##..", "#..."]);
+        assert_eq!(block(chip8.get_display(), 64, 0, 0), PATTERN);
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+// CLS, then draws the font digit for V0 in a loop that skips over a sprite
+const ROM: &[u8] = &[
+    0x00, 0xE0, // CLS
+    0xF0, 0x29, // LD F, V0
+    0xD0, 0x05, // DRW V0, V0, 5
+    0x12, 0x0A, // JP 0x20A
+    0xFF, 0x81, // sprite data
+    0x00, 0xD1, // SCU 1, XO-CHIP
+    0x12, 0x02, // JP 0x202
+];
+
+fn run_info(name: &str, rom: &[u8], args: &[&str]) -> String {
+    let path: PathBuf = std::env::temp_dir().join(name);
+    std::fs::write(&path, rom).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_chip8-info"))
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn reports_quirks_and_reachable_opcodes() {
+    let out = run_info("chip8_info_text.ch8", ROM, &[]);
+    assert!(out.contains("opcodes: 6 reachable"), "{out}");
+    assert!(out.contains("XO-CHIP"), "{out}");
+    assert!(out.contains("  DXYN  1\n"), "{out}");
+
+    // without the scroll it is plain CHIP-8
+    let out = run_info("chip8_info_vip.ch8", &ROM[..8], &[]);
+    assert!(out.contains("COSMAC"), "{out}");
+    assert!(out.contains("opcodes: 4 reachable"), "{out}");
+}
+
+#[test]
+fn check_compatibility_lists_unsupported_opcodes() {
+    let out = run_info(
+        "chip8_info_compat.ch8",
+        ROM,
+        &["--check-compatibility", "superchip"],
+    );
+    assert!(out.contains("unsupported by SuperChip: 1"), "{out}");
+    assert!(out.contains("0x20A  00D1  SCU 1"), "{out}");
+}
+
+#[test]
+fn json_report() {
+    let out = run_info(
+        "chip8_info_json.ch8",
+        ROM,
+        &["--json", "--check-compatibility", "chip8"],
+    );
+    let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(report["size"], ROM.len());
+    assert_eq!(report["opcode_count"], 6);
+    assert_eq!(report["opcode_families"]["1NNN"], 2);
+    assert_eq!(report["compatibility"]["unsupported"][0]["opcode"], 0x00D1);
+}
@@ -0,0 +1,76 @@
+//! Whole-screen checks of ROMs against golden screens, with a diff of the
+//! two on failure.
+
+use chip8_core::roms::DRAW_ROM;
+use chip8_core::testing::{assert_display_eq, render_screen_diff};
+use chip8_core::{Emulator, QuirksConfig, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const ZERO: &str = "
+    ####
+    #..#
+    #..#
+    #..#
+    ####
+";
+
+const EIGHT: &str = "
+    ####
+    #..#
+    ####
+    #..#
+    ####
+";
+
+/// A blank screen with `pattern` (rows of `#` and `.`) drawn at `(x, y)`,
+/// wrapping at the edges.
+fn golden(pattern: &str, x: usize, y: usize) -> [bool; SCREEN_WIDTH * SCREEN_HEIGHT] {
+    let mut screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+    let rows = pattern.lines().map(str::trim).filter(|row| !row.is_empty());
+    for (dy, row) in rows.enumerate() {
+        for (dx, pixel) in row.chars().enumerate() {
+            let (px, py) = ((x + dx) % SCREEN_WIDTH, (y + dy) % SCREEN_HEIGHT);
+            screen[py * SCREEN_WIDTH + px] = pixel == '#';
+        }
+    }
+    screen
+}
+
+// no display wait, so every tick runs an instruction, and sprites wrap
+fn run(rom: &[u8], ticks: usize) -> Emulator {
+    let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48().with_clip_sprites(false));
+    chip8.load_rom(rom).unwrap();
+    chip8.tick_n(ticks);
+    chip8
+}
+
+#[test]
+fn draw_rom_glyphs() {
+    // after 1 + 6k ticks the screen shows glyph k - 1
+    assert_display_eq(&run(DRAW_ROM, 7), &golden(ZERO, 0, 0));
+    assert_display_eq(&run(DRAW_ROM, 55), &golden(EIGHT, 0, 0));
+    // V0 wraps back to 0 after F
+    assert_display_eq(&run(DRAW_ROM, 103), &golden(ZERO, 0, 0));
+}
+
+#[test]
+fn sprite_wraps_into_every_corner() {
+    // LD V0, 62; LD V1, 30; LD V2, 0; LD F, V2; DRW V0, V1, 5
+    let rom = [0x60, 0x3E, 0x61, 0x1E, 0x62, 0x00, 0xF2, 0x29, 0xD0, 0x15];
+    assert_display_eq(&run(&rom, 5), &golden(ZERO, 62, 30));
+}
+
+#[test]
+fn diff_marks_the_differences() {
+    let expected = [true, true, false, false];
+    let actual = [true, false, true, false];
+    assert_eq!(render_screen_diff(&expected, &actual, 2), "#-\n+.");
+}
+
+#[test]
+#[should_panic(expected = "2 pixels differ")]
+fn mismatched_screens_fail() {
+    let mut wrong = golden(ZERO, 0, 0);
+    wrong[0] = false;
+    wrong[SCREEN_WIDTH * SCREEN_HEIGHT - 1] = true;
+    assert_display_eq(&run(DRAW_ROM, 7), &wrong);
+}
@@ -0,0 +1,186 @@
+use chip8_core::testing::{assert_screen_matches, opcode_test, run_opcodes};
+
+#[test]
+fn cls_clears_the_screen() {
+    // LD F, V0; DRW V0, V0, 5; CLS
+    let mut chip8 = run_opcodes(&[0xF029, 0xD005, 0x00E0], 2);
+    chip8.trigger_frame_interrupt();
+    chip8.tick();
+    assert_screen_matches(
+        &chip8,
+        "
+        ....
+        ....
+        ",
+    );
+    assert_eq!(chip8.get_pixel(0, 0), Some(false));
+}
+
+#[test]
+fn jump() {
+    // JP 0x206 over two loads into V0
+    let chip8 = run_opcodes(&[0x1206, 0x6001, 0x6002, 0x6103], 2);
+    assert_eq!(chip8.get_registers()[0], 0);
+    assert_eq!(chip8.get_registers()[1], 3);
+}
+
+#[test]
+fn call_and_return() {
+    // CALL 0x206; LD V1, 0x01; NOP; RET
+    let chip8 = run_opcodes(&[0x2206, 0x6101, 0x0000, 0x00EE], 1);
+    assert_eq!(chip8.get_pc(), 0x206);
+    assert_eq!(chip8.get_sp(), 1);
+    assert_eq!(chip8.get_stack()[0], 0x202);
+
+    let chip8 = run_opcodes(&[0x2206, 0x6101, 0x0000, 0x00EE], 2);
+    assert_eq!(chip8.get_pc(), 0x202);
+    assert_eq!(chip8.get_sp(), 0);
+}
+
+#[test]
+fn skips() {
+    // each skip is followed by LD V1, 0x01, which only runs when not skipped
+    for (op, skipped) in [
+        (0x3000, true),  // SE V0, 0x00
+        (0x3001, false), // SE V0, 0x01
+        (0x4001, true),  // SNE V0, 0x01
+        (0x4000, false), // SNE V0, 0x00
+        (0x5020, true),  // SE V0, V2
+        (0x9020, false), // SNE V0, V2
+    ] {
+        let chip8 = run_opcodes(&[op, 0x6101], 2);
+        assert_eq!(chip8.get_registers()[1] == 0, skipped, "{:04X}", op);
+    }
+}
+
+#[test]
+fn load_and_add_byte() {
+    // LD V5, 0xFE; ADD V5, 0x03
+    let chip8 = run_opcodes(&[0x65FE, 0x7503], 2);
+    // 7XNN wraps and leaves VF alone
+    assert_eq!(chip8.get_registers()[5], 0x01);
+    assert_eq!(chip8.get_registers()[0xF], 0);
+}
+
+#[test]
+fn alu() {
+    // V0 = 0xF0, V1 = 0x33, then 8 0 1 N
+    let cases = [
+        (0x8010, 0x33, None),
+        (0x8011, 0xF3, Some(0)),
+        (0x8012, 0x30, Some(0)),
+        (0x8013, 0xC3, Some(0)),
+        (0x8014, 0x23, Some(1)),
+        (0x8015, 0xBD, Some(1)),
+        // the shifts use VY on the COSMAC VIP
+        (0x8016, 0x19, Some(1)),
+        (0x8017, 0x43, Some(0)),
+        (0x801E, 0x66, Some(0)),
+    ];
+    for (op, result, flag) in cases {
+        let chip8 = run_opcodes(&[0x60F0, 0x6133, op], 3);
+        assert_eq!(chip8.get_registers()[0], result, "{:04X}", op);
+        if let Some(flag) = flag {
+            assert_eq!(chip8.get_registers()[0xF], flag, "{:04X} VF", op);
+        }
+    }
+}
+
+#[test]
+fn load_i_and_jump_v0() {
+    // LD I, 0x345; LD V0, 0x04; JP V0, 0x202
+    let chip8 = run_opcodes(&[0xA345, 0x6004, 0xB202], 3);
+    assert_eq!(chip8.get_i(), 0x345);
+    assert_eq!(chip8.get_pc(), 0x206);
+}
+
+#[test]
+fn random_is_masked() {
+    // RND V0, 0x00 always gives 0, RND V1, 0x0F never sets the high nibble
+    let chip8 = run_opcodes(&[0x60FF, 0xC000, 0xC10F], 3);
+    assert_eq!(chip8.get_registers()[0], 0);
+    assert_eq!(chip8.get_registers()[1] & 0xF0, 0);
+}
+
+#[test]
+fn draw_font_digit_and_collide() {
+    // LD V0, 0x01; LD F, V0; LD V0, 0x00; DRW V0, V0, 5
+    let mut chip8 = run_opcodes(&[0x6001, 0xF029, 0x6000, 0xD005, 0xD005], 4);
+    assert_screen_matches(
+        &chip8,
+        "
+        ..#.
+        .##.
+        ..#.
+        ..#.
+        .###
+        ",
+    );
+    assert_eq!(chip8.get_registers()[0xF], 0);
+
+    // drawing it again erases it and reports the collision
+    chip8.trigger_frame_interrupt();
+    chip8.tick();
+    assert_screen_matches(
+        &chip8,
+        "
+        ....
+        ....
+        ",
+    );
+    assert_eq!(chip8.get_registers()[0xF], 1);
+}
+
+#[test]
+fn key_skips() {
+    // LD V0, 0x07; SKP V0; LD V1, 0x01; SKNP V0; LD V2, 0x02
+    let mut chip8 = opcode_test(&[0x6007, 0xE09E, 0x6101, 0xE0A1, 0x6202]);
+    chip8.keypress(0x7, true);
+    chip8.tick_n(4);
+    assert_eq!(chip8.get_registers()[1], 0);
+    assert_eq!(chip8.get_registers()[2], 0x02);
+}
+
+#[test]
+fn wait_for_key() {
+    // LD V3, K
+    let mut chip8 = run_opcodes(&[0xF30A, 0x6101], 3);
+    assert_eq!(chip8.get_pc(), 0x200);
+
+    chip8.keypress(0xA, true);
+    chip8.tick();
+    assert_eq!(chip8.get_registers()[3], 0xA);
+    chip8.keypress(0xA, false);
+    chip8.tick();
+    assert_eq!(chip8.get_registers()[1], 0x01);
+}
+
+#[test]
+fn timers() {
+    // LD V0, 0x20; LD DT, V0; LD ST, V0; LD V1, DT
+    let chip8 = run_opcodes(&[0x6020, 0xF015, 0xF018, 0xF107], 4);
+    assert_eq!(chip8.get_dt(), 0x20);
+    assert_eq!(chip8.get_st(), 0x20);
+    assert_eq!(chip8.get_registers()[1], 0x20);
+}
+
+#[test]
+fn add_i_and_bcd() {
+    // LD I, 0x300; LD V0, 0x89 (137); ADD I, V0; BCD V0; LD V2, [I]
+    let chip8 = run_opcodes(&[0xA300, 0x6089, 0xF01E, 0xF033, 0xF265], 5);
+    assert_eq!(&chip8.get_registers()[..3], &[1, 3, 7]);
+    // FX65 leaves I after the last register on the COSMAC VIP
+    assert_eq!(chip8.get_i(), 0x389 + 3);
+}
+
+#[test]
+fn store_and_load_registers() {
+    // V0-V2 = 0x11, 0x22, 0x33; LD [I], V2 at 0x300; zero them; LD V2, [I] from 0x300
+    let chip8 = run_opcodes(
+        &[
+            0x6011, 0x6122, 0x6233, 0xA300, 0xF255, 0x6000, 0x6100, 0x6200, 0xA300, 0xF265,
+        ],
+        10,
+    );
+    assert_eq!(&chip8.get_registers()[..3], &[0x11, 0x22, 0x33]);
+}
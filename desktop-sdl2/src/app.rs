@@ -0,0 +1,489 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chip8_core::{
+    COMPRESSED_SAVE_STATE_EXTENSION, ChipError, Emulator, JSON_SAVE_STATE_EXTENSION, MAX_ROM_SIZE,
+    RomInfo, SAVE_STATE_EXTENSION, disassemble, disassemble_chip8x,
+};
+use sdl2::render::Canvas;
+use sdl2::video::{FullscreenType, Window};
+
+use crate::recent::RecentRoms;
+use crate::{Colors, DEFAULT_COLORS, HISTORY_DEPTH, WINDOW_TITLE};
+
+const MIN_TICKS_PER_FRAME: u32 = 1;
+const MAX_TICKS_PER_FRAME: u32 = 1000;
+const STATUS_DURATION: Duration = Duration::from_secs(2);
+
+/// Where the ROM came from, so a reset can load it again.
+pub enum RomSource {
+    File(PathBuf),
+    /// Piped in with `-` as the ROM argument, kept in memory since stdin can't be re-read.
+    Stdin(Vec<u8>),
+}
+
+impl RomSource {
+    /// Reads a piped ROM to the end, rejecting an empty one or one too big for memory.
+    pub fn from_reader(reader: impl Read) -> Result<Self, String> {
+        let mut data = Vec::new();
+        // one byte past the limit is enough to know it's too big
+        reader
+            .take(MAX_ROM_SIZE as u64 + 1)
+            .read_to_end(&mut data)
+            .map_err(|err| format!("Unable to read ROM from stdin: {}", err))?;
+        if data.is_empty() {
+            return Err("No ROM data on stdin".to_string());
+        }
+        if data.len() > MAX_ROM_SIZE {
+            return Err(format!(
+                "ROM on stdin is over the {} byte limit",
+                MAX_ROM_SIZE
+            ));
+        }
+        Ok(RomSource::Stdin(data))
+    }
+
+    /// The ROM's bytes, read afresh for files.
+    pub fn read(&self) -> io::Result<Vec<u8>> {
+        match self {
+            RomSource::File(path) => fs::read(path),
+            RomSource::Stdin(data) => Ok(data.clone()),
+        }
+    }
+
+    /// Just the file name for files, for places where the full path is too long.
+    pub fn short_name(&self) -> String {
+        match self {
+            RomSource::File(path) => path
+                .file_name()
+                .map_or_else(|| self.to_string(), |n| n.to_string_lossy().into_owned()),
+            RomSource::Stdin(_) => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RomSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomSource::File(path) => write!(f, "{}", path.display()),
+            RomSource::Stdin(_) => write!(f, "<stdin>"),
+        }
+    }
+}
+
+/// What is drawn on top of the CHIP-8 screen. Emulation pauses while one is open.
+pub enum Overlay {
+    None,
+    Recent,
+    Palette { query: String, selected: usize },
+}
+
+/// Everything the frontend's commands can act on.
+pub struct AppState {
+    pub chip8: Emulator,
+    pub canvas: Canvas<Window>,
+    pub rom: RomSource,
+    pub recent: Option<RecentRoms>,
+    pub overlay: Overlay,
+    pub ticks_per_frame: u32,
+    pub self_modified: bool,
+    /// The ROM was blocked in FX0A when the screen was last drawn.
+    pub waiting_for_key: bool,
+    pub reported_halt: bool,
+    pub needs_redraw: bool,
+    pub paused: bool,
+    pub muted: bool,
+    pub keypad_hud: bool,
+    pub colors: Colors,
+    /// Swap the foreground and background colors, for high contrast.
+    pub inverted: bool,
+    /// Write save state files LZ4 compressed (.c8z) instead of as JSON (.ch8state).
+    pub compress_saves: bool,
+    /// How many frames `rewind` can go back.
+    pub rewind_depth: usize,
+    pub quit: bool,
+    save_slot: Option<Vec<u8>>,
+    // one copy of the machine per frame, newest at the back
+    rewind_frames: VecDeque<Emulator>,
+    // window title for the loaded ROM, and when a temporary status message replaces it
+    title: String,
+    status_until: Option<Instant>,
+}
+
+impl AppState {
+    pub fn new(
+        chip8: Emulator,
+        canvas: Canvas<Window>,
+        rom: RomSource,
+        recent: Option<RecentRoms>,
+        ticks_per_frame: u32,
+    ) -> Self {
+        AppState {
+            chip8,
+            canvas,
+            rom,
+            recent,
+            overlay: Overlay::None,
+            ticks_per_frame,
+            self_modified: false,
+            waiting_for_key: false,
+            reported_halt: false,
+            needs_redraw: true,
+            paused: false,
+            muted: false,
+            keypad_hud: false,
+            colors: DEFAULT_COLORS,
+            inverted: false,
+            compress_saves: false,
+            rewind_depth: 0,
+            quit: false,
+            save_slot: None,
+            rewind_frames: VecDeque::new(),
+            title: WINDOW_TITLE.to_string(),
+            status_until: None,
+        }
+    }
+
+    /// Loads `rom` on top of the current state, updates the title and records it as recent.
+    pub fn load_rom(&mut self) -> Result<RomInfo, ChipError> {
+        let info = match &self.rom {
+            RomSource::File(path) => self.chip8.load_rom_from_file(path)?,
+            RomSource::Stdin(data) => self.chip8.load_rom_from_bytes(data)?,
+        };
+        self.rom_loaded(&info);
+        Ok(info)
+    }
+
+    /// The part of `load_rom` after the ROM is in RAM, for emulators that
+    /// were created with it already loaded.
+    pub fn rom_loaded(&mut self, info: &RomInfo) {
+        self.chip8.set_mode(info.detected_variant.mode());
+        self.title = match self.chip8.identify_rom() {
+            Some(entry) => format!("{} - {} by {}", WINDOW_TITLE, entry.title, entry.author),
+            None => WINDOW_TITLE.to_string(),
+        };
+        self.canvas.window_mut().set_title(&self.title).unwrap();
+
+        if let (Some(recent), RomSource::File(path)) = (self.recent.as_mut(), &self.rom) {
+            recent.push(path, &info.sha256);
+            if let Err(err) = recent.save() {
+                eprintln!("Unable to save recent ROMs: {}", err);
+            }
+        }
+    }
+
+    /// Resets the machine and restarts the ROM it was running.
+    pub fn reset_rom(&mut self) {
+        self.chip8.soft_reset();
+        self.rewind_frames.clear();
+        self.self_modified = false;
+        self.reported_halt = false;
+        self.needs_redraw = true;
+    }
+
+    /// Resets the machine and loads `rom` in place of the current ROM.
+    pub fn switch_rom(&mut self, rom: RomSource) {
+        self.rom = rom;
+        self.chip8.reset();
+        self.rewind_frames.clear();
+        self.self_modified = false;
+        self.reported_halt = false;
+        self.needs_redraw = true;
+        if let Err(err) = self.load_rom() {
+            eprintln!("Unable to load {}: {}", self.rom, err);
+            self.quit = true;
+        }
+    }
+
+    /// Reloads a ROM that changed on disk. Unlike `switch_rom`, a failed load
+    /// is reported without quitting so the next save can fix it.
+    pub fn hot_reload(&mut self) {
+        self.chip8.reset();
+        self.rewind_frames.clear();
+        self.self_modified = false;
+        self.reported_halt = false;
+        self.needs_redraw = true;
+        match self.load_rom() {
+            Ok(_) => self.show_status(&format!("Reloaded {}", self.rom.short_name())),
+            Err(err) => eprintln!("Unable to reload {}: {}", self.rom, err),
+        }
+    }
+
+    /// Shows `message` in the window title for a couple of seconds.
+    pub fn show_status(&mut self, message: &str) {
+        let title = format!("{} - {}", WINDOW_TITLE, message);
+        self.canvas.window_mut().set_title(&title).unwrap();
+        self.status_until = Some(Instant::now() + STATUS_DURATION);
+    }
+
+    /// Puts the ROM title back once a status message has expired.
+    pub fn update_status(&mut self) {
+        if self
+            .status_until
+            .is_some_and(|until| Instant::now() >= until)
+        {
+            self.status_until = None;
+            self.canvas.window_mut().set_title(&self.title).unwrap();
+        }
+    }
+
+    /// Remembers the machine as it is now, dropping the oldest frame once
+    /// `rewind_depth` are kept.
+    pub fn record_rewind_frame(&mut self) {
+        if self.rewind_depth == 0 {
+            return;
+        }
+        if self.rewind_frames.len() >= self.rewind_depth {
+            self.rewind_frames.pop_front();
+        }
+        let mut frame = self.chip8.clone();
+        // every frame would otherwise carry its own copy of the undo history
+        frame.disable_history();
+        self.rewind_frames.push_back(frame);
+    }
+
+    /// Goes back one recorded frame. The keys stay as they are held now and
+    /// the undo history starts over from the restored frame.
+    pub fn rewind(&mut self) {
+        let Some(frame) = self.rewind_frames.pop_back() else {
+            return;
+        };
+        let keys = *self.chip8.get_keys();
+        self.chip8 = frame;
+        self.chip8.enable_history(HISTORY_DEPTH);
+        for (key, pressed) in keys.into_iter().enumerate() {
+            if self.chip8.is_key_pressed(key) != pressed {
+                self.chip8.keypress(key, pressed);
+            }
+        }
+        self.needs_redraw = true;
+    }
+
+    pub fn undo(&mut self) {
+        self.needs_redraw |= self.chip8.undo();
+    }
+
+    pub fn redo(&mut self) {
+        self.needs_redraw |= self.chip8.redo();
+    }
+
+    /// Saves to the in-memory slot and, for ROM files, to a state file next to the ROM.
+    pub fn save_state(&mut self) {
+        self.save_slot = Some(self.chip8.save_state());
+
+        let RomSource::File(rom_path) = &self.rom else {
+            return;
+        };
+        let (path, written) = if self.compress_saves {
+            let path = rom_path.with_extension(COMPRESSED_SAVE_STATE_EXTENSION);
+            let written = self.chip8.save_state_to_file_compressed(&path);
+            (path, written)
+        } else {
+            // JSON states remember the ROM, so loading one for another ROM fails cleanly
+            let path = rom_path.with_extension(JSON_SAVE_STATE_EXTENSION);
+            let written = self.chip8.save_state_json().and_then(|json| {
+                fs::write(&path, json).map_err(|err| ChipError::SaveStateFileFailed(err.kind()))
+            });
+            (path, written)
+        };
+        match written {
+            Ok(()) => self.show_status("State saved"),
+            Err(err) => eprintln!("Unable to save state to {}: {}", path.display(), err),
+        }
+    }
+
+    /// Loads the in-memory slot, falling back to the ROM's state file from an
+    /// earlier session. Any of the extensions is accepted whatever `compress_saves` is.
+    pub fn load_state(&mut self) {
+        if let Some(state) = &self.save_slot {
+            self.chip8
+                .load_state(state)
+                .expect("the save slot only holds states from this emulator");
+            self.needs_redraw = true;
+            return;
+        }
+
+        let RomSource::File(rom_path) = &self.rom else {
+            return;
+        };
+        let found = [
+            COMPRESSED_SAVE_STATE_EXTENSION,
+            JSON_SAVE_STATE_EXTENSION,
+            SAVE_STATE_EXTENSION,
+        ]
+        .iter()
+        .map(|ext| rom_path.with_extension(ext))
+        .find(|path| path.exists());
+        if let Some(path) = found {
+            let loaded = if path
+                .extension()
+                .is_some_and(|ext| ext == JSON_SAVE_STATE_EXTENSION)
+            {
+                fs::read(&path)
+                    .map_err(|err| ChipError::SaveStateFileFailed(err.kind()))
+                    .and_then(|json| self.chip8.load_state_json(&json))
+            } else {
+                self.chip8.load_state_from_file(&path)
+            };
+            match loaded {
+                Ok(()) => self.needs_redraw = true,
+                Err(err) => eprintln!("Unable to load state from {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    /// Prints the ROM's disassembly to stdout, read afresh for file sources.
+    pub fn print_disassembly(&mut self) {
+        let rom = match self.rom.read() {
+            Ok(rom) => rom,
+            Err(err) => {
+                eprintln!("Unable to read {}: {}", self.rom, err);
+                return;
+            }
+        };
+        let lines = if self.chip8.get_quirks().chip8x_enabled {
+            disassemble_chip8x(&rom)
+        } else {
+            disassemble(&rom)
+        };
+        for line in lines {
+            if let Some(label) = &line.label {
+                println!("{}:", label);
+            }
+            println!(
+                "    {:#05X}  {:04X}  {}",
+                line.address, line.opcode, line.mnemonic
+            );
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    /// Passes a keypad press on to the emulator, redrawing if the keypad HUD shows it.
+    pub fn press_key(&mut self, key: usize, pressed: bool) {
+        self.chip8.keypress(key, pressed);
+        self.needs_redraw |= self.keypad_hud;
+    }
+
+    pub fn toggle_inverted(&mut self) {
+        self.inverted = !self.inverted;
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_keypad_hud(&mut self) {
+        self.keypad_hud = !self.keypad_hud;
+        self.needs_redraw = true;
+    }
+
+    pub fn speed_up(&mut self) {
+        self.ticks_per_frame = (self.ticks_per_frame * 2).min(MAX_TICKS_PER_FRAME);
+    }
+
+    pub fn slow_down(&mut self) {
+        self.ticks_per_frame = (self.ticks_per_frame / 2).max(MIN_TICKS_PER_FRAME);
+    }
+
+    pub fn toggle_fullscreen(&mut self) {
+        let window = self.canvas.window_mut();
+        let next = match window.fullscreen_state() {
+            FullscreenType::Off => FullscreenType::Desktop,
+            _ => FullscreenType::Off,
+        };
+        window.set_fullscreen(next).unwrap();
+        self.needs_redraw = true;
+    }
+
+    pub fn toggle_recent(&mut self) {
+        self.overlay = match self.overlay {
+            Overlay::Recent => Overlay::None,
+            _ => {
+                if let Some(recent) = self.recent.as_mut() {
+                    recent.refresh();
+                }
+                Overlay::Recent
+            }
+        };
+        self.needs_redraw = true;
+    }
+
+    pub fn open_palette(&mut self) {
+        self.overlay = Overlay::Palette {
+            query: String::new(),
+            selected: 0,
+        };
+        self.needs_redraw = true;
+    }
+
+    pub fn close_overlay(&mut self) {
+        self.overlay = Overlay::None;
+        self.needs_redraw = true;
+    }
+
+    pub fn quit(&mut self) {
+        self.quit = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BrokenPipe;
+
+    impl Read for BrokenPipe {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    fn stdin_bytes(result: Result<RomSource, String>) -> Vec<u8> {
+        match result {
+            Ok(RomSource::Stdin(data)) => data,
+            Ok(RomSource::File(_)) => panic!("expected a stdin ROM"),
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    #[test]
+    fn reads_a_piped_rom() {
+        let rom = [0x60, 0x01, 0x12, 0x02];
+        assert_eq!(stdin_bytes(RomSource::from_reader(&rom[..])), rom);
+        let full = vec![0xAA; MAX_ROM_SIZE];
+        assert_eq!(
+            stdin_bytes(RomSource::from_reader(&full[..])).len(),
+            MAX_ROM_SIZE
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized_input() {
+        let err = RomSource::from_reader(io::empty()).err().unwrap();
+        assert_eq!(err, "No ROM data on stdin");
+        let big = vec![0; MAX_ROM_SIZE + 1];
+        let err = RomSource::from_reader(&big[..]).err().unwrap();
+        assert!(err.contains("3584"), "{}", err);
+    }
+
+    #[test]
+    fn reports_read_errors() {
+        let err = RomSource::from_reader(BrokenPipe).err().unwrap();
+        assert!(err.starts_with("Unable to read ROM from stdin"), "{}", err);
+    }
+
+    #[test]
+    fn stdin_rom_reads_back_the_same_bytes() {
+        let source = RomSource::Stdin(vec![1, 2, 3]);
+        assert_eq!(source.read().unwrap(), [1, 2, 3]);
+        assert_eq!(source.short_name(), "<stdin>");
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use chip8_core::QuirksConfig;
+use clap::Parser;
+use sdl2::pixels::Color;
+
+/// A CHIP-8 emulator.
+///
+/// Keys 1234/QWER/ASDF/ZXCV are the hex keypad, run with --show-keymap to see
+/// the mapping. Ctrl+P opens the command palette, hold Backspace to rewind.
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Args {
+    /// ROM to run, or - to read it from stdin. Defaults to the most recently played ROM.
+    pub rom: Option<PathBuf>,
+
+    /// Window pixels per CHIP-8 pixel [default: 15]
+    #[arg(long)]
+    pub scale: Option<u32>,
+
+    /// Emulation speed as a multiple of 600 instructions per second
+    #[arg(long, value_parser = parse_speed)]
+    pub speed: Option<f32>,
+
+    /// Comma separated quirks or presets, e.g. chip48,clip-sprites
+    #[arg(long)]
+    pub quirks: Option<QuirksConfig>,
+
+    /// Start in fullscreen
+    #[arg(long)]
+    pub fullscreen: bool,
+
+    /// Start with the sound muted
+    #[arg(long)]
+    pub mute: bool,
+
+    /// Color of lit pixels as RRGGBB hex [default: FFFFFF]
+    #[arg(long, value_parser = parse_color)]
+    pub fg_color: Option<Color>,
+
+    /// Color of unlit pixels as RRGGBB hex [default: 000000]
+    #[arg(long, value_parser = parse_color)]
+    pub bg_color: Option<Color>,
+
+    /// Seed for CXNN so random ROMs behave the same every run
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Stop executing after this many instructions
+    #[arg(long)]
+    pub max_cycles: Option<u64>,
+
+    /// Record the screen to an animated GIF until the window closes
+    #[arg(long, value_name = "OUT.gif")]
+    pub record_gif: Option<PathBuf>,
+
+    /// Reload the ROM whenever the file changes
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Print which keyboard key drives each keypad key
+    #[arg(long)]
+    pub show_keymap: bool,
+
+    /// Frames kept for rewinding by holding Backspace, 0 turns it off [default: 600]
+    #[arg(long, value_name = "N")]
+    pub rewind_depth: Option<usize>,
+
+    /// Write LZ4 compressed save states (.c8z) instead of JSON ones (.ch8state)
+    #[arg(long)]
+    pub compress_saves: bool,
+
+    /// Play a replay file against the ROM without a window, exiting 0 if it passes
+    #[arg(long, value_name = "REPLAY.json")]
+    pub replay: Option<PathBuf>,
+
+    /// Step through the ROM one instruction per Enter in the terminal, without a window
+    #[arg(long, conflicts_with_all = ["replay", "split_screen"])]
+    pub debug: bool,
+
+    /// Run two ROMs side by side with shared input
+    #[arg(long, num_args = 2, value_names = ["LEFT", "RIGHT"], conflicts_with = "rom")]
+    pub split_screen: Option<Vec<PathBuf>>,
+}
+
+fn parse_speed(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err("expected a positive number such as 0.5 or 2".to_string()),
+    }
+}
+
+// accepts RRGGBB with or without a leading #
+fn parse_color(s: &str) -> Result<Color, String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 {
+        return Err("expected a color like FF6600".to_string());
+    }
+    let rgb = u32::from_str_radix(hex, 16).map_err(|_| "expected a color like FF6600")?;
+    Ok(Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
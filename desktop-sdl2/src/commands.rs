@@ -0,0 +1,103 @@
+use crate::app::AppState;
+
+pub struct Command {
+    pub name: &'static str,
+    pub shortcut: &'static str,
+    pub action: Box<dyn Fn(&mut AppState)>,
+}
+
+impl Command {
+    fn new(name: &'static str, shortcut: &'static str, action: fn(&mut AppState)) -> Self {
+        Command {
+            name,
+            shortcut,
+            action: Box::new(action),
+        }
+    }
+}
+
+/// Every command listed in the palette. Keyboard shortcuts call the same `AppState` methods.
+pub fn registry() -> Vec<Command> {
+    vec![
+        Command::new("Reset", "Space", AppState::reset_rom),
+        Command::new("Undo", "Ctrl+Z", AppState::undo),
+        Command::new("Redo", "Ctrl+Y", AppState::redo),
+        Command::new("Save state", "F5", AppState::save_state),
+        Command::new("Load state", "F9", AppState::load_state),
+        Command::new("Pause / resume", "P", AppState::toggle_pause),
+        Command::new("Speed up", "Ctrl+=", AppState::speed_up),
+        Command::new("Slow down", "Ctrl+-", AppState::slow_down),
+        Command::new("Toggle fullscreen", "F11", AppState::toggle_fullscreen),
+        Command::new("Mute / unmute", "M", AppState::toggle_mute),
+        Command::new("Invert colors", "Ctrl+I", AppState::toggle_inverted),
+        Command::new("Keypad HUD", "K", AppState::toggle_keypad_hud),
+        Command::new("Recent ROMs", "Ctrl+O", AppState::toggle_recent),
+        Command::new("Print disassembly", "Ctrl+D", AppState::print_disassembly),
+        Command::new("Quit", "Esc", AppState::quit),
+    ]
+}
+
+/// Commands whose name contains `query`, ignoring case.
+pub fn filter<'a>(commands: &'a [Command], query: &str) -> Vec<&'a Command> {
+    let query = query.to_lowercase();
+    commands
+        .iter()
+        .filter(|command| command.name.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// The palette overlay: the query, then the matching commands with `selected` marked.
+pub fn palette_lines(commands: &[Command], query: &str, selected: usize) -> Vec<String> {
+    let mut lines = vec![format!("> {}_", query), String::new()];
+    for (i, command) in filter(commands, query).iter().enumerate() {
+        let marker = if i == selected { '>' } else { ' ' };
+        lines.push(format!(
+            "{} {:<18} {}",
+            marker, command.name, command.shortcut
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names<'a>(commands: &[&'a Command]) -> Vec<&'a str> {
+        commands.iter().map(|command| command.name).collect()
+    }
+
+    #[test]
+    fn registry_has_at_least_ten_commands() {
+        let commands = registry();
+        assert!(commands.len() >= 10);
+        let mut unique = names(&commands.iter().collect::<Vec<_>>());
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), commands.len());
+    }
+
+    #[test]
+    fn filter_ignores_case() {
+        let commands = registry();
+        assert_eq!(names(&filter(&commands, "save")), ["Save state"]);
+        assert_eq!(names(&filter(&commands, "SAVE")), ["Save state"]);
+        assert_eq!(
+            names(&filter(&commands, "state")),
+            ["Save state", "Load state"]
+        );
+        assert_eq!(filter(&commands, "").len(), commands.len());
+        assert!(filter(&commands, "nothing like this").is_empty());
+    }
+
+    #[test]
+    fn palette_marks_the_selected_match() {
+        let commands = registry();
+        let lines = palette_lines(&commands, "state", 1);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "> state_");
+        assert!(lines[2].starts_with("  Save state"));
+        assert!(lines[3].starts_with("> Load state"));
+        assert!(lines[3].ends_with("F9"));
+    }
+}
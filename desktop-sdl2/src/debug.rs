@@ -0,0 +1,67 @@
+use std::io::{self, BufRead};
+
+use chip8_core::{Emulator, StepInfo};
+
+use crate::TICKS_PER_FRAME;
+use crate::app::RomSource;
+
+/// Single-steps `chip8` from the terminal without opening a window. Enter
+/// runs one instruction, `q` or end of input stops. The timers tick once
+/// every `TICKS_PER_FRAME` steps, as they would at normal speed.
+pub fn run(mut chip8: Emulator, rom: &RomSource) {
+    let loaded = match rom {
+        RomSource::File(path) => chip8.load_rom_from_file(path),
+        RomSource::Stdin(data) => chip8.load_rom_from_bytes(data),
+    };
+    match loaded {
+        Ok(info) => chip8.set_mode(info.detected_variant.mode()),
+        Err(err) => {
+            eprintln!("Unable to load {}: {}", rom, err);
+            return;
+        }
+    }
+
+    println!("Enter steps one instruction, q quits.");
+    let mut steps: u64 = 0;
+    for line in io::stdin().lock().lines() {
+        match line {
+            Ok(line) if line.trim() == "q" => break,
+            Ok(_) => (),
+            Err(_) => break,
+        }
+        match chip8.step() {
+            Ok(step) => print_step(&step),
+            Err(err) => {
+                crate::report_error("Stopped", &err, &chip8);
+                break;
+            }
+        }
+        steps += 1;
+        if steps.is_multiple_of(TICKS_PER_FRAME as u64) {
+            chip8.tick_timers();
+        }
+    }
+}
+
+fn print_step(step: &StepInfo) {
+    let Some(mnemonic) = &step.mnemonic else {
+        println!("{:#05X}  {:?}", step.pc_before, step.result);
+        return;
+    };
+    println!(
+        "{:#05X}  {:04X}  {:<16} -> {:#05X}",
+        step.pc_before, step.opcode, mnemonic, step.pc_after
+    );
+    for (idx, old, new) in &step.registers_changed {
+        println!("    V{:X}: {:#04X} -> {:#04X}", idx, old, new);
+    }
+    if step.draw_occurred {
+        println!("    screen redrawn");
+    }
+    if step.waiting_for_vblank {
+        println!("    waiting for vblank");
+    }
+    if let Some(key) = step.waiting_for_key_release {
+        println!("    waiting for key {:X} to be released", key);
+    }
+}
@@ -0,0 +1,89 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+const SPIN_DURATION: Duration = Duration::from_micros(500);
+
+/// The deadline one frame after `previous`. A loop that has fallen more than
+/// a frame behind starts again from `now` instead of catching up with a burst.
+pub fn next_deadline(previous: Instant, now: Instant) -> Instant {
+    let next = previous + FRAME_DURATION;
+    if next < now { now } else { next }
+}
+
+/// Whole milliseconds since `last_frame`, which moves forward by only that
+/// much so the rest carries into the next frame.
+pub fn take_elapsed_ms(last_frame: &mut Instant, now: Instant) -> u32 {
+    let elapsed_ms = now.saturating_duration_since(*last_frame).as_millis() as u32;
+    *last_frame += Duration::from_millis(elapsed_ms as u64);
+    elapsed_ms
+}
+
+/// Sleeps until `deadline`, spinning for the last stretch because
+/// `thread::sleep` tends to overshoot.
+pub fn wait_until(deadline: Instant) {
+    if let Some(sleep_for) = sleep_time(deadline, Instant::now()) {
+        thread::sleep(sleep_for);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+// nothing when the deadline has passed or is too close to risk a sleep
+fn sleep_time(deadline: Instant, now: Instant) -> Option<Duration> {
+    deadline
+        .checked_duration_since(now)?
+        .checked_sub(SPIN_DURATION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadlines_advance_by_one_frame() {
+        let start = Instant::now();
+        let next = next_deadline(start, start + Duration::from_millis(5));
+        assert_eq!(next, start + FRAME_DURATION);
+        assert_eq!(next_deadline(next, next), next + FRAME_DURATION);
+    }
+
+    #[test]
+    fn a_long_frame_resets_the_deadline() {
+        let start = Instant::now();
+        let late = start + Duration::from_millis(50);
+        assert_eq!(next_deadline(start, late), late);
+    }
+
+    #[test]
+    fn sleeps_all_but_the_spin() {
+        let now = Instant::now();
+        let deadline = now + Duration::from_millis(10);
+        assert_eq!(
+            sleep_time(deadline, now),
+            Some(Duration::from_millis(10) - SPIN_DURATION)
+        );
+        // too close to sleep, and already passed
+        assert_eq!(sleep_time(now + Duration::from_micros(100), now), None);
+        assert_eq!(sleep_time(now, deadline), None);
+    }
+
+    #[test]
+    fn partial_milliseconds_carry_over() {
+        let start = Instant::now();
+        let mut last_frame = start;
+        let ms = take_elapsed_ms(&mut last_frame, start + Duration::from_micros(16_700));
+        assert_eq!(ms, 16);
+        assert_eq!(last_frame, start + Duration::from_millis(16));
+        let ms = take_elapsed_ms(&mut last_frame, start + Duration::from_micros(33_400));
+        assert_eq!(ms, 17);
+    }
+
+    #[test]
+    fn wait_until_does_not_return_early() {
+        let deadline = Instant::now() + Duration::from_millis(2);
+        wait_until(deadline);
+        assert!(Instant::now() >= deadline);
+    }
+}
@@ -0,0 +1,87 @@
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+
+const ICON_SIZE: u32 = 32;
+
+// "CHIP8" in the 4x5 style of the built-in font, one glyph per entry with a
+// blank column between glyphs.
+const ICON_TEXT: [[u8; 5]; 5] = [
+    [0xF0, 0x80, 0x80, 0x80, 0xF0], // C
+    [0x90, 0x90, 0xF0, 0x90, 0x90], // H
+    [0xE0, 0x40, 0x40, 0x40, 0xE0], // I
+    [0xF0, 0x90, 0xF0, 0x80, 0x80], // P
+    [0xF0, 0x90, 0xF0, 0x90, 0xF0], // 8
+];
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 5;
+const GLYPH_SPACING: usize = 1;
+const TEXT_WIDTH: usize = ICON_TEXT.len() * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING;
+
+// RGBA8888 packs each pixel as a native-endian 0xRRGGBBAA word
+const ICON_FG: u32 = 0xFFFF_FFFF;
+const ICON_BG: u32 = 0x0000_00FF;
+
+/// Renders the "CHIP8" logo, scaled up to a 32x32 RGBA8888 surface with nearest-neighbour sampling.
+pub fn window_icon() -> Surface<'static> {
+    let mut surface = Surface::new(ICON_SIZE, ICON_SIZE, PixelFormatEnum::RGBA8888).unwrap();
+    let pitch = surface.pitch() as usize;
+    let size = ICON_SIZE as usize;
+    let colors = icon_pixels();
+
+    surface.with_lock_mut(|pixels| {
+        for (y, row) in colors.chunks_exact(size).enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                let offset = y * pitch + x * 4;
+                pixels[offset..offset + 4].copy_from_slice(&color.to_ne_bytes());
+            }
+        }
+    });
+    surface
+}
+
+/// The icon as row-major RGBA8888 words, `ICON_SIZE` pixels on a side.
+fn icon_pixels() -> Vec<u32> {
+    let size = ICON_SIZE as usize;
+    (0..size * size)
+        .map(|i| {
+            let (x, y) = (i % size, i / size);
+            if text_pixel(x * TEXT_WIDTH / size, y * GLYPH_HEIGHT / size) {
+                ICON_FG
+            } else {
+                ICON_BG
+            }
+        })
+        .collect()
+}
+
+fn text_pixel(x: usize, y: usize) -> bool {
+    let glyph = x / (GLYPH_WIDTH + GLYPH_SPACING);
+    let column = x % (GLYPH_WIDTH + GLYPH_SPACING);
+    column < GLYPH_WIDTH && ICON_TEXT[glyph][y] & (0b1000_0000 >> column) != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_is_32x32_in_two_colors() {
+        let pixels = icon_pixels();
+        assert_eq!(pixels.len(), 32 * 32);
+        assert!(pixels.iter().all(|&p| p == ICON_FG || p == ICON_BG));
+        assert!(pixels.contains(&ICON_FG));
+        // opaque in RGBA8888
+        assert!(pixels.iter().all(|&p| p & 0xFF == 0xFF));
+    }
+
+    #[test]
+    fn icon_samples_the_text() {
+        let pixels = icon_pixels();
+        // the top left of the C is lit, the gap after it isn't
+        assert_eq!(pixels[0], ICON_FG);
+        let gap_x = (GLYPH_WIDTH * 32).div_ceil(TEXT_WIDTH);
+        assert_eq!(pixels[gap_x], ICON_BG);
+        // the middle of the C is hollow
+        assert_eq!(pixels[16 * 32 + 3], ICON_BG);
+    }
+}
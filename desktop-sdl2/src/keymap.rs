@@ -0,0 +1,91 @@
+use sdl2::keyboard::Keycode;
+
+/// The hex keypad as laid out on the COSMAC VIP, row by row.
+pub const KEYPAD_LAYOUT: [[usize; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+/// Maps the left hand block of the keyboard (1234/QWER/ASDF/ZXCV) onto the hex keypad.
+pub fn key2btn(key: Keycode) -> Option<usize> {
+    match key {
+        Keycode::NUM_1 => Some(0x1),
+        Keycode::NUM_2 => Some(0x2),
+        Keycode::NUM_3 => Some(0x3),
+        Keycode::NUM_4 => Some(0xC),
+        Keycode::Q => Some(0x4),
+        Keycode::W => Some(0x5),
+        Keycode::E => Some(0x6),
+        Keycode::R => Some(0xD),
+        Keycode::A => Some(0x7),
+        Keycode::S => Some(0x8),
+        Keycode::D => Some(0x9),
+        Keycode::F => Some(0xE),
+        Keycode::Z => Some(0xA),
+        Keycode::X => Some(0x0),
+        Keycode::C => Some(0xB),
+        Keycode::V => Some(0xF),
+        _ => None,
+    }
+}
+
+/// The physical key for a CHIP-8 key, the inverse of `key2btn`.
+pub fn key2btn_inverse(chip8_key: usize) -> Option<Keycode> {
+    match chip8_key {
+        0x1 => Some(Keycode::NUM_1),
+        0x2 => Some(Keycode::NUM_2),
+        0x3 => Some(Keycode::NUM_3),
+        0xC => Some(Keycode::NUM_4),
+        0x4 => Some(Keycode::Q),
+        0x5 => Some(Keycode::W),
+        0x6 => Some(Keycode::E),
+        0xD => Some(Keycode::R),
+        0x7 => Some(Keycode::A),
+        0x8 => Some(Keycode::S),
+        0x9 => Some(Keycode::D),
+        0xE => Some(Keycode::F),
+        0xA => Some(Keycode::Z),
+        0x0 => Some(Keycode::X),
+        0xB => Some(Keycode::C),
+        0xF => Some(Keycode::V),
+        _ => None,
+    }
+}
+
+/// A cell label like "C:4", the CHIP-8 key then the physical key.
+pub fn key_label(chip8_key: usize) -> String {
+    let physical = key2btn_inverse(chip8_key).map_or_else(|| "?".to_string(), |k| k.name());
+    format!("{:X}:{}", chip8_key, physical)
+}
+
+/// The keypad grid printed by `--show-keymap`.
+pub fn print_keymap() {
+    println!("CHIP-8 key:keyboard key");
+    for row in KEYPAD_LAYOUT {
+        let cells: Vec<String> = row.iter().map(|&k| key_label(k)).collect();
+        println!("  {}", cells.join("  "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_round_trips_every_key() {
+        for chip8_key in 0..16 {
+            let physical = key2btn_inverse(chip8_key).unwrap();
+            assert_eq!(key2btn(physical), Some(chip8_key));
+        }
+        assert_eq!(key2btn_inverse(16), None);
+    }
+
+    #[test]
+    fn layout_covers_every_key_once() {
+        let mut keys: Vec<usize> = KEYPAD_LAYOUT.iter().flatten().copied().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, (0..16).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,679 @@
+mod app;
+mod args;
+mod commands;
+mod debug;
+mod frame;
+mod icon;
+mod keymap;
+mod recent;
+mod recorder;
+mod replay;
+mod split;
+mod text;
+mod watch;
+
+use app::{AppState, Overlay, RomSource};
+use args::Args;
+use chip8_core::*;
+use clap::{CommandFactory, Parser};
+use commands::Command;
+use frame::{next_deadline, take_elapsed_ms, wait_until};
+use keymap::{KEYPAD_LAYOUT, key2btn};
+use recent::RecentRoms;
+use recorder::GifRecorder;
+use sdl2::audio::{AudioCallback, AudioSpecDesired, AudioStatus};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::io;
+use std::process;
+use std::time::Instant;
+use watch::RomWatcher;
+
+const SCALE: u32 = 15;
+const WINDOW_WIDTH: u32 = (SCREEN_WIDTH as u32) * SCALE;
+const WINDOW_HEIGHT: u32 = (SCREEN_HEIGHT as u32) * SCALE;
+const TICKS_PER_FRAME: u32 = 10;
+const HISTORY_DEPTH: usize = 1000;
+// ten seconds at 60 frames per second
+const REWIND_DEPTH: usize = 600;
+const TRACE_DEPTH: usize = 10;
+const BORDER_WIDTH: u32 = 4;
+const WINDOW_TITLE: &str = "Chip-8 Emulator";
+const OVERLAY_SCALE: u32 = 3;
+const OVERLAY_MARGIN: u32 = 30;
+const PALETTE_WIDTH: u32 = 400;
+const PALETTE_HEIGHT: u32 = 300;
+const HUD_CELL: u32 = 36;
+const HUD_TEXT_SCALE: u32 = 2;
+const DEFAULT_COLORS: Colors = Colors {
+    fg: Color::RGB(255, 255, 255),
+    bg: Color::RGB(0, 0, 0),
+};
+
+/// Colors for lit (`fg`) and unlit (`bg`) CHIP-8 pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Colors {
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Colors {
+    pub fn swapped(self) -> Self {
+        Colors {
+            fg: self.bg,
+            bg: self.fg,
+        }
+    }
+}
+
+struct SquareWave {
+    phase_inc: f32,
+    phase: f32,
+    volume: f32,
+}
+
+impl AudioCallback for SquareWave {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        // Generate a square wave
+        for x in out.iter_mut() {
+            *x = if self.phase <= 0.5 {
+                self.volume
+            } else {
+                -self.volume
+            };
+            self.phase = (self.phase + self.phase_inc) % 1.0;
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let quirks = args.quirks.unwrap_or_default();
+    let colors = Colors {
+        fg: args.fg_color.unwrap_or(DEFAULT_COLORS.fg),
+        bg: args.bg_color.unwrap_or(DEFAULT_COLORS.bg),
+    };
+    let rom = match &args.rom {
+        Some(path) if path.as_os_str() == "-" => match RomSource::from_reader(io::stdin().lock()) {
+            Ok(source) => Some(source),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                return;
+            }
+        },
+        Some(path) => Some(RomSource::File(path.clone())),
+        None => None,
+    };
+    let split_roms = args
+        .split_screen
+        .clone()
+        .map(|paths| [paths[0].clone(), paths[1].clone()]);
+
+    let recent = RecentRoms::default_path().map(RecentRoms::load);
+    // with no ROM argument, pick up where the last session left off
+    let rom = rom.or_else(|| {
+        let path = recent.as_ref()?.get_most_recent()?.path.clone();
+        Some(RomSource::File(path))
+    });
+    if rom.is_none() && split_roms.is_none() {
+        eprintln!("No ROM given and no recently played ROM to fall back on.\n");
+        Args::command().print_help().unwrap();
+        return;
+    }
+
+    if args.show_keymap {
+        keymap::print_keymap();
+    }
+
+    // replays run headless and report through the exit code
+    if let (Some(rom), Some(path)) = (&rom, &args.replay) {
+        process::exit(if replay::run(rom, path) { 0 } else { 1 });
+    }
+    if let (Some(rom), true) = (&rom, args.debug) {
+        debug::run(build_emulator(quirks, &args), rom);
+        return;
+    }
+
+    // Setup SDL
+    let sdl_context = sdl2::init().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+    let audio_subsystem = sdl_context.audio().unwrap();
+
+    let desired_spec = AudioSpecDesired {
+        freq: Some(44100),
+        channels: Some(1), // mono
+        samples: None,     // default sample size
+    };
+
+    // Set up the audio device
+    let device = audio_subsystem
+        .open_playback(None, &desired_spec, |spec| {
+            let frequency = 440.0; // A4 note frequency in Hz
+            let phase_inc = frequency / spec.freq as f32;
+
+            SquareWave {
+                phase_inc,
+                phase: 0.0,
+                volume: 0.25,
+            }
+        })
+        .unwrap();
+
+    // split-screen mode puts a second full-size screen to the right of the first
+    let window_width = if split_roms.is_some() {
+        2 * WINDOW_WIDTH
+    } else {
+        WINDOW_WIDTH
+    };
+    // drawing always happens at SCALE, SDL stretches it to the size picked with --scale
+    let scale = args.scale.unwrap_or(SCALE).max(1);
+    let window = video_subsystem
+        .window(
+            WINDOW_TITLE,
+            window_width / SCALE * scale,
+            WINDOW_HEIGHT / SCALE * scale,
+        )
+        .position_centered()
+        .opengl()
+        .build()
+        .unwrap();
+
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+    canvas
+        .set_logical_size(window_width, WINDOW_HEIGHT)
+        .unwrap();
+    canvas.window_mut().set_icon(icon::window_icon());
+    canvas.clear();
+    canvas.present();
+
+    let mut event_pump = sdl_context.event_pump().unwrap();
+
+    if let Some(paths) = split_roms {
+        split::run(canvas, &mut event_pump, &device, paths, &args, colors);
+        return;
+    }
+    let Some(rom) = rom else {
+        return;
+    };
+
+    let rom_data = match rom.read() {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("Unable to read {}: {}", rom, err);
+            return;
+        }
+    };
+    let mut chip8 = match Emulator::new_with_rom_and_quirks(&rom_data, desktop_quirks(quirks)) {
+        Ok(chip8) => chip8,
+        Err(err) => {
+            eprintln!("Unable to load {}: {}", rom, err);
+            return;
+        }
+    };
+    apply_run_args(&mut chip8, &args);
+    chip8.enable_history(HISTORY_DEPTH);
+
+    let commands = commands::registry();
+    let ticks_per_frame = args.speed.map_or(TICKS_PER_FRAME, |speed| {
+        ((TICKS_PER_FRAME as f32 * speed).round() as u32).max(1)
+    });
+    let mut app = AppState::new(chip8, canvas, rom, recent, ticks_per_frame);
+    app.compress_saves = args.compress_saves;
+    app.rewind_depth = args.rewind_depth.unwrap_or(REWIND_DEPTH);
+    app.muted = args.mute;
+    app.colors = colors;
+    if args.fullscreen {
+        app.toggle_fullscreen();
+    }
+    app.rom_loaded(&RomInfo::from_rom(&rom_data));
+
+    let mut recorder = match &args.record_gif {
+        Some(path) => match GifRecorder::create(path, colors.fg, colors.bg) {
+            Ok(recorder) => Some(recorder),
+            Err(err) => {
+                eprintln!("Unable to record to {}: {}", path.display(), err);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let mut watcher = match (&app.rom, args.watch) {
+        (RomSource::File(path), true) => match RomWatcher::new(path) {
+            Ok(watcher) => Some(watcher),
+            Err(err) => {
+                eprintln!("Unable to watch {}: {}", path.display(), err);
+                None
+            }
+        },
+        (RomSource::Stdin(_), true) => {
+            eprintln!("--watch needs a ROM file, ignoring it for stdin");
+            None
+        }
+        _ => None,
+    };
+
+    let mut last_frame = Instant::now();
+    let mut next_frame = last_frame;
+
+    'gameLoop: loop {
+        for evt in event_pump.poll_iter() {
+            handle_event(&mut app, &commands, evt);
+        }
+        if app.quit {
+            break 'gameLoop;
+        }
+        if watcher.as_mut().is_some_and(RomWatcher::poll) {
+            app.hot_reload();
+        }
+        app.update_status();
+
+        let running = !app.paused && matches!(app.overlay, Overlay::None);
+        // R is keypad D, so rewinding is on Backspace
+        let rewinding = event_pump
+            .keyboard_state()
+            .is_scancode_pressed(Scancode::Backspace);
+        if running && rewinding {
+            app.rewind();
+        } else if running {
+            app.record_rewind_frame();
+            for _ in 0..app.ticks_per_frame {
+                match app.chip8.tick() {
+                    TickResult::Halted if !app.reported_halt => {
+                        println!(
+                            "Watchdog limit reached after {} cycles",
+                            app.chip8.get_total_ticks()
+                        );
+                        app.reported_halt = true;
+                    }
+                    TickResult::Error(err) => {
+                        report_error("Emulation error", &err, &app.chip8);
+                        break 'gameLoop;
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        app.needs_redraw |= app.chip8.needs_redraw();
+
+        let beeping = app.chip8.sound_timer() > 0 && !app.muted && running;
+        match device.status() {
+            AudioStatus::Playing => {
+                if !beeping {
+                    device.pause();
+                }
+            }
+            AudioStatus::Paused | AudioStatus::Stopped => {
+                if beeping {
+                    device.resume();
+                }
+            }
+        }
+
+        if app
+            .chip8
+            .drain_warnings()
+            .iter()
+            .any(|w| matches!(w, ChipWarning::SelfModification(_)))
+            && !app.self_modified
+        {
+            app.self_modified = true;
+            app.needs_redraw = true;
+        }
+        if app.chip8.is_waiting_for_key() != app.waiting_for_key {
+            app.waiting_for_key = !app.waiting_for_key;
+            app.needs_redraw = true;
+        }
+
+        if app.needs_redraw {
+            draw_screen(&mut app, &commands);
+            app.needs_redraw = false;
+            app.chip8.mark_draw_complete();
+
+            if let Some(rec) = recorder.as_mut()
+                && let Err(err) =
+                    rec.add_frame(app.chip8.get_display(), app.chip8.get_screen_hash())
+            {
+                eprintln!("Stopped recording: {}", err);
+                recorder = None;
+            }
+        }
+        app.chip8.trigger_frame_interrupt();
+
+        next_frame = next_deadline(next_frame, Instant::now());
+        wait_until(next_frame);
+        let elapsed_ms = take_elapsed_ms(&mut last_frame, Instant::now());
+        if running {
+            app.chip8.tick_timers_with_dt(elapsed_ms);
+        }
+    }
+
+    if let Some(rec) = recorder
+        && let Err(err) = rec.finish()
+    {
+        eprintln!("Unable to finish the recording: {}", err);
+    }
+}
+
+fn build_emulator(quirks: QuirksConfig, args: &Args) -> Emulator {
+    let mut chip8 = Emulator::new_with_quirks(desktop_quirks(quirks));
+    apply_run_args(&mut chip8, args);
+    chip8
+}
+
+// the desktop always reports self-modifying ROMs
+fn desktop_quirks(quirks: QuirksConfig) -> QuirksConfig {
+    QuirksConfig {
+        detect_self_modification: true,
+        ..quirks
+    }
+}
+
+// --seed and --max-cycles, plus the trace printed with unknown opcode errors
+fn apply_run_args(chip8: &mut Emulator, args: &Args) {
+    chip8.enable_trace(TRACE_DEPTH);
+    if let Some(seed) = args.seed {
+        chip8.seed_rng(seed);
+    }
+    if let Some(limit) = args.max_cycles {
+        chip8.set_watchdog(limit);
+    }
+}
+
+// prints the error, then the call stack when the failing instruction is known
+fn report_error(what: &str, err: &ChipError, chip8: &Emulator) {
+    eprintln!("{}: {}", what, err);
+    if let Some(context) = std::error::Error::source(err) {
+        eprintln!("  {}", context);
+    }
+    // an unknown opcode usually means a bad jump, the trace shows where from
+    if let (ChipError::UnknownOpcode(_), Some(trace)) = (err.inner(), chip8.get_trace()) {
+        eprintln!("  last instructions:");
+        for &(pc, op) in trace.last(TRACE_DEPTH) {
+            eprintln!("    {:#05X}  {:04X}  {}", pc, op, chip8.decode_opcode(op));
+        }
+    }
+}
+
+fn handle_event(app: &mut AppState, commands: &[Command], evt: Event) {
+    match evt {
+        Event::Quit { .. } => app.quit(),
+        Event::KeyDown {
+            keycode: Some(key),
+            keymod,
+            repeat,
+            ..
+        } => {
+            let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+            match app.overlay {
+                // held CHIP-8 keys repeat on the emulator's own timing, not the OS's
+                Overlay::None if repeat && key2btn(key).is_some() => (),
+                Overlay::None => shortcut_key(app, key, ctrl),
+                Overlay::Recent => recent_key(app, key, ctrl),
+                Overlay::Palette { .. } => palette_key(app, commands, key),
+            }
+        }
+        Event::KeyUp {
+            keycode: Some(key), ..
+        } => {
+            if let Some(k) = key2btn(key) {
+                app.press_key(k, false);
+            }
+        }
+        Event::TextInput { text, .. } => {
+            if let Overlay::Palette { query, selected } = &mut app.overlay {
+                query.push_str(&text);
+                *selected = 0;
+                app.needs_redraw = true;
+            }
+        }
+        _ => (),
+    }
+}
+
+fn shortcut_key(app: &mut AppState, key: Keycode, ctrl: bool) {
+    if ctrl {
+        match key {
+            Keycode::Z => app.undo(),
+            Keycode::Y => app.redo(),
+            Keycode::O => app.toggle_recent(),
+            Keycode::P => app.open_palette(),
+            Keycode::D => app.print_disassembly(),
+            Keycode::I => app.toggle_inverted(),
+            Keycode::EQUALS | Keycode::PLUS | Keycode::KP_PLUS => app.speed_up(),
+            Keycode::MINUS | Keycode::KP_MINUS => app.slow_down(),
+            _ => (),
+        }
+        return;
+    }
+
+    if let Some(k) = key2btn(key) {
+        app.press_key(k, true);
+        return;
+    }
+    match key {
+        Keycode::Escape => app.quit(),
+        Keycode::Space => app.reset_rom(),
+        Keycode::F5 => app.save_state(),
+        Keycode::F9 => app.load_state(),
+        Keycode::F11 => app.toggle_fullscreen(),
+        Keycode::P => app.toggle_pause(),
+        Keycode::M => app.toggle_mute(),
+        Keycode::K => app.toggle_keypad_hud(),
+        _ => (),
+    }
+}
+
+// the picker takes the digit keys while it is open
+fn recent_key(app: &mut AppState, key: Keycode, ctrl: bool) {
+    match key {
+        Keycode::Escape => app.close_overlay(),
+        Keycode::O if ctrl => app.toggle_recent(),
+        _ => {
+            let picked = digit_index(key)
+                .and_then(|i| app.recent.as_ref()?.entries().get(i))
+                .map(|entry| entry.path.clone());
+            if let Some(path) = picked {
+                app.close_overlay();
+                app.switch_rom(RomSource::File(path));
+            }
+        }
+    }
+}
+
+// typed characters arrive separately as TextInput events
+fn palette_key(app: &mut AppState, commands: &[Command], key: Keycode) {
+    let Overlay::Palette { query, selected } = &mut app.overlay else {
+        return;
+    };
+    let matches = commands::filter(commands, query);
+
+    match key {
+        Keycode::Escape => app.close_overlay(),
+        Keycode::Backspace => {
+            query.pop();
+            *selected = 0;
+        }
+        Keycode::Up => *selected = selected.saturating_sub(1),
+        Keycode::Down => {
+            if *selected + 1 < matches.len() {
+                *selected += 1;
+            }
+        }
+        Keycode::Return | Keycode::KP_ENTER => {
+            if let Some(command) = matches.get(*selected) {
+                app.close_overlay();
+                (command.action)(app);
+            }
+        }
+        _ => return,
+    }
+    app.needs_redraw = true;
+}
+
+// 1-9 pick entries one to nine and 0 picks the tenth
+fn digit_index(key: Keycode) -> Option<usize> {
+    let digits = [
+        Keycode::NUM_1,
+        Keycode::NUM_2,
+        Keycode::NUM_3,
+        Keycode::NUM_4,
+        Keycode::NUM_5,
+        Keycode::NUM_6,
+        Keycode::NUM_7,
+        Keycode::NUM_8,
+        Keycode::NUM_9,
+        Keycode::NUM_0,
+    ];
+    digits.iter().position(|d| *d == key)
+}
+
+fn draw_screen(app: &mut AppState, commands: &[Command]) {
+    let canvas = &mut app.canvas;
+    let colors = if app.inverted {
+        app.colors.swapped()
+    } else {
+        app.colors
+    };
+    canvas.set_draw_color(colors.bg);
+    canvas.clear();
+
+    draw_display(canvas, &app.chip8, 0, colors);
+
+    // blue border while FX0A blocks, so waiting for input doesn't look like a hang
+    if app.waiting_for_key {
+        draw_border(canvas, Color::RGB(0, 128, 255));
+    }
+    // yellow border warns that the ROM has written over its own code
+    if app.self_modified {
+        draw_border(canvas, Color::RGB(255, 255, 0));
+    }
+
+    if app.keypad_hud {
+        draw_keypad_hud(canvas, &app.chip8);
+    }
+
+    match &app.overlay {
+        Overlay::None => (),
+        Overlay::Recent => {
+            let lines = app
+                .recent
+                .as_ref()
+                .map(RecentRoms::menu_lines)
+                .unwrap_or_default();
+            let panel = Rect::new(
+                OVERLAY_MARGIN as i32,
+                OVERLAY_MARGIN as i32,
+                WINDOW_WIDTH - 2 * OVERLAY_MARGIN,
+                WINDOW_HEIGHT - 2 * OVERLAY_MARGIN,
+            );
+            draw_overlay(canvas, panel, &lines);
+        }
+        Overlay::Palette { query, selected } => {
+            let lines = commands::palette_lines(commands, query, *selected);
+            let panel = Rect::new(
+                ((WINDOW_WIDTH - PALETTE_WIDTH) / 2) as i32,
+                ((WINDOW_HEIGHT - PALETTE_HEIGHT) / 2) as i32,
+                PALETTE_WIDTH,
+                PALETTE_HEIGHT,
+            );
+            draw_overlay(canvas, panel, &lines);
+        }
+    }
+
+    canvas.present();
+}
+
+/// Draws the lit pixels of a CHIP-8 display in `colors.fg`, `x_offset`
+/// window pixels from the left edge. The background is left to the caller.
+/// SUPER-CHIP high resolution displays fill the same area at half the scale.
+fn draw_display(canvas: &mut Canvas<Window>, chip8: &Emulator, x_offset: i32, colors: Colors) {
+    canvas.set_draw_color(colors.fg);
+    let width = chip8.screen_width() as u32;
+    // 15 doesn't halve evenly, so edges are rounded per pixel
+    let edge = |n: u32| n * WINDOW_WIDTH / width;
+
+    for (y, row) in chip8.get_display_2d().enumerate() {
+        for (x, &pixel) in row.iter().enumerate() {
+            if !pixel {
+                continue;
+            }
+            let (x, y) = (x as u32, y as u32);
+            // Draw a rectangle at (x, y) scaled up to the window.
+            let rect = Rect::new(
+                x_offset + edge(x) as i32,
+                edge(y) as i32,
+                edge(x + 1) - edge(x),
+                edge(y + 1) - edge(y),
+            );
+            canvas.fill_rect(rect).unwrap();
+        }
+    }
+}
+
+fn draw_border(canvas: &mut Canvas<Window>, color: Color) {
+    canvas.set_draw_color(color);
+    let far_x = (WINDOW_WIDTH - BORDER_WIDTH) as i32;
+    let far_y = (WINDOW_HEIGHT - BORDER_WIDTH) as i32;
+    let borders = [
+        Rect::new(0, 0, WINDOW_WIDTH, BORDER_WIDTH),
+        Rect::new(0, far_y, WINDOW_WIDTH, BORDER_WIDTH),
+        Rect::new(0, 0, BORDER_WIDTH, WINDOW_HEIGHT),
+        Rect::new(far_x, 0, BORDER_WIDTH, WINDOW_HEIGHT),
+    ];
+    canvas.fill_rects(&borders).unwrap();
+}
+
+/// The hex keypad in the top right corner, held keys filled in.
+fn draw_keypad_hud(canvas: &mut Canvas<Window>, chip8: &Emulator) {
+    let left = (WINDOW_WIDTH - 4 * HUD_CELL - OVERLAY_MARGIN) as i32;
+    let top = OVERLAY_MARGIN as i32;
+    let label_x = (HUD_CELL - 3 * text::GLYPH_ADVANCE * HUD_TEXT_SCALE) as i32 / 2;
+    let label_y = (HUD_CELL - text::GLYPH_HEIGHT * HUD_TEXT_SCALE) as i32 / 2;
+
+    for (row, keys) in KEYPAD_LAYOUT.iter().enumerate() {
+        for (col, &key) in keys.iter().enumerate() {
+            let x = left + (col as u32 * HUD_CELL) as i32;
+            let y = top + (row as u32 * HUD_CELL) as i32;
+            // one pixel gap between cells
+            let cell = Rect::new(x, y, HUD_CELL - 1, HUD_CELL - 1);
+            let pressed = chip8.is_key_pressed(key);
+            let (fill, ink) = if pressed {
+                (Color::RGB(255, 255, 255), Color::RGB(0, 0, 0))
+            } else {
+                (Color::RGB(32, 32, 32), Color::RGB(255, 255, 255))
+            };
+            canvas.set_draw_color(fill);
+            canvas.fill_rect(cell).unwrap();
+            canvas.set_draw_color(ink);
+            text::draw_text(
+                canvas,
+                &keymap::key_label(key),
+                x + label_x,
+                y + label_y,
+                HUD_TEXT_SCALE,
+            );
+        }
+    }
+}
+
+fn draw_overlay(canvas: &mut Canvas<Window>, panel: Rect, lines: &[String]) {
+    let line_height = (text::GLYPH_HEIGHT + 2) * OVERLAY_SCALE;
+    canvas.set_draw_color(Color::RGB(32, 32, 32));
+    canvas.fill_rect(panel).unwrap();
+
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    let padding = (2 * OVERLAY_SCALE) as i32;
+    let visible = (panel.height() - 2 * padding as u32) / line_height;
+    for (i, line) in lines.iter().take(visible as usize).enumerate() {
+        let y = panel.y() + padding + (i as u32 * line_height) as i32;
+        text::draw_text(canvas, line, panel.x() + padding, y, OVERLAY_SCALE);
+    }
+}
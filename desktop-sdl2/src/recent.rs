@@ -0,0 +1,169 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentRom {
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+/// Most recently loaded ROMs, newest first, persisted as a JSON array.
+pub struct RecentRoms {
+    file: PathBuf,
+    entries: Vec<RecentRom>,
+}
+
+impl RecentRoms {
+    /// `~/.chip8/recent.json`, or `None` if there is no home directory.
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        Some(PathBuf::from(home).join(".chip8").join("recent.json"))
+    }
+
+    /// Reads the list from `file`. A missing or unreadable file starts an empty list.
+    pub fn load(file: PathBuf) -> Self {
+        let entries = fs::read_to_string(&file)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let mut recent = RecentRoms { file, entries };
+        recent.refresh();
+        recent
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&self.entries).map_err(io::Error::other)?;
+        fs::write(&self.file, json)
+    }
+
+    /// Moves `path` to the front of the list, dropping the oldest entry past ten.
+    pub fn push(&mut self, path: &Path, sha256: &[u8; 32]) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.insert(
+            0,
+            RecentRom {
+                path,
+                sha256: sha256.iter().map(|b| format!("{:02x}", b)).collect(),
+            },
+        );
+        self.entries.truncate(MAX_RECENT);
+    }
+
+    /// Drops entries whose file no longer exists.
+    pub fn refresh(&mut self) {
+        self.entries.retain(|entry| entry.path.is_file());
+    }
+
+    pub fn get_most_recent(&self) -> Option<&RecentRom> {
+        self.entries.first()
+    }
+
+    pub fn entries(&self) -> &[RecentRom] {
+        &self.entries
+    }
+
+    /// The recent ROMs overlay, numbered for the 1-9 and 0 keys.
+    pub fn menu_lines(&self) -> Vec<String> {
+        let mut lines = vec!["Recent ROMs (Esc to close)".to_string(), String::new()];
+        if self.entries.is_empty() {
+            lines.push("No recent ROMs".to_string());
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            let name = entry.path.file_name().map_or_else(
+                || entry.path.display().to_string(),
+                |n| n.to_string_lossy().into_owned(),
+            );
+            // entry ten is picked with the 0 key
+            lines.push(format!("{} {}", (i + 1) % 10, name));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("chip8-recent-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rom(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, [0x12, 0x00]).unwrap();
+        path.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn newest_first_without_duplicates() {
+        let dir = scratch_dir("order");
+        let mut recent = RecentRoms::load(dir.join("recent.json"));
+        assert!(recent.get_most_recent().is_none());
+
+        let (a, b) = (rom(&dir, "a.ch8"), rom(&dir, "b.ch8"));
+        recent.push(&a, &[0; 32]);
+        recent.push(&b, &[1; 32]);
+        recent.push(&a, &[0; 32]);
+
+        let paths: Vec<_> = recent.entries().iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, [a.clone(), b]);
+        assert_eq!(recent.get_most_recent().unwrap().path, a);
+        assert_eq!(recent.get_most_recent().unwrap().sha256, "00".repeat(32));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_ten_newest() {
+        let dir = scratch_dir("limit");
+        let mut recent = RecentRoms::load(dir.join("recent.json"));
+        for i in 0..12 {
+            recent.push(&rom(&dir, &format!("{i}.ch8")), &[i; 32]);
+        }
+        assert_eq!(recent.entries().len(), MAX_RECENT);
+        assert_eq!(
+            recent.get_most_recent().unwrap().path,
+            dir.join("11.ch8").canonicalize().unwrap()
+        );
+
+        let lines = recent.menu_lines();
+        assert_eq!(lines[2], "1 11.ch8");
+        assert_eq!(lines[11], "0 2.ch8");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_files_are_dropped_on_load() {
+        let dir = scratch_dir("missing");
+        let file = dir.join("recent.json");
+        let (kept, gone) = (rom(&dir, "kept.ch8"), rom(&dir, "gone.ch8"));
+        let mut recent = RecentRoms::load(file.clone());
+        recent.push(&kept, &[0; 32]);
+        recent.push(&gone, &[1; 32]);
+        recent.save().unwrap();
+        fs::remove_file(&gone).unwrap();
+
+        let recent = RecentRoms::load(file);
+        assert_eq!(recent.entries().len(), 1);
+        assert_eq!(recent.get_most_recent().unwrap().path, kept);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn empty_list_says_so() {
+        let recent = RecentRoms::load(std::env::temp_dir().join("chip8-recent-none.json"));
+        assert_eq!(recent.menu_lines()[2], "No recent ROMs");
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::Instant;
+
+use chip8_core::{HIRES_HEIGHT, HIRES_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH};
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use sdl2::pixels::Color;
+
+// each CHIP-8 pixel becomes a GIF_SCALE x GIF_SCALE block, 64x32 is too small to watch
+const GIF_SCALE: usize = 4;
+// browsers slow down frames shorter than 2/100 s, so never write one
+const MIN_FRAME_DELAY: u16 = 2;
+
+/// Writes each drawn frame to an animated GIF, timed by when it was shown.
+pub struct GifRecorder {
+    encoder: Encoder<BufWriter<File>>,
+    // a frame is only written once the next one arrives and its duration is known
+    pending: Option<PendingFrame>,
+}
+
+struct PendingFrame {
+    pixels: Vec<u8>,
+    shown_at: Instant,
+    screen_hash: u64,
+}
+
+impl GifRecorder {
+    pub fn create(path: &Path, fg: Color, bg: Color) -> Result<Self, EncodingError> {
+        let file = BufWriter::new(File::create(path)?);
+        let palette = [bg.r, bg.g, bg.b, fg.r, fg.g, fg.b];
+        let width = (SCREEN_WIDTH * GIF_SCALE) as u16;
+        let height = (SCREEN_HEIGHT * GIF_SCALE) as u16;
+        let mut encoder = Encoder::new(file, width, height, &palette)?;
+        encoder.set_repeat(Repeat::Infinite)?;
+        Ok(GifRecorder {
+            encoder,
+            pending: None,
+        })
+    }
+
+    /// Queues `display` as the next frame. A frame with the same `screen_hash`
+    /// as the previous one just extends how long the previous one is shown.
+    /// SUPER-CHIP 128x64 displays are recorded at half the scale.
+    pub fn add_frame(&mut self, display: &[bool], screen_hash: u64) -> Result<(), EncodingError> {
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|frame| frame.screen_hash == screen_hash)
+        {
+            return Ok(());
+        }
+        let now = Instant::now();
+        self.write_pending(now)?;
+
+        let (width, scale) = if display.len() == HIRES_WIDTH * HIRES_HEIGHT {
+            (HIRES_WIDTH, GIF_SCALE / 2)
+        } else {
+            (SCREEN_WIDTH, GIF_SCALE)
+        };
+        let pixels = (0..SCREEN_HEIGHT * GIF_SCALE)
+            .flat_map(|y| (0..SCREEN_WIDTH * GIF_SCALE).map(move |x| (x / scale, y / scale)))
+            .map(|(x, y)| display[x + width * y] as u8)
+            .collect();
+        self.pending = Some(PendingFrame {
+            pixels,
+            shown_at: now,
+            screen_hash,
+        });
+        Ok(())
+    }
+
+    /// Writes the last frame and the GIF trailer.
+    pub fn finish(mut self) -> Result<(), EncodingError> {
+        self.write_pending(Instant::now())?;
+        self.encoder.into_inner()?;
+        Ok(())
+    }
+
+    fn write_pending(&mut self, now: Instant) -> Result<(), EncodingError> {
+        let Some(PendingFrame {
+            pixels, shown_at, ..
+        }) = self.pending.take()
+        else {
+            return Ok(());
+        };
+        let width = (SCREEN_WIDTH * GIF_SCALE) as u16;
+        let height = (SCREEN_HEIGHT * GIF_SCALE) as u16;
+        let mut frame = Frame::from_indexed_pixels(width, height, pixels, None);
+        let centis = now.duration_since(shown_at).as_millis() / 10;
+        frame.delay = centis.clamp(MIN_FRAME_DELAY as u128, u16::MAX as u128) as u16;
+        self.encoder.write_frame(&frame)
+    }
+}
@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+
+use chip8_core::{Emulator, ReplayFile};
+
+use crate::app::RomSource;
+
+/// Plays a replay file against `rom` without opening a window and reports
+/// the outcome on stdout/stderr. Returns whether every assertion passed.
+pub fn run(rom: &RomSource, replay_path: &Path) -> bool {
+    let replay: ReplayFile = match fs::read_to_string(replay_path)
+        .map_err(|err| err.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|err| err.to_string()))
+    {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("Unable to read {}: {}", replay_path.display(), err);
+            return false;
+        }
+    };
+
+    let mut chip8 = Emulator::new();
+    let loaded = match rom {
+        RomSource::File(path) => chip8.load_rom_from_file(path),
+        RomSource::Stdin(data) => chip8.load_rom_from_bytes(data),
+    };
+    match loaded {
+        Ok(info) => chip8.set_mode(info.detected_variant.mode()),
+        Err(err) => {
+            eprintln!("Unable to load {}: {}", rom, err);
+            return false;
+        }
+    }
+
+    match chip8.play_replay(&replay) {
+        Ok(_) => {
+            println!("Replay passed");
+            true
+        }
+        Err(err) => {
+            eprintln!("Replay failed: {}", err);
+            false
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chip8_core::{ChipError, Emulator, TickResult};
+use sdl2::EventPump;
+use sdl2::audio::{AudioDevice, AudioStatus};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::args::Args;
+use crate::frame::{next_deadline, take_elapsed_ms, wait_until};
+use crate::keymap::key2btn;
+use crate::{
+    Colors, SquareWave, TICKS_PER_FRAME, WINDOW_HEIGHT, WINDOW_WIDTH, build_emulator, draw_display,
+    report_error,
+};
+
+/// Runs two ROMs side by side with shared keyboard input, for comparing them.
+/// Space resets both and Escape quits. Undo, overlays, the palette, --speed
+/// and --record-gif are single-screen only.
+pub fn run(
+    mut canvas: Canvas<Window>,
+    event_pump: &mut EventPump,
+    device: &AudioDevice<SquareWave>,
+    paths: [PathBuf; 2],
+    args: &Args,
+    colors: Colors,
+) {
+    let quirks = args.quirks.unwrap_or_default();
+    let mut screens = [build_emulator(quirks, args), build_emulator(quirks, args)];
+    if args.mute {
+        device.pause();
+    }
+    if !load_all(&mut screens, &paths) {
+        return;
+    }
+
+    let mut last_frame = Instant::now();
+    let mut next_frame = last_frame;
+    let mut needs_redraw = true;
+
+    'splitLoop: loop {
+        for evt in event_pump.poll_iter() {
+            match evt {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'splitLoop,
+                Event::KeyDown {
+                    keycode: Some(Keycode::Space),
+                    ..
+                } => {
+                    if !load_all(&mut screens, &paths) {
+                        break 'splitLoop;
+                    }
+                    needs_redraw = true;
+                }
+                Event::KeyDown {
+                    keycode: Some(key), ..
+                } => {
+                    if let Some(k) = key2btn(key) {
+                        press_all(&mut screens, k, true);
+                    }
+                }
+                Event::KeyUp {
+                    keycode: Some(key), ..
+                } => {
+                    if let Some(k) = key2btn(key) {
+                        press_all(&mut screens, k, false);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Err((idx, err)) = run_frame(&mut screens) {
+            report_error(
+                &format!("Emulation error in {}", paths[idx].display()),
+                &err,
+                &screens[idx],
+            );
+            break 'splitLoop;
+        }
+
+        needs_redraw |= screens.iter().any(Emulator::needs_redraw);
+
+        let beeping = !args.mute && screens.iter().any(|chip8| chip8.sound_timer() > 0);
+        match device.status() {
+            AudioStatus::Playing if !beeping => device.pause(),
+            AudioStatus::Paused | AudioStatus::Stopped if beeping => device.resume(),
+            _ => (),
+        }
+
+        if needs_redraw {
+            canvas.set_draw_color(colors.bg);
+            canvas.clear();
+            draw_display(&mut canvas, &screens[0], 0, colors);
+            draw_display(&mut canvas, &screens[1], WINDOW_WIDTH as i32, colors);
+            canvas.set_draw_color(Color::RGB(128, 128, 128));
+            canvas
+                .fill_rect(Rect::new(WINDOW_WIDTH as i32, 0, 1, WINDOW_HEIGHT))
+                .unwrap();
+            canvas.present();
+            needs_redraw = false;
+            screens.iter_mut().for_each(Emulator::mark_draw_complete);
+        }
+
+        next_frame = next_deadline(next_frame, Instant::now());
+        wait_until(next_frame);
+        let elapsed_ms = take_elapsed_ms(&mut last_frame, Instant::now());
+        for chip8 in screens.iter_mut() {
+            chip8.trigger_frame_interrupt();
+            chip8.tick_timers_with_dt(elapsed_ms);
+        }
+    }
+}
+
+// both machines get the same number of ticks, stopping at the first error
+// with the index of the machine that hit it
+fn run_frame(screens: &mut [Emulator; 2]) -> Result<(), (usize, ChipError)> {
+    for (idx, chip8) in screens.iter_mut().enumerate() {
+        for _ in 0..TICKS_PER_FRAME {
+            if let TickResult::Error(err) = chip8.tick() {
+                return Err((idx, err));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn press_all(screens: &mut [Emulator; 2], key: usize, pressed: bool) {
+    screens
+        .iter_mut()
+        .for_each(|chip8| chip8.keypress(key, pressed));
+}
+
+// resets both machines and loads each ROM, reporting the first failure
+fn load_all(screens: &mut [Emulator; 2], paths: &[PathBuf; 2]) -> bool {
+    for (chip8, path) in screens.iter_mut().zip(paths) {
+        chip8.reset();
+        match chip8.load_rom_from_file(path) {
+            Ok(info) => chip8.set_mode(info.detected_variant.mode()),
+            Err(err) => {
+                eprintln!("Unable to load {}: {}", path.display(), err);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use chip8_core::{QuirksConfig, roms};
+
+    use super::*;
+
+    fn pair(roms: [&[u8]; 2]) -> [Emulator; 2] {
+        roms.map(|rom| {
+            let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+            chip8.load_rom(rom).unwrap();
+            chip8
+        })
+    }
+
+    #[test]
+    fn both_machines_tick_equally_with_their_own_screens() {
+        let mut screens = pair([roms::COUNTER_ROM, roms::DRAW_ROM]);
+        // four frames stop DRAW_ROM just after a draw
+        for _ in 0..4 {
+            run_frame(&mut screens).unwrap();
+        }
+        let ticks = screens
+            .each_ref()
+            .map(|chip8| chip8.execution_summary().total_ticks);
+        assert_eq!(ticks, [4 * TICKS_PER_FRAME as u64; 2]);
+        assert!(screens[0].get_display().iter().all(|&pixel| !pixel));
+        assert!(screens[1].get_display().iter().any(|&pixel| pixel));
+    }
+
+    #[test]
+    fn keys_go_to_both_machines() {
+        let mut screens = pair([roms::COUNTER_ROM, roms::COUNTER_ROM]);
+        press_all(&mut screens, 0xA, true);
+        assert!(screens.iter().all(|chip8| chip8.is_key_pressed(0xA)));
+        press_all(&mut screens, 0xA, false);
+        assert!(screens.iter().all(|chip8| !chip8.is_key_pressed(0xA)));
+    }
+
+    #[test]
+    fn errors_name_the_machine() {
+        // 5XYN is only defined for N = 0
+        let mut screens = pair([roms::COUNTER_ROM, &[0x50, 0x01]]);
+        let (idx, err) = run_frame(&mut screens).unwrap_err();
+        assert_eq!(idx, 1);
+        assert_eq!(err.inner(), &ChipError::UnknownOpcode(0x5001));
+    }
+}
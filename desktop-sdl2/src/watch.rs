@@ -0,0 +1,122 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+// editors often write a file in several steps, wait for them to settle
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches a ROM file for changes made by an assembler or editor.
+pub struct RomWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    file_name: PathBuf,
+    debounce: Debounce,
+}
+
+impl RomWatcher {
+    pub fn new(path: &Path) -> notify::Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        // watch the directory so saves that replace the file by renaming are still seen
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        Ok(RomWatcher {
+            _watcher: watcher,
+            events,
+            file_name: path.file_name().map(PathBuf::from).unwrap_or_default(),
+            debounce: Debounce::default(),
+        })
+    }
+
+    /// Returns true once the file has changed and then been quiet for the debounce period.
+    pub fn poll(&mut self) -> bool {
+        let now = Instant::now();
+        for event in self.events.try_iter().flatten() {
+            if changes_file(&event, self.file_name.as_os_str()) {
+                self.debounce.changed(now);
+            }
+        }
+        self.debounce.settled(now)
+    }
+}
+
+// writes and creates count, including a rename that lands on the file
+fn changes_file(event: &Event, file_name: &OsStr) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|p| p.file_name() == Some(file_name))
+}
+
+/// Waits for a burst of changes to end before reporting it, once.
+#[derive(Debug, Default)]
+struct Debounce {
+    changed_at: Option<Instant>,
+}
+
+impl Debounce {
+    fn changed(&mut self, now: Instant) {
+        self.changed_at = Some(now);
+    }
+
+    fn settled(&mut self, now: Instant) -> bool {
+        match self.changed_at {
+            Some(at) if now.saturating_duration_since(at) >= DEBOUNCE => {
+                self.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+    use super::*;
+
+    #[test]
+    fn reloads_once_the_changes_settle() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut debounce = Debounce::default();
+        assert!(!debounce.settled(ms(1000)));
+
+        debounce.changed(start);
+        assert!(!debounce.settled(ms(150)));
+        // another write restarts the wait
+        debounce.changed(ms(150));
+        assert!(!debounce.settled(ms(300)));
+        assert!(debounce.settled(ms(350)));
+        // and it fires only once per burst
+        assert!(!debounce.settled(ms(1000)));
+    }
+
+    #[test]
+    fn only_writes_to_the_rom_count() {
+        let rom = OsStr::new("game.ch8");
+        let event = |kind| Event::new(kind).add_path(PathBuf::from("roms/game.ch8"));
+
+        assert!(changes_file(
+            &event(EventKind::Modify(ModifyKind::Any)),
+            rom
+        ));
+        assert!(changes_file(
+            &event(EventKind::Create(CreateKind::File)),
+            rom
+        ));
+        assert!(!changes_file(
+            &event(EventKind::Remove(RemoveKind::File)),
+            rom
+        ));
+        let other = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("roms/other.ch8"));
+        assert!(!changes_file(&other, rom));
+    }
+}
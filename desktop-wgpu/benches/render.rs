@@ -0,0 +1,101 @@
+//! One frame of the CHIP-8 screen drawn at 960x480, scaled on the CPU into
+//! an RGBA buffer versus uploaded as one byte per pixel and scaled on the
+//! GPU. The GPU side draws into an offscreen texture and waits for the
+//! queue, so both numbers cover a finished frame but not presenting it.
+
+use std::hint::black_box;
+
+use chip8_core::{Emulator, QuirksConfig, roms};
+use criterion::{Criterion, criterion_group, criterion_main};
+use desktop_wgpu::renderer::{DEFAULT_COLORS, Renderer};
+
+const TARGET_WIDTH: u32 = 960;
+const TARGET_HEIGHT: u32 = 480;
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+// a screen with something on it, nothing ticks the timers so no display wait
+fn drawn_screen() -> Emulator {
+    let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+    chip8.load_rom(roms::DRAW_ROM).unwrap();
+    for _ in 0..100 {
+        chip8.tick();
+    }
+    chip8
+}
+
+// the CPU path, nearest neighbour scaling into RGBA8888 pixels
+fn cpu_frame(chip8: &Emulator, frame: &mut [u32]) {
+    let width = chip8.screen_width();
+    let scale = TARGET_WIDTH as usize / width;
+    let [fg, bg] =
+        [DEFAULT_COLORS.fg, DEFAULT_COLORS.bg].map(|[r, g, b]| u32::from_be_bytes([r, g, b, 0xFF]));
+    for (y, row) in frame.chunks_exact_mut(TARGET_WIDTH as usize).enumerate() {
+        let pixels = &chip8.get_display()[(y / scale) * width..][..width];
+        for (x, out) in row.iter_mut().enumerate() {
+            *out = if pixels[x / scale] { fg } else { bg };
+        }
+    }
+}
+
+fn bench_cpu(c: &mut Criterion) {
+    let chip8 = drawn_screen();
+    let mut frame = vec![0; (TARGET_WIDTH * TARGET_HEIGHT) as usize];
+    c.bench_function("render cpu 960x480", |b| {
+        b.iter(|| {
+            cpu_frame(&chip8, &mut frame);
+            black_box(&frame);
+        })
+    });
+}
+
+fn bench_gpu(c: &mut Criterion) {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+    let adapter =
+        match pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        {
+            Ok(adapter) => adapter,
+            Err(err) => {
+                eprintln!("Skipping the GPU benchmark: {}", err);
+                return;
+            }
+        };
+    eprintln!("GPU benchmark on {}", adapter.get_info().name);
+    let (device, queue) =
+        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).unwrap();
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("benchmark target"),
+        size: wgpu::Extent3d {
+            width: TARGET_WIDTH,
+            height: TARGET_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut renderer = Renderer::new(&device, &queue, TARGET_FORMAT, DEFAULT_COLORS);
+    let chip8 = drawn_screen();
+
+    c.bench_function("render gpu 960x480", |b| {
+        b.iter(|| {
+            renderer.upload(&device, &queue, &chip8);
+            let mut encoder =
+                device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            renderer.draw(&mut encoder, &view, (TARGET_WIDTH, TARGET_HEIGHT));
+            queue.submit([encoder.finish()]);
+            device
+                .poll(wgpu::PollType::Wait {
+                    submission_index: None,
+                    timeout: None,
+                })
+                .unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, bench_cpu, bench_gpu);
+criterion_main!(benches);
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chip8_core::{Emulator, TickResult};
+use desktop_wgpu::renderer::Colors;
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Window, WindowId};
+
+use crate::audio::Beeper;
+use crate::gpu::Gpu;
+use crate::keymap::key2btn;
+
+const WINDOW_TITLE: &str = "CHIP-8";
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// The emulator and everything the event loop needs to drive it. The window
+/// only exists once the loop has resumed, so it starts out as `None`.
+pub struct App {
+    chip8: Emulator,
+    ticks_per_frame: u32,
+    window_size: PhysicalSize<u32>,
+    colors: Colors,
+    muted: bool,
+    gpu: Option<Gpu>,
+    beeper: Option<Beeper>,
+    next_frame: Instant,
+    reported_halt: bool,
+}
+
+impl App {
+    pub fn new(
+        chip8: Emulator,
+        ticks_per_frame: u32,
+        window_size: PhysicalSize<u32>,
+        colors: Colors,
+        muted: bool,
+    ) -> Self {
+        let beeper = match Beeper::open() {
+            Ok(beeper) => Some(beeper),
+            Err(err) => {
+                eprintln!("No sound: {}", err);
+                None
+            }
+        };
+        App {
+            chip8,
+            ticks_per_frame,
+            window_size,
+            colors,
+            muted,
+            gpu: None,
+            beeper,
+            next_frame: Instant::now(),
+            reported_halt: false,
+        }
+    }
+
+    // one 60 Hz frame of instructions, then the timers and the vblank interrupt
+    fn run_frame(&mut self, event_loop: &ActiveEventLoop) {
+        for _ in 0..self.ticks_per_frame {
+            match self.chip8.tick() {
+                TickResult::Halted if !self.reported_halt => {
+                    println!(
+                        "Watchdog limit reached after {} cycles",
+                        self.chip8.get_total_ticks()
+                    );
+                    self.reported_halt = true;
+                }
+                TickResult::Error(err) => {
+                    eprintln!("Emulation error: {}", err);
+                    event_loop.exit();
+                    return;
+                }
+                _ => (),
+            }
+        }
+        self.chip8.trigger_frame_interrupt();
+        self.chip8.tick_timers();
+
+        if let Some(beeper) = &self.beeper {
+            beeper.set_beeping(self.chip8.sound_timer() > 0 && !self.muted);
+        }
+        if let Some(gpu) = &self.gpu
+            && self.chip8.needs_redraw()
+        {
+            gpu.window.request_redraw();
+        }
+    }
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.gpu.is_some() {
+            return;
+        }
+        let attributes = Window::default_attributes()
+            .with_title(WINDOW_TITLE)
+            .with_inner_size(self.window_size);
+        let window = match event_loop.create_window(attributes) {
+            Ok(window) => Arc::new(window),
+            Err(err) => {
+                eprintln!("Unable to open a window: {}", err);
+                event_loop.exit();
+                return;
+            }
+        };
+        match Gpu::new(window, event_loop.owned_display_handle(), self.colors) {
+            Ok(gpu) => self.gpu = Some(gpu),
+            Err(err) => {
+                eprintln!("Unable to set up the GPU: {}", err);
+                event_loop.exit();
+            }
+        }
+        self.next_frame = Instant::now();
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(size) => {
+                if let Some(gpu) = &mut self.gpu {
+                    gpu.resize(size.width, size.height);
+                    gpu.window.request_redraw();
+                }
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let PhysicalKey::Code(code) = event.physical_key else {
+                    return;
+                };
+                let pressed = event.state == ElementState::Pressed;
+                if code == KeyCode::Escape && pressed {
+                    event_loop.exit();
+                } else if let Some(btn) = key2btn(code)
+                    && !event.repeat
+                {
+                    self.chip8.keypress(btn, pressed);
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                if let Some(gpu) = &mut self.gpu {
+                    gpu.render(&self.chip8);
+                    self.chip8.mark_draw_complete();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        if self.gpu.is_some() && now >= self.next_frame {
+            self.run_frame(event_loop);
+            self.next_frame = next_deadline(self.next_frame, now);
+        }
+        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(beeper) = &self.beeper {
+            beeper.set_beeping(false);
+        }
+    }
+}
+
+/// The deadline one frame after `previous`. A loop that has fallen more than
+/// a frame behind starts again from `now` instead of catching up with a burst.
+fn next_deadline(previous: Instant, now: Instant) -> Instant {
+    let next = previous + FRAME_DURATION;
+    if next < now { now } else { next }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadlines_are_a_frame_apart_unless_behind() {
+        let start = Instant::now();
+        assert_eq!(next_deadline(start, start), start + FRAME_DURATION);
+        let late = start + 3 * FRAME_DURATION;
+        assert_eq!(next_deadline(start, late), late);
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use chip8_core::QuirksConfig;
+use clap::Parser;
+
+/// A CHIP-8 emulator drawing through wgpu.
+///
+/// Keys 1234/QWER/ASDF/ZXCV are the hex keypad, Escape quits.
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Args {
+    /// ROM to run
+    pub rom: PathBuf,
+
+    /// Window pixels per CHIP-8 pixel [default: 15]
+    #[arg(long)]
+    pub scale: Option<u32>,
+
+    /// Emulation speed as a multiple of 600 instructions per second
+    #[arg(long, value_parser = parse_speed)]
+    pub speed: Option<f32>,
+
+    /// Comma separated quirks or presets, e.g. chip48,clip-sprites
+    #[arg(long)]
+    pub quirks: Option<QuirksConfig>,
+
+    /// Start with the sound muted
+    #[arg(long)]
+    pub mute: bool,
+
+    /// Color of lit pixels as RRGGBB hex [default: FFFFFF]
+    #[arg(long, value_parser = parse_color)]
+    pub fg_color: Option<[u8; 3]>,
+
+    /// Color of unlit pixels as RRGGBB hex [default: 000000]
+    #[arg(long, value_parser = parse_color)]
+    pub bg_color: Option<[u8; 3]>,
+}
+
+fn parse_speed(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err("expected a positive number such as 0.5 or 2".to_string()),
+    }
+}
+
+// accepts RRGGBB with or without a leading #
+fn parse_color(s: &str) -> Result<[u8; 3], String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 {
+        return Err("expected a color like FF6600".to_string());
+    }
+    let rgb = u32::from_str_radix(hex, 16).map_err(|_| "expected a color like FF6600")?;
+    Ok([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+}
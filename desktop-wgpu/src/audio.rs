@@ -0,0 +1,81 @@
+#[cfg(feature = "audio")]
+use std::sync::Arc;
+#[cfg(feature = "audio")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "audio")]
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+#[cfg(feature = "audio")]
+const FREQUENCY: f32 = 440.0; // A4 note frequency in Hz
+#[cfg(feature = "audio")]
+const VOLUME: f32 = 0.25;
+
+/// A 440 Hz square wave on the default output device, silent unless beeping.
+/// The stream runs the whole time and writes zeros while off, so switching
+/// is just a flag the audio thread reads.
+#[cfg(feature = "audio")]
+pub struct Beeper {
+    _stream: cpal::Stream,
+    beeping: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "audio")]
+impl Beeper {
+    pub fn open() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or("no audio output device")?;
+        let config = device
+            .default_output_config()
+            .map_err(|err| err.to_string())?
+            .config();
+        let channels = config.channels as usize;
+        let phase_inc = FREQUENCY / config.sample_rate as f32;
+
+        let beeping = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&beeping);
+        let mut phase = 0.0;
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |out: &mut [f32], _| {
+                    let on = flag.load(Ordering::Relaxed);
+                    for frame in out.chunks_mut(channels) {
+                        let sample = match (on, phase <= 0.5) {
+                            (false, _) => 0.0,
+                            (true, true) => VOLUME,
+                            (true, false) => -VOLUME,
+                        };
+                        frame.fill(sample);
+                        phase = (phase + phase_inc) % 1.0;
+                    }
+                },
+                |err| eprintln!("Audio error: {}", err),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+        stream.play().map_err(|err| err.to_string())?;
+        Ok(Beeper {
+            _stream: stream,
+            beeping,
+        })
+    }
+
+    pub fn set_beeping(&self, on: bool) {
+        self.beeping.store(on, Ordering::Relaxed);
+    }
+}
+
+/// Without the `audio` feature there is nothing to open.
+#[cfg(not(feature = "audio"))]
+pub struct Beeper;
+
+#[cfg(not(feature = "audio"))]
+impl Beeper {
+    pub fn open() -> Result<Self, String> {
+        Err("built without the audio feature".to_string())
+    }
+
+    pub fn set_beeping(&self, _on: bool) {}
+}
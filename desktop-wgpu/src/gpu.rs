@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use chip8_core::Emulator;
+use desktop_wgpu::renderer::{Colors, Renderer};
+use winit::event_loop::OwnedDisplayHandle;
+use winit::window::Window;
+
+/// The window's surface and the device drawing into it.
+pub struct Gpu {
+    pub window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    renderer: Renderer,
+}
+
+impl Gpu {
+    pub fn new(
+        window: Arc<Window>,
+        display: OwnedDisplayHandle,
+        colors: Colors,
+    ) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_with_display_handle(
+            Box::new(display),
+        ));
+        let surface = instance
+            .create_surface(Arc::clone(&window))
+            .map_err(|err| err.to_string())?;
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        }))
+        .map_err(|err| err.to_string())?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+                .map_err(|err| err.to_string())?;
+
+        let size = window.inner_size();
+        let mut config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or("the window surface is not supported by the GPU")?;
+        // the renderer writes colors as given, which only an sRGB surface would re-encode
+        let formats = surface.get_capabilities(&adapter).formats;
+        if let Some(&format) = formats.iter().find(|f| !f.is_srgb()) {
+            config.format = format;
+        }
+        surface.configure(&device, &config);
+        let renderer = Renderer::new(&device, &queue, config.format, colors);
+
+        Ok(Gpu {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            renderer,
+        })
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            // minimized, nothing to draw into
+            return;
+        }
+        self.config.width = width;
+        self.config.height = height;
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Uploads and draws the current screen. Frames the surface can't give
+    /// a texture for are dropped, the next draw tries again.
+    pub fn render(&mut self, chip8: &Emulator) {
+        let frame = match self.surface.get_current_texture() {
+            wgpu::CurrentSurfaceTexture::Success(frame)
+            | wgpu::CurrentSurfaceTexture::Suboptimal(frame) => frame,
+            wgpu::CurrentSurfaceTexture::Outdated | wgpu::CurrentSurfaceTexture::Lost => {
+                self.surface.configure(&self.device, &self.config);
+                return;
+            }
+            _ => return,
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.renderer.upload(&self.device, &self.queue, chip8);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        self.renderer
+            .draw(&mut encoder, &view, (self.config.width, self.config.height));
+        self.queue.submit([encoder.finish()]);
+        self.window.pre_present_notify();
+        self.queue.present(frame);
+    }
+}
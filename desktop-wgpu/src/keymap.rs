@@ -0,0 +1,60 @@
+use winit::keyboard::KeyCode;
+
+/// Maps the left hand block of the keyboard (1234/QWER/ASDF/ZXCV) onto the hex
+/// keypad. Physical keys, so the layout stays put on AZERTY and friends.
+pub fn key2btn(key: KeyCode) -> Option<usize> {
+    match key {
+        KeyCode::Digit1 => Some(0x1),
+        KeyCode::Digit2 => Some(0x2),
+        KeyCode::Digit3 => Some(0x3),
+        KeyCode::Digit4 => Some(0xC),
+        KeyCode::KeyQ => Some(0x4),
+        KeyCode::KeyW => Some(0x5),
+        KeyCode::KeyE => Some(0x6),
+        KeyCode::KeyR => Some(0xD),
+        KeyCode::KeyA => Some(0x7),
+        KeyCode::KeyS => Some(0x8),
+        KeyCode::KeyD => Some(0x9),
+        KeyCode::KeyF => Some(0xE),
+        KeyCode::KeyZ => Some(0xA),
+        KeyCode::KeyX => Some(0x0),
+        KeyCode::KeyC => Some(0xB),
+        KeyCode::KeyV => Some(0xF),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_keypad_key_has_one_keyboard_key() {
+        let keys = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::KeyQ,
+            KeyCode::KeyW,
+            KeyCode::KeyE,
+            KeyCode::KeyR,
+            KeyCode::KeyA,
+            KeyCode::KeyS,
+            KeyCode::KeyD,
+            KeyCode::KeyF,
+            KeyCode::KeyZ,
+            KeyCode::KeyX,
+            KeyCode::KeyC,
+            KeyCode::KeyV,
+        ];
+        let mut seen = [false; 16];
+        for key in keys {
+            let btn = key2btn(key).unwrap();
+            assert!(!seen[btn], "key {:X} mapped twice", btn);
+            seen[btn] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(key2btn(KeyCode::Escape), None);
+    }
+}
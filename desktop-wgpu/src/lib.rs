@@ -0,0 +1,3 @@
+//! The GPU renderer, a library so the render benchmark can drive it without a window.
+
+pub mod renderer;
@@ -0,0 +1,59 @@
+mod app;
+mod args;
+mod audio;
+mod gpu;
+mod keymap;
+
+use std::fs;
+
+use app::App;
+use args::Args;
+use chip8_core::{Emulator, SCREEN_HEIGHT, SCREEN_WIDTH};
+use clap::Parser;
+use desktop_wgpu::renderer::{Colors, DEFAULT_COLORS};
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+const SCALE: u32 = 15;
+const TICKS_PER_FRAME: u32 = 10;
+
+fn main() {
+    let args = Args::parse();
+    let rom_data = match fs::read(&args.rom) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("Unable to read {}: {}", args.rom.display(), err);
+            return;
+        }
+    };
+    let quirks = args.quirks.unwrap_or_default();
+    let chip8 = match Emulator::new_with_rom_and_quirks(&rom_data, quirks) {
+        Ok(chip8) => chip8,
+        Err(err) => {
+            eprintln!("Unable to load {}: {}", args.rom.display(), err);
+            return;
+        }
+    };
+
+    let colors = Colors {
+        fg: args.fg_color.unwrap_or(DEFAULT_COLORS.fg),
+        bg: args.bg_color.unwrap_or(DEFAULT_COLORS.bg),
+    };
+    let scale = args.scale.unwrap_or(SCALE).max(1);
+    let window_size = PhysicalSize::new(SCREEN_WIDTH as u32 * scale, SCREEN_HEIGHT as u32 * scale);
+    let ticks_per_frame = args.speed.map_or(TICKS_PER_FRAME, |speed| {
+        ((TICKS_PER_FRAME as f32 * speed).round() as u32).max(1)
+    });
+
+    let event_loop = match EventLoop::new() {
+        Ok(event_loop) => event_loop,
+        Err(err) => {
+            eprintln!("Unable to start the event loop: {}", err);
+            return;
+        }
+    };
+    let mut app = App::new(chip8, ticks_per_frame, window_size, colors, args.mute);
+    if let Err(err) = event_loop.run_app(&mut app) {
+        eprintln!("Event loop error: {}", err);
+    }
+}
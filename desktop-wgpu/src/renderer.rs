@@ -0,0 +1,335 @@
+use chip8_core::{Emulator, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const SHADER: &str = include_str!("screen.wgsl");
+
+/// Lit and unlit pixel colors as RGB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Colors {
+    pub fg: [u8; 3],
+    pub bg: [u8; 3],
+}
+
+pub const DEFAULT_COLORS: Colors = Colors {
+    fg: [0xFF, 0xFF, 0xFF],
+    bg: [0x00, 0x00, 0x00],
+};
+
+/// Draws the CHIP-8 screen as a quad sampling a texture with one byte per
+/// pixel, so each frame uploads `screen_width * screen_height` bytes and the
+/// GPU does the scaling and coloring.
+pub struct Renderer {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    colors: wgpu::Buffer,
+    clear: wgpu::Color,
+    screen: ScreenTexture,
+    texels: Vec<u8>,
+}
+
+// recreated when the resolution changes, the bind group points at the texture
+struct ScreenTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    width: u32,
+    height: u32,
+}
+
+impl Renderer {
+    /// A renderer drawing into targets of `format`, which should not be an
+    /// sRGB one since `colors` are passed to the shader as they are.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        colors: Colors,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("screen shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("screen bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("screen pipeline layout"),
+            bind_group_layouts: &[Some(&layout)],
+            immediate_size: 0,
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("screen pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(format.into())],
+            }),
+            multiview_mask: None,
+            cache: None,
+        });
+        // nearest so every CHIP-8 pixel stays a hard edged block
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("screen sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let colors_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("screen colors"),
+            size: COLORS_SIZE as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&colors_buffer, 0, &color_uniform(colors));
+
+        let screen = ScreenTexture::new(
+            device,
+            &layout,
+            &sampler,
+            &colors_buffer,
+            SCREEN_WIDTH as u32,
+            SCREEN_HEIGHT as u32,
+        );
+        Renderer {
+            pipeline,
+            layout,
+            sampler,
+            colors: colors_buffer,
+            clear: clear_color(colors),
+            screen,
+            texels: Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT),
+        }
+    }
+
+    /// Copies the emulator's screen into the texture, making a new one first
+    /// if the resolution changed since the last upload.
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, chip8: &Emulator) {
+        let width = chip8.screen_width() as u32;
+        let height = chip8.screen_height() as u32;
+        if (width, height) != (self.screen.width, self.screen.height) {
+            self.screen = ScreenTexture::new(
+                device,
+                &self.layout,
+                &self.sampler,
+                &self.colors,
+                width,
+                height,
+            );
+        }
+        fill_texels(chip8.get_display(), &mut self.texels);
+        queue.write_texture(
+            self.screen.texture.as_image_copy(),
+            &self.texels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width),
+                rows_per_image: Some(height),
+            },
+            self.screen.texture.size(),
+        );
+    }
+
+    /// Records drawing the last uploaded screen into `target`, which is
+    /// `target_size` pixels, letterboxed to keep the screen's 2:1 shape.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_size: (u32, u32),
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("screen pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+        let (x, y, width, height) = letterbox(target_size, (self.screen.width, self.screen.height));
+        if width > 0.0 && height > 0.0 {
+            pass.set_viewport(x, y, width, height, 0.0, 1.0);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.screen.bind_group, &[]);
+            pass.draw(0..4, 0..1);
+        }
+    }
+}
+
+impl ScreenTexture {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        colors: &wgpu::Buffer,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("screen texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("screen bind group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: colors.as_entire_binding(),
+                },
+            ],
+        });
+        ScreenTexture {
+            texture,
+            bind_group,
+            width,
+            height,
+        }
+    }
+}
+
+// two vec4<f32>, fg then bg
+const COLORS_SIZE: usize = 32;
+
+fn color_uniform(colors: Colors) -> [u8; COLORS_SIZE] {
+    let mut bytes = [0; COLORS_SIZE];
+    let channels = [colors.fg, colors.bg]
+        .into_iter()
+        .flat_map(|[r, g, b]| [r, g, b, 0xFF])
+        .map(|c| c as f32 / 255.0);
+    for (chunk, channel) in bytes.chunks_exact_mut(4).zip(channels) {
+        chunk.copy_from_slice(&channel.to_le_bytes());
+    }
+    bytes
+}
+
+// the bars around a letterboxed screen are the unlit color
+fn clear_color(colors: Colors) -> wgpu::Color {
+    let [r, g, b] = colors.bg.map(|c| c as f64 / 255.0);
+    wgpu::Color { r, g, b, a: 1.0 }
+}
+
+/// One texel per pixel, 0 when unlit and 255 when lit, reusing `texels`.
+pub fn fill_texels(display: &[bool], texels: &mut Vec<u8>) {
+    texels.clear();
+    texels.extend(display.iter().map(|&lit| if lit { 0xFF } else { 0x00 }));
+}
+
+/// The largest viewport with the screen's shape centered in the target, as
+/// `(x, y, width, height)`.
+pub fn letterbox(target: (u32, u32), screen: (u32, u32)) -> (f32, f32, f32, f32) {
+    let (target_w, target_h) = (target.0 as f32, target.1 as f32);
+    let scale = (target_w / screen.0 as f32).min(target_h / screen.1 as f32);
+    let (width, height) = (screen.0 as f32 * scale, screen.1 as f32 * scale);
+    (
+        (target_w - width) / 2.0,
+        (target_h - height) / 2.0,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texels_are_one_byte_per_pixel() {
+        let mut texels = vec![7; 3];
+        fill_texels(&[true, false, false, true], &mut texels);
+        assert_eq!(texels, [0xFF, 0x00, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn color_uniform_is_fg_then_bg() {
+        let bytes = color_uniform(Colors {
+            fg: [0xFF, 0x00, 0x33],
+            bg: [0x00, 0xFF, 0x00],
+        });
+        let floats: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        assert_eq!(floats, [1.0, 0.0, 0.2, 1.0, 0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn letterbox_centers_the_screen() {
+        // exact fit
+        assert_eq!(letterbox((960, 480), (64, 32)), (0.0, 0.0, 960.0, 480.0));
+        // too tall, bars above and below
+        assert_eq!(letterbox((640, 640), (64, 32)), (0.0, 160.0, 640.0, 320.0));
+        // too wide, bars left and right
+        assert_eq!(
+            letterbox((1000, 400), (128, 64)),
+            (100.0, 0.0, 800.0, 400.0)
+        );
+    }
+}
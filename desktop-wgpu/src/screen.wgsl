@@ -0,0 +1,32 @@
+// The CHIP-8 screen as one quad covering the viewport. Each texel of the
+// screen texture is 0 for an unlit pixel and 255 for a lit one.
+
+struct Colors {
+    fg: vec4<f32>,
+    bg: vec4<f32>,
+}
+
+@group(0) @binding(0) var screen: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+@group(0) @binding(2) var<uniform> colors: Colors;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// drawn as a 4 vertex triangle strip, top left first
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let lit = textureSample(screen, screen_sampler, in.uv).r;
+    return mix(colors.bg, colors.fg, lit);
+}
@@ -0,0 +1,110 @@
+//! Draws into an offscreen texture and reads it back. Skipped on machines
+//! without any GPU adapter, software ones such as llvmpipe count.
+
+use chip8_core::{Emulator, QuirksConfig, roms};
+use desktop_wgpu::renderer::{Colors, Renderer};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const COLORS: Colors = Colors {
+    fg: [0xFF, 0x66, 0x00],
+    bg: [0x00, 0x00, 0x33],
+};
+
+fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .ok()?;
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()
+}
+
+// RGBA rows of a `width` x `height` render of `chip8`
+fn render(chip8: &Emulator, width: u32, height: u32) -> Option<Vec<[u8; 4]>> {
+    let (device, queue) = device()?;
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    // rows of a texture to buffer copy must be a multiple of 256 bytes
+    assert_eq!(width * 4 % wgpu::COPY_BYTES_PER_ROW_ALIGNMENT, 0);
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (width * height * 4) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut renderer = Renderer::new(&device, &queue, FORMAT, COLORS);
+    renderer.upload(&device, &queue, chip8);
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer.draw(&mut encoder, &view, (width, height));
+    encoder.copy_texture_to_buffer(
+        target.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+        },
+        size,
+    );
+    queue.submit([encoder.finish()]);
+
+    readback.map_async(wgpu::MapMode::Read, .., |result| result.unwrap());
+    device
+        .poll(wgpu::PollType::Wait {
+            submission_index: None,
+            timeout: None,
+        })
+        .unwrap();
+    let bytes = readback.slice(..).get_mapped_range().unwrap();
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|px| px.try_into().unwrap())
+            .collect(),
+    )
+}
+
+fn rgba([r, g, b]: [u8; 3]) -> [u8; 4] {
+    [r, g, b, 0xFF]
+}
+
+#[test]
+fn screen_is_scaled_and_letterboxed() {
+    let mut chip8 = Emulator::new_with_quirks(QuirksConfig::chip48());
+    chip8.load_rom(roms::DRAW_ROM).unwrap();
+    for _ in 0..100 {
+        chip8.tick();
+    }
+    assert!(chip8.get_display().contains(&true));
+
+    // 64x32 doubled is 128x64, leaving 16 rows of bars above and below
+    let Some(pixels) = render(&chip8, 128, 96) else {
+        eprintln!("no GPU adapter, skipping");
+        return;
+    };
+    for (y, row) in pixels.chunks_exact(128).enumerate() {
+        for (x, &pixel) in row.iter().enumerate() {
+            let expected = match y.checked_sub(16).filter(|&sy| sy < 64) {
+                Some(sy) if chip8.get_display()[(sy / 2) * 64 + x / 2] => rgba(COLORS.fg),
+                _ => rgba(COLORS.bg),
+            };
+            assert_eq!(pixel, expected, "pixel ({}, {})", x, y);
+        }
+    }
+}